@@ -1,6 +1,7 @@
 use camino::Utf8PathBuf;
 use clap::Parser;
 
+use crate::commands::Check;
 use crate::message::{Message, MessageType};
 use oranda::config::Config;
 use oranda::errors::*;
@@ -8,30 +9,72 @@ use oranda::site::Site;
 
 #[derive(Debug, Parser)]
 pub struct Build {
+    /// The directory of the project to build the site for
     #[arg(long, default_value = "./")]
     project_root: Utf8PathBuf,
-    #[arg(long, default_value = "./oranda.json")]
-    config_path: Utf8PathBuf,
+    /// The path to the config file [default: <PROJECT_ROOT>/oranda.json or oranda.toml]
+    #[arg(long)]
+    config_path: Option<Utf8PathBuf>,
+    /// Don't make any network requests for release data
+    ///
+    /// Releases will only be loaded from `releases.snapshot`, if it's set.
+    #[arg(long)]
+    offline: bool,
+    /// Delete the dist dir and build everything from scratch
+    ///
+    /// By default, only files that changed since the last build are rewritten.
+    #[arg(long)]
+    clean: bool,
+    /// Check that the links on the built site go somewhere (see `oranda check`)
+    #[arg(long)]
+    check: bool,
+    /// Whether to make pages reload themselves when the site is rebuilt
+    #[arg(skip)]
+    live_reload: bool,
 }
 
 impl Build {
     pub fn new(project_root: Option<Utf8PathBuf>, config_path: Option<Utf8PathBuf>) -> Self {
         Build {
             project_root: project_root.unwrap_or(Utf8PathBuf::from("./")),
-            config_path: config_path.unwrap_or(Utf8PathBuf::from("./oranda.json")),
+            config_path,
+            offline: false,
+            clean: false,
+            check: false,
+            live_reload: false,
         }
     }
 
+    /// Build without making any network requests for release data
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Build pages that reload themselves when the site is rebuilt, for `oranda dev`
+    pub fn with_live_reload(mut self) -> Self {
+        self.live_reload = true;
+        self
+    }
+
     pub fn run(&self) -> Result<()> {
         Message::new(MessageType::Info, "Running build...").print();
         tracing::info!("Running build...");
-        let config = Config::build(&self.config_path)?;
+        let mut config = Config::build(&self.project_root, self.config_path.as_deref())?;
+        config.offline = self.offline;
+        config.live_reload = self.live_reload;
+        if self.clean {
+            Site::clean_dist_dir(&config.dist_dir)?;
+        }
         Site::build(&config)?.write(&config)?;
         let msg = format!(
             "Successfully built your site in the `{}` directory. To view, run `oranda serve`.",
-            { config.dist_dir }
+            config.dist_dir
         );
         Message::new(MessageType::Success, &msg).print();
+        if self.check {
+            Check::check_links(&config)?;
+        }
         Ok(())
     }
 }
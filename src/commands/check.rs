@@ -0,0 +1,43 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Parser;
+
+use crate::message::{Message, MessageType};
+use oranda::config::Config;
+use oranda::errors::*;
+use oranda::site::check;
+
+/// Check that the links and images on your built site go somewhere
+#[derive(Debug, Parser)]
+pub struct Check {
+    /// The directory of the project the site is for
+    #[arg(long, default_value = "./")]
+    project_root: Utf8PathBuf,
+    /// The path to the config file [default: <PROJECT_ROOT>/oranda.json or oranda.toml]
+    #[arg(long)]
+    config_path: Option<Utf8PathBuf>,
+}
+
+impl Check {
+    pub fn run(&self) -> Result<()> {
+        Message::new(MessageType::Info, "Running check...").print();
+        tracing::info!("Running check...");
+        let config = Config::build(&self.project_root, self.config_path.as_deref())?;
+        if !Utf8Path::new(&config.dist_dir).is_dir() {
+            return Err(OrandaError::BuildNotFound {
+                dist_dir: config.dist_dir.to_string(),
+            });
+        }
+        Self::check_links(&config)
+    }
+
+    /// Check the links on the site that was built with this config
+    pub fn check_links(config: &Config) -> Result<()> {
+        let msg = format!("Checking links in the `{}` directory...", config.dist_dir);
+        Message::new(MessageType::Info, &msg).print();
+        tracing::info!("{}", &msg);
+        let pages = check::run(config)?;
+        let msg = format!("Checked {pages} pages, and every link goes somewhere!");
+        Message::new(MessageType::Success, &msg).print();
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use camino::Utf8PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::message::{Message, MessageType};
+use oranda::config::Config;
+use oranda::errors::*;
+
+/// Work with your oranda config
+#[derive(Debug, Parser)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    command: ConfigSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigSubcommand {
+    Show(Show),
+    Validate(Validate),
+}
+
+impl ConfigCommand {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ConfigSubcommand::Show(cmd) => cmd.run(),
+            ConfigSubcommand::Validate(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// Print your config after merging it with everything else we know about your project,
+/// along with where each value came from
+#[derive(Debug, Parser)]
+pub struct Show {
+    /// The directory of the project the config is for
+    #[arg(long, default_value = "./")]
+    project_root: Utf8PathBuf,
+    /// The path to the config file [default: <PROJECT_ROOT>/oranda.json or oranda.toml]
+    #[arg(long)]
+    config_path: Option<Utf8PathBuf>,
+    /// The format to print the config in
+    #[arg(long, value_enum, default_value_t = ShowFormat::Toml)]
+    format: ShowFormat,
+}
+
+/// Formats we can print the config in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum ShowFormat {
+    /// TOML, with a comment after each value saying where it came from
+    Toml,
+    /// JSON, with each value replaced by an object with its `value` and `source`
+    Json,
+}
+
+impl Show {
+    pub fn run(&self) -> Result<()> {
+        let (config, provenance) =
+            Config::build_with_provenance(&self.project_root, self.config_path.as_deref())?;
+        let output = match self.format {
+            ShowFormat::Toml => provenance.annotated_toml(&config),
+            ShowFormat::Json => serde_json::to_string_pretty(&provenance.annotated_json(&config))?,
+        };
+        println!("{output}");
+        Ok(())
+    }
+}
+
+/// Check your config for mistakes, without building your site
+#[derive(Debug, Parser)]
+pub struct Validate {
+    /// The directory of the project the config is for
+    #[arg(long, default_value = "./")]
+    project_root: Utf8PathBuf,
+    /// The path to the config file [default: <PROJECT_ROOT>/oranda.json or oranda.toml]
+    #[arg(long)]
+    config_path: Option<Utf8PathBuf>,
+}
+
+impl Validate {
+    pub fn run(&self) -> Result<()> {
+        Message::new(MessageType::Info, "Validating config...").print();
+        tracing::info!("Validating config...");
+        Config::build(&self.project_root, self.config_path.as_deref())?;
+        Message::new(MessageType::Success, "Your config is valid!").print();
+        Ok(())
+    }
+}
@@ -1,23 +1,36 @@
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
 use std::thread::sleep;
 use std::time::Duration;
 
 use axoproject::WorkspaceSearch;
 use camino::Utf8PathBuf;
 use clap::Parser;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
 
 use crate::{
-    commands::{Build, Serve},
+    commands::{Build, LiveReload, Serve},
     message::{Message, MessageType},
 };
 use oranda::{
-    config::Config,
+    config::{resolve_path, Config},
     errors::*,
     site::mdbook::{custom_theme, load_mdbook, mdbook_dir},
 };
 
+/// Files that change which other files we need to watch
+const WATCH_SET_FILES: &[&str] = &["Cargo.toml", "package.json", "book.toml"];
+/// Where oranda looks for an mdbook when the config doesn't say
+const MDBOOK_CANDIDATES: &[&str] = &["./book.toml", "./book/book.toml", "./docs/book.toml"];
+/// How often to check whether paths we want to watch have been created
+const MISSING_PATHS_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Debug, Parser)]
 pub struct Dev {
+    /// The address for the file server to listen on
+    #[arg(long)]
+    host: Option<IpAddr>,
     /// The port for the file server to be launched on
     #[arg(long)]
     port: Option<u16>,
@@ -30,11 +43,25 @@ pub struct Dev {
     /// Skip the first build before starting to watch for changes
     #[arg(long)]
     no_first_build: bool,
+    /// Don't make any network requests for release data (see `oranda build --offline`)
+    #[arg(long)]
+    offline: bool,
     /// List of extra paths to watch
     #[arg(short, long)]
     include_paths: Option<Vec<Utf8PathBuf>>,
 }
 
+/// The paths we're watching for changes
+#[derive(Debug, Default)]
+struct WatchSet {
+    /// Paths the watcher is watching
+    watched: Vec<PathBuf>,
+    /// Paths we'd like to watch, but that don't exist (yet)
+    missing: Vec<PathBuf>,
+    /// The config files (including the ones they extend), which decide what else we watch
+    config_files: Vec<PathBuf>,
+}
+
 impl Dev {
     pub fn run(self) -> Result<()> {
         Message::new(
@@ -44,124 +71,47 @@ impl Dev {
         .print();
         tracing::info!("Starting dev, looking for paths to watch...");
 
-        let config = Config::build(
-            &self
-                .config_path
-                .clone()
-                .unwrap_or(Utf8PathBuf::from("./oranda.json")),
-        )?;
-        let mut paths_to_watch = vec![];
-        // Watch for the readme file
-        paths_to_watch.push(config.readme_path);
-        // Watch for the oranda config file
-        paths_to_watch.push(
-            self.config_path
-                .clone()
-                .unwrap_or(Utf8PathBuf::from("./oranda.json"))
-                .into(),
-        );
-
-        // Watch for any user-provided paths
-        if self.include_paths.is_some() {
-            let mut include_paths: Vec<String> = self
-                .include_paths
-                .unwrap()
-                .iter()
-                .map(|p| p.to_string())
-                .collect();
-            paths_to_watch.append(&mut include_paths);
-        }
-
-        // Watch for the funding.md page and the funding.yml file
-        if config.funding.is_some() {
-            paths_to_watch.push("funding.md".into());
-            paths_to_watch.push(".github/FUNDING.yml".into());
-        }
-
-        // Watch for additional pages, if we have any
-        if config.additional_pages.is_some() {
-            let mut additional_pages: Vec<String> =
-                config.additional_pages.unwrap().values().cloned().collect();
-            paths_to_watch.append(&mut additional_pages);
-        }
-
-        // Watch for the mdbook directory, if we have it
-        if let Some(book_cfg) = &config.mdbook {
-            let path = mdbook_dir(book_cfg)?;
-            let md = load_mdbook(&path)?;
-            // watch book.toml and /src/
-            paths_to_watch.push(md.root.join("book.toml").display().to_string());
-            paths_to_watch.push(md.source_dir().display().to_string());
-
-            // If we're not clobbering the theme, also watch the theme dir
-            // (note that this may not exist on the fs, mdbook reports the path regardless)
-            if custom_theme(book_cfg, &config.styles.theme()).is_none() {
-                paths_to_watch.push(md.theme_dir().display().to_string());
-            }
-        }
-
-        // Watch for any project manifest files
-        let project = axoproject::get_workspaces("./".into(), None);
-        if let WorkspaceSearch::Found(workspace) = project.rust {
-            paths_to_watch.push(workspace.manifest_path.into());
-        }
-        if let WorkspaceSearch::Found(workspace) = project.javascript {
-            paths_to_watch.push(workspace.manifest_path.into());
-        }
-
         let (tx, rx) = std::sync::mpsc::channel();
 
         // We debounce events so that we don't end up building 5 times in a row because 5 different
         // filesystem events fired.
         let mut debouncer = notify_debouncer_mini::new_debouncer(Duration::from_secs(1), None, tx)?;
-        let watcher = debouncer.watcher();
-        let mut existing_paths = vec![];
-        for path in paths_to_watch {
-            let path = PathBuf::from(path);
-            // If no path exists, oranda won't work anyways, so there's not much need to let the user know
-            // (they'll know either way ;) )
-            if path.exists() {
-                watcher.watch(
-                    path.as_path(),
-                    notify_debouncer_mini::notify::RecursiveMode::Recursive,
-                )?;
-                existing_paths.push(path.clone());
-            }
-        }
-
-        Message::new(
-            MessageType::Info,
-            &format!(
-                "Found {} paths to watch, starting watch...",
-                existing_paths.len()
-            ),
-        )
-        .print();
-        tracing::info!(
-            "Found {} paths to watch, starting watch...",
-            existing_paths.len()
-        );
-        Message::new(
-            MessageType::Debug,
-            &format!("Files watched: {:?}", existing_paths),
-        )
-        .print();
+        let mut watch_set = WatchSet::default();
+        self.update_watch_set(debouncer.watcher(), &mut watch_set);
 
+        let live_reload = LiveReload::new();
         if !self.no_first_build {
-            Build::new(self.project_root.clone(), self.config_path.clone()).run()?;
+            self.rebuild(&live_reload);
         }
 
         // Spawn the serve process out into a separate thread so that we can loop through received events on this thread
-        let _ = std::thread::spawn(move || Serve::new(self.port).run());
+        let receiver = live_reload.subscribe();
+        let serve = Serve::new(
+            self.project_root.clone(),
+            self.config_path.clone(),
+            self.host,
+            self.port,
+        )
+        .with_offline(self.offline);
+        let _ = std::thread::spawn(move || {
+            if let Err(e) = serve.run_with_live_reload(receiver) {
+                eprintln!("{:?}", miette::Report::new(e));
+            }
+        });
         loop {
-            // Wait for all debounced events to arrive
-            let first_event = rx.recv().unwrap();
+            // Wait for all debounced events to arrive, checking in on paths we're waiting
+            // to be created every so often
+            let first_event = match rx.recv_timeout(MISSING_PATHS_INTERVAL) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => panic!("file watcher stopped unexpectedly"),
+            };
             sleep(Duration::from_millis(50));
             let other_events = rx.try_iter();
 
-            let all_events = std::iter::once(first_event).chain(other_events);
+            let all_events = first_event.into_iter().chain(other_events);
             // Unpack events into paths
-            let paths: Vec<_> = all_events
+            let mut paths: Vec<_> = all_events
                 .filter_map(|event| match event {
                     Ok(events) => Some(events),
                     Err(errors) => {
@@ -179,6 +129,16 @@ impl Dev {
                 .flatten()
                 .map(|event| event.path)
                 .collect();
+            let created = watch_set
+                .missing
+                .iter()
+                .filter(|path| path.exists())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let watch_set_changed =
+                !created.is_empty() || paths.iter().any(|path| changes_watch_set(path, &watch_set));
+            paths.extend(created);
 
             if !paths.is_empty() {
                 Message::new(
@@ -187,10 +147,220 @@ impl Dev {
                 )
                 .print();
 
-                Build::new(self.project_root.clone(), self.config_path.clone())
-                    .run()
-                    .unwrap();
+                if watch_set_changed {
+                    self.update_watch_set(debouncer.watcher(), &mut watch_set);
+                }
+                self.rebuild(&live_reload);
             }
         }
     }
+
+    /// Rebuild the site, and tell any open pages how it went
+    ///
+    /// If the build fails, we report why and keep going, since the fix is probably on its way.
+    fn rebuild(&self, live_reload: &LiveReload) {
+        let result = Build::new(self.project_root.clone(), self.config_path.clone())
+            .with_offline(self.offline)
+            .with_live_reload()
+            .run();
+        live_reload.build_finished(&result);
+        if let Err(e) = result {
+            eprintln!("{:?}", miette::Report::new(e));
+        }
+    }
+
+    fn project_root(&self) -> Utf8PathBuf {
+        self.project_root.clone().unwrap_or(Utf8PathBuf::from("./"))
+    }
+
+    fn config_paths(&self) -> Vec<Utf8PathBuf> {
+        Config::file_paths(&self.project_root(), self.config_path.as_deref())
+    }
+
+    /// Work out which paths to watch from the current config, and start watching them
+    ///
+    /// If the config is broken, we keep watching what we were already watching (and the
+    /// config itself), so we notice once it's fixed.
+    fn update_watch_set(&self, watcher: &mut dyn Watcher, watch_set: &mut WatchSet) {
+        let mut config_files: Vec<PathBuf> =
+            self.config_paths().into_iter().map(PathBuf::from).collect();
+        let mut paths_to_watch =
+            match Config::build(&self.project_root(), self.config_path.as_deref()) {
+                Ok(config) => {
+                    config_files.extend(config.extended_configs.iter().map(PathBuf::from));
+                    self.paths_to_watch(&config)
+                }
+                // The build reports what's wrong with the config, so we don't need to here
+                Err(e) => {
+                    tracing::debug!("Couldn't load config to find paths to watch: {e}");
+                    // Until it's fixed, it still extends whatever it did before
+                    config_files.extend(watch_set.config_files.iter().cloned());
+                    let mut paths = watch_set.watched.clone();
+                    paths.extend(watch_set.missing.iter().cloned());
+                    paths.extend(config_files.iter().cloned());
+                    paths
+                }
+            };
+        config_files.sort();
+        config_files.dedup();
+        watch_set.config_files = config_files;
+        paths_to_watch.sort();
+        paths_to_watch.dedup();
+
+        for path in &watch_set.watched {
+            if !paths_to_watch.contains(path) {
+                // The path may have been deleted, which also stops the watch
+                let _ = watcher.unwatch(path);
+            }
+        }
+        let mut existing_paths = vec![];
+        let mut missing_paths = vec![];
+        for path in paths_to_watch {
+            // If a path doesn't exist yet, we keep an eye out for it being created
+            if !path.exists() {
+                missing_paths.push(path);
+                continue;
+            }
+            if !watch_set.watched.contains(&path) {
+                if let Err(e) = watcher.watch(path.as_path(), RecursiveMode::Recursive) {
+                    let msg = format!("Couldn't watch {} for changes: {e}", path.display());
+                    Message::new(MessageType::Warning, &msg).print();
+                    tracing::warn!("{}", &msg);
+                    continue;
+                }
+            }
+            existing_paths.push(path);
+        }
+        watch_set.watched = existing_paths;
+        watch_set.missing = missing_paths;
+
+        Message::new(
+            MessageType::Info,
+            &format!(
+                "Found {} paths to watch, starting watch...",
+                watch_set.watched.len()
+            ),
+        )
+        .print();
+        tracing::info!(
+            "Found {} paths to watch, starting watch...",
+            watch_set.watched.len()
+        );
+        Message::new(
+            MessageType::Debug,
+            &format!("Files watched: {:?}", watch_set.watched),
+        )
+        .print();
+    }
+
+    /// Get the paths that are relevant to building the site with this config
+    fn paths_to_watch(&self, config: &Config) -> Vec<PathBuf> {
+        let mut paths_to_watch: Vec<String> = vec![];
+        // Watch for the readme file
+        if !config.readme_path.is_empty() {
+            paths_to_watch.push(config.readme_path.clone());
+        }
+        // Watch for the oranda config files, and the ones they extend
+        paths_to_watch.extend(self.config_paths().into_iter().map(String::from));
+        paths_to_watch.extend(config.extended_configs.iter().map(|path| path.to_string()));
+
+        // Watch for any user-provided paths
+        if let Some(include_paths) = &self.include_paths {
+            let mut include_paths: Vec<String> =
+                include_paths.iter().map(|p| p.to_string()).collect();
+            paths_to_watch.append(&mut include_paths);
+        }
+
+        // Watch for the funding.md page and the funding.yml file
+        let project_root = self.project_root();
+        if config.funding.is_some() {
+            paths_to_watch.push(resolve_path(&project_root, "funding.md"));
+            paths_to_watch.push(resolve_path(&project_root, ".github/FUNDING.yml"));
+        }
+
+        // Watch for additional pages, if we have any
+        if let Some(additional_pages) = &config.additional_pages {
+            let mut additional_pages: Vec<String> = additional_pages.values().cloned().collect();
+            paths_to_watch.append(&mut additional_pages);
+        }
+
+        // Watch for the mdbook directory, if we have it
+        let book = config.mdbook.as_ref().map(|book_cfg| {
+            let path = mdbook_dir(book_cfg)?;
+            let md = load_mdbook(&path)?;
+            Ok::<_, OrandaError>((book_cfg, md))
+        });
+        match book {
+            Some(Ok((book_cfg, md))) => {
+                // watch book.toml and /src/
+                paths_to_watch.push(md.root.join("book.toml").display().to_string());
+                paths_to_watch.push(md.source_dir().display().to_string());
+
+                // If we're not clobbering the theme, also watch the theme dir
+                // (note that this may not exist on the fs, mdbook reports the path regardless)
+                if custom_theme(book_cfg, &config.styles.theme()).is_none() {
+                    paths_to_watch.push(md.theme_dir().display().to_string());
+                }
+            }
+            // The build will report what's wrong with the book, so just watch its config
+            Some(Err(_)) => {
+                if let Some(path) = config.mdbook.as_ref().and_then(|cfg| cfg.path.as_ref()) {
+                    paths_to_watch.push(Utf8PathBuf::from(path).join("book.toml").into());
+                }
+            }
+            // Look out for a book being added where we'd find it
+            None => {
+                paths_to_watch.extend(
+                    MDBOOK_CANDIDATES
+                        .iter()
+                        .map(|path| resolve_path(&project_root, path)),
+                );
+            }
+        }
+
+        // Watch for any project manifest files
+        let project = axoproject::get_workspaces(&project_root, None);
+        match project.rust {
+            WorkspaceSearch::Found(workspace) => {
+                paths_to_watch.push(workspace.manifest_path.into());
+            }
+            _ => paths_to_watch.push(resolve_path(&project_root, "./Cargo.toml")),
+        }
+        match project.javascript {
+            WorkspaceSearch::Found(workspace) => {
+                paths_to_watch.push(workspace.manifest_path.into());
+            }
+            _ => paths_to_watch.push(resolve_path(&project_root, "./package.json")),
+        }
+
+        paths_to_watch.into_iter().map(PathBuf::from).collect()
+    }
+}
+
+/// Whether a change to this file could change which files we should be watching
+///
+/// That's the case for the config files, and for the manifests that say what the project
+/// (and its mdbook) is.
+fn changes_watch_set(path: &Path, watch_set: &WatchSet) -> bool {
+    let is_manifest = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| WATCH_SET_FILES.contains(&name));
+    let path = comparable_path(path);
+    is_manifest
+        || watch_set
+            .config_files
+            .iter()
+            .any(|config_file| comparable_path(config_file) == path)
+}
+
+/// Get a path in a form that can be compared with other paths to the same file
+///
+/// (a file that was just deleted can't be canonicalized, so it's just made absolute)
+fn comparable_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_owned())
+    })
 }
@@ -0,0 +1,89 @@
+//! Telling pages that are open in a browser that `oranda dev` rebuilt the site
+//!
+//! Pages built for `oranda dev` include a script that long-polls [`LIVE_RELOAD_ROUTE`][]: each
+//! request says which build the page last heard about, and gets answered as soon as there's a
+//! newer one (or after a while, so connections don't hang around forever).
+
+use std::time::Duration;
+
+use axum::{extract::Query, routing::get, Json, Router};
+use miette::{GraphicalReportHandler, GraphicalTheme};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use oranda::errors::*;
+
+/// The route that pages poll to find out about new builds
+pub const LIVE_RELOAD_ROUTE: &str = "/__oranda/live-reload";
+/// How long a poll waits for a new build before answering anyway
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The outcome of the latest build
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildStatus {
+    /// Goes up by one with every build
+    build: u64,
+    /// The diagnostic for the build, if it failed
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PollQuery {
+    /// The build the page last heard about
+    since: Option<u64>,
+}
+
+/// Where `oranda dev` reports builds to, for the server to pass on
+#[derive(Debug)]
+pub struct LiveReload {
+    sender: watch::Sender<BuildStatus>,
+}
+
+impl LiveReload {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(BuildStatus::default());
+        Self { sender }
+    }
+
+    /// Let pages know a build finished (and whether it worked)
+    pub fn build_finished(&self, result: &Result<()>) {
+        self.sender.send_modify(|status| {
+            status.build += 1;
+            status.error = result.as_ref().err().map(render_error);
+        });
+    }
+
+    /// Get a handle on build statuses, for the server
+    pub fn subscribe(&self) -> watch::Receiver<BuildStatus> {
+        self.sender.subscribe()
+    }
+}
+
+/// Add the route that pages poll for builds to a server
+pub fn route(router: Router, receiver: watch::Receiver<BuildStatus>) -> Router {
+    router.route(
+        LIVE_RELOAD_ROUTE,
+        get(move |Query(query): Query<PollQuery>| poll(receiver.clone(), query)),
+    )
+}
+
+async fn poll(mut receiver: watch::Receiver<BuildStatus>, query: PollQuery) -> Json<BuildStatus> {
+    if let Some(since) = query.since {
+        if receiver.borrow_and_update().build == since {
+            // Timing out just means we answer with the build they already know about
+            let _ = tokio::time::timeout(POLL_TIMEOUT, receiver.changed()).await;
+        }
+    }
+    let status = receiver.borrow().clone();
+    Json(status)
+}
+
+/// Render a diagnostic the way it'd show up in the terminal, minus the colors
+fn render_error(error: &OrandaError) -> String {
+    let mut rendered = String::new();
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor());
+    match handler.render_report(&mut rendered, error) {
+        Ok(()) => rendered,
+        Err(_) => error.to_string(),
+    }
+}
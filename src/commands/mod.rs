@@ -1,9 +1,15 @@
 mod build;
+mod check;
+mod config;
 mod dev;
+mod live_reload;
 mod print;
 mod serve;
 
 pub use build::Build;
+pub use check::Check;
+pub use config::ConfigCommand;
 pub use dev::Dev;
+pub use live_reload::LiveReload;
 pub use print::ConfigSchema;
 pub use serve::Serve;
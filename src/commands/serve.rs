@@ -1,41 +1,88 @@
 use camino::{Utf8Path, Utf8PathBuf};
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
+use crate::commands::live_reload::{self, BuildStatus};
 use crate::message::{Message, MessageType};
 use oranda::config::Config;
 use oranda::errors::*;
+use oranda::site::hosting::{self, Resolution};
 
-use axum::{http::StatusCode, response::Redirect, routing::get, routing::get_service, Router};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
 
 use clap::Parser;
-use tower_http::services::ServeDir;
+use tokio::sync::watch;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 
-#[derive(Debug, Default, Parser)]
+#[derive(Debug, Parser)]
 pub struct Serve {
+    /// The directory of the project the site is for
+    #[arg(long, default_value = "./")]
+    project_root: Utf8PathBuf,
+    /// The path to the config file [default: <PROJECT_ROOT>/oranda.json or oranda.toml]
+    #[arg(long)]
+    config_path: Option<Utf8PathBuf>,
+    /// The address to listen on (use 0.0.0.0 to make the site reachable from other machines)
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
     #[arg(long, default_value = "7979")]
     port: u16,
+    /// Don't make any network requests
+    ///
+    /// Serving never needs the network, but this is accepted for parity with `build` and `dev`.
+    #[arg(long)]
+    offline: bool,
 }
 
 impl Serve {
-    pub fn new(port: Option<u16>) -> Self {
+    pub fn new(
+        project_root: Option<Utf8PathBuf>,
+        config_path: Option<Utf8PathBuf>,
+        host: Option<IpAddr>,
+        port: Option<u16>,
+    ) -> Self {
         Serve {
+            project_root: project_root.unwrap_or(Utf8PathBuf::from("./")),
+            config_path,
+            host: host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
             port: port.unwrap_or(7979),
+            offline: false,
         }
     }
 
+    /// Don't make any network requests (for `oranda dev --offline`)
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
     pub fn run(&self) -> Result<()> {
+        self.run_with(None)
+    }
+
+    /// Serve the site for `oranda dev`, along with the route that pages poll to reload
+    /// themselves after each build
+    pub fn run_with_live_reload(&self, receiver: watch::Receiver<BuildStatus>) -> Result<()> {
+        self.run_with(Some(receiver))
+    }
+
+    fn run_with(&self, live_reload: Option<watch::Receiver<BuildStatus>>) -> Result<()> {
         Message::new(MessageType::Info, "Running serve...").print();
         tracing::info!("Running serve...");
-        let config = Config::build(&Utf8PathBuf::from("./oranda.json"))?;
+        let mut config = Config::build(&self.project_root, self.config_path.as_deref())?;
+        config.offline = self.offline;
         if Utf8Path::new(&config.dist_dir).is_dir() {
             let msg = format!("Found build in {} directory...", &config.dist_dir);
             Message::new(MessageType::Info, &msg).print();
-            if let Some(prefix) = config.path_prefix {
-                tracing::debug!("`path_prefix` configured: {}", &prefix);
-                self.serve_prefix(&config.dist_dir, &prefix)?;
-            } else {
-                self.serve(&config.dist_dir)?;
+            if let Some(prefix) = &config.path_prefix {
+                tracing::debug!("`path_prefix` configured: {}", prefix);
             }
+            self.serve(&config.dist_dir, &config.path_prefix, live_reload)?;
             Ok(())
         } else {
             Err(OrandaError::BuildNotFound {
@@ -45,19 +92,27 @@ impl Serve {
     }
 
     #[tokio::main]
-    async fn serve(&self, dist_dir: &str) -> Result<()> {
-        let serve_dir =
-            get_service(ServeDir::new(dist_dir)).handle_error(|error: std::io::Error| async move {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Unhandled internal error: {}", error),
-                )
-            });
-
-        let app = Router::new().nest_service("/", serve_dir);
+    async fn serve(
+        &self,
+        dist_dir: &str,
+        path_prefix: &Option<String>,
+        live_reload: Option<watch::Receiver<BuildStatus>>,
+    ) -> Result<()> {
+        let dist_dir = Utf8PathBuf::from(dist_dir);
+        let prefix = path_prefix.clone();
+        let app = Router::new().fallback(move |request: Request<Body>| {
+            serve_path(dist_dir.clone(), prefix.clone(), request)
+        });
+        let app = match live_reload {
+            Some(receiver) => live_reload::route(app, receiver),
+            None => app,
+        };
 
-        let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
-        let msg = format!("Your project is available at: http://{}", addr);
+        let addr = SocketAddr::new(self.host, self.port);
+        let msg = match path_prefix {
+            Some(prefix) => format!("Your project is available at: http://{}/{}/", addr, prefix),
+            None => format!("Your project is available at: http://{}", addr),
+        };
         Message::new(MessageType::Success, &msg).print();
         axum::Server::bind(&addr)
             .serve(app.into_make_service())
@@ -65,43 +120,45 @@ impl Serve {
             .expect("failed to start server");
         Ok(())
     }
+}
 
-    #[tokio::main]
-    async fn serve_prefix(&self, dist_dir: &str, prefix: &str) -> Result<()> {
-        let serve_dir =
-            get_service(ServeDir::new(dist_dir)).handle_error(|error: std::io::Error| async move {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Unhandled internal error: {}", error),
-                )
-            });
-        let prefix_route = format!("/{}", prefix);
-        let oranda_route = format!("/{}/oranda.css", prefix);
-        let custom_route = format!("/{}/custom.css", prefix);
-        let app = Router::new()
-            .nest_service(&prefix_route, serve_dir)
-            .route(
-                "/oranda.css",
-                get(move || async {
-                    let oranda_route = oranda_route;
-                    Redirect::permanent(&oranda_route)
-                }),
+/// Respond to a request the way GitHub Pages would
+async fn serve_path(
+    dist_dir: Utf8PathBuf,
+    path_prefix: Option<String>,
+    request: Request<Body>,
+) -> Response {
+    match hosting::resolve(&dist_dir, &path_prefix, request.uri().path()) {
+        Resolution::File(path) => serve_file(path, request).await,
+        Resolution::Redirect(mut location) => {
+            if let Some(query) = request.uri().query() {
+                location = format!("{location}?{query}");
+            }
+            (
+                StatusCode::MOVED_PERMANENTLY,
+                [(header::LOCATION, location)],
             )
-            .route(
-                "/custom.css",
-                get(move || async {
-                    let custom_route = custom_route;
-                    Redirect::permanent(&custom_route)
-                }),
-            );
+                .into_response()
+        }
+        Resolution::NotFound(Some(page)) => {
+            // Start from a blank request, so conditional headers can't turn this into a 304
+            let mut page_request = Request::new(Body::empty());
+            *page_request.method_mut() = request.method().clone();
+            let mut response = serve_file(page, page_request).await;
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response
+        }
+        Resolution::NotFound(None) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
 
-        let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
-        let msg = format!("Your project is available at: http://{}/{}", addr, prefix);
-        Message::new(MessageType::Success, &msg).print();
-        axum::Server::bind(&addr)
-            .serve(app.into_make_service())
-            .await
-            .expect("failed to start server");
-        Ok(())
+async fn serve_file(path: Utf8PathBuf, request: Request<Body>) -> Response {
+    match ServeFile::new(path).oneshot(request).await {
+        Ok(response) => response.map(axum::body::boxed),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Unhandled internal error: {}", error),
+        )
+            .into_response(),
     }
 }
@@ -1,18 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
 pub mod oranda_config;
 pub mod project;
+mod provenance;
+pub mod validate;
 
 use crate::errors::*;
+use crate::message::{Message, MessageType};
 pub use oranda_config::{
-    AnalyticsConfig, ArtifactsConfig, BoolOr, FundingConfig, MdBookConfig, OrandaConfig,
-    SocialConfig, StyleConfig,
+    AnalyticsConfig, ArtifactsConfig, BoolOr, CacheConfig, ChangelogConfig, CheckConfig,
+    ConfigSource, FundingConfig, LatestReleaseKind, MdBookConfig, OrandaConfig, ReleaseSourceKind,
+    ReleasesConfig, RobotsConfig, SocialConfig, StyleConfig, WorkspaceConfig,
+    WorkspacePackageConfig,
 };
 use project::ProjectConfig;
+pub use provenance::{Provenance, DEFAULT_LAYER};
+use serde::Serialize;
+use serde_json::Value;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     pub description: String,
     pub dist_dir: String,
@@ -34,22 +42,81 @@ pub struct Config {
     /// The config for using mdbook
     pub mdbook: Option<MdBookConfig>,
     pub styles: StyleConfig,
-    pub changelog: bool,
+    /// The config for the changelog, if we're building one
+    pub changelog: Option<ChangelogConfig>,
+    /// Whether to build a search index and put a search box in the header
+    pub search: bool,
+    /// Whether to build a `sitemap.xml`
+    pub sitemap: bool,
+    /// The config for the `robots.txt`, if we're building one
+    pub robots: Option<RobotsConfig>,
+    /// The config for checking the links on the built site
+    pub check: CheckConfig,
     pub funding: Option<FundingConfig>,
+    pub releases: ReleasesConfig,
+    pub cache: Option<CacheConfig>,
+    pub workspace: Option<WorkspaceConfig>,
+    /// The packages of the workspace we're building a site for, if any
+    ///
+    /// Each of these gets its own page, using [`Config::for_member`][].
+    pub members: Vec<ProjectConfig>,
+    /// The config files that the config extends (see [`OrandaConfig::load_all`][])
+    #[serde(skip)]
+    pub extended_configs: Vec<Utf8PathBuf>,
+    /// Whether we're forbidden from touching the network
+    ///
+    /// (only settable from the CLI)
+    #[serde(skip)]
+    pub offline: bool,
+    /// Whether pages should reload themselves when the site is rebuilt
+    ///
+    /// (only set by `oranda dev`)
+    #[serde(skip)]
+    pub live_reload: bool,
 }
 
 impl Config {
-    pub fn build(config_path: &Utf8PathBuf) -> Result<Config> {
+    /// Get the paths of the config files we look for
+    ///
+    /// This is just the path we were given, if there was one, or otherwise `oranda.json`
+    /// and `oranda.toml` in the project root.
+    pub fn file_paths(project_root: &Utf8Path, config_path: Option<&Utf8Path>) -> Vec<Utf8PathBuf> {
+        match config_path {
+            Some(config_path) => vec![config_path.to_owned()],
+            None => CONFIG_FILE_NAMES
+                .iter()
+                .map(|name| project_root.join(name))
+                .collect(),
+        }
+    }
+
+    /// Compute the config for the project in `project_root`
+    ///
+    /// Paths in the config file are relative to the directory it's in, and any paths we
+    /// come up with ourselves (like defaults) are relative to the project root.
+    pub fn build(project_root: &Utf8Path, config_path: Option<&Utf8Path>) -> Result<Config> {
+        Self::build_with_provenance(project_root, config_path).map(|(cfg, _)| cfg)
+    }
+
+    /// Compute the config for the project in `project_root`, keeping track of where each
+    /// value came from (see [`Config::build`][])
+    pub fn build_with_provenance(
+        project_root: &Utf8Path,
+        config_path: Option<&Utf8Path>,
+    ) -> Result<(Config, Provenance)> {
         // Users can have multiple types of configuration or no configuration at all
         //
         // - Project configuration comes from a project manifest file. We currently
         //   support `Cargo.toml` and `package.json`, but could support any manifest
         //   that provided a `name`, `description`, `repository` and `homepage` field.
         //
-        // - Custom configuration comes from a `oranda.config.json` file. If this
-        //   file exists, it has precedence over project configuration, which means
-        //   you could use this file to override fields in your project manifest.
-        //   This file can contain all possible public configuration fields.
+        // - Custom configuration comes from a `oranda.json` or `oranda.toml` file, or
+        //   an `oranda` table in the project manifest. If it exists, it has precedence
+        //   over project configuration, which means you could use this file to override
+        //   fields in your project manifest. It can contain all possible public
+        //   configuration fields. If it's in several places, those are layered from the
+        //   most general to the most specific: the workspace's manifest, then the
+        //   package's manifest, then the config files.
         //
         // We apply these in layers, with later layers winning over earlier ones.
         //
@@ -59,16 +126,98 @@ impl Config {
         // If new stages are added or better defaults get introduced, we always
         // want to defer to those values if the layer we're currently applying doesn't have
         // an opinion on that value, which is what "None" in a config is really expressing.
+        if !project_root.is_dir() {
+            return Err(OrandaError::ProjectRootNotFound {
+                path: project_root.to_string(),
+            });
+        }
         let mut cfg = Config::default();
-        let custom = OrandaConfig::load(config_path)?;
-        let project = ProjectConfig::load(None)?;
+        cfg.dist_dir = resolve_path(project_root, &cfg.dist_dir);
+        cfg.static_dir = resolve_path(project_root, &cfg.static_dir);
+        cfg.readme_path = resolve_path(project_root, &cfg.readme_path);
+        let mut provenance = Provenance::default();
+        provenance.record(&cfg, DEFAULT_LAYER, &Value::Null);
+
+        // Finding the project needs an absolute path to start from
+        let start_dir = axoasset::LocalAsset::current_dir()?.join(project_root);
+        let project = ProjectConfig::load(Some(start_dir.clone().into()))?;
+        // If we're not in a package, we might be at the root of a whole workspace
+        let members = if project.is_none() {
+            ProjectConfig::load_workspace(Some(start_dir.into()))?
+        } else {
+            vec![]
+        };
+
+        let config_files: Vec<_> = Self::file_paths(project_root, config_path)
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect();
+        let mut embedded_configs = match (&project, members.first()) {
+            (Some(project), _) => project.embedded_configs.clone(),
+            // Every package knows about the workspace's config, so we can ask any of them
+            (None, Some(member)) => member
+                .embedded_configs
+                .iter()
+                .filter(|embedded| embedded.is_workspace_level())
+                .cloned()
+                .collect(),
+            (None, None) => vec![],
+        };
+        // The package's config overrides the workspace's
+        embedded_configs.sort_by_key(|embedded| !embedded.is_workspace_level());
+        let sources: Vec<_> = embedded_configs
+            .into_iter()
+            .map(ConfigSource::Embedded)
+            .chain(config_files.iter().cloned().map(ConfigSource::File))
+            .collect();
+        if sources.is_empty() {
+            Message::new(MessageType::Info, "No config found, using default values").print();
+        }
+
+        let mut custom = vec![];
+        let mut set_by = BTreeMap::new();
+        for source in &sources {
+            let layers = OrandaConfig::load_all(source)?;
+            warn_about_conflicts(source, &layers, &mut set_by)?;
+            custom.extend(layers);
+        }
 
+        let project_layer = match &project {
+            Some(project) => Some((
+                project.manifest_path.clone(),
+                serde_json::to_value(project)?,
+            )),
+            None => None,
+        };
         cfg.apply_project_layer(project);
-        cfg.apply_custom_layer(custom);
-        cfg.find_mdbook();
-        FundingConfig::find_paths(&mut cfg.funding)?;
+        if let Some((manifest_path, set)) = project_layer {
+            provenance.record(&cfg, manifest_path.as_str(), &set);
+        }
+        // Any other config files we loaded were extended by these
+        cfg.extended_configs = custom
+            .iter()
+            .filter_map(|(source, _)| match source {
+                ConfigSource::File(path) if !config_files.contains(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
+        let asked_for_sitemap = custom.iter().any(|(_, custom)| custom.sitemap.is_some());
+        let asked_for_robots = custom.iter().any(|(_, custom)| custom.robots.is_some());
+        for (source, custom) in custom {
+            let set = serde_json::to_value(&custom)?;
+            cfg.apply_custom_layer(Some(custom));
+            provenance.record(&cfg, &source.to_string(), &set);
+        }
+        cfg.apply_workspace_layer(members);
+        provenance.record(&cfg, "packages in the workspace", &Value::Null);
+        cfg.default_search_engine_files(asked_for_sitemap, asked_for_robots);
+        provenance.record(&cfg, DEFAULT_LAYER, &Value::Null);
+        cfg.find_mdbook(project_root);
+        provenance.record(&cfg, "autodetected mdbook", &Value::Null);
+        FundingConfig::find_paths(&mut cfg.funding, project_root)?;
+        provenance.record(&cfg, "autodetected funding files", &Value::Null);
 
-        Ok(cfg)
+        Ok((cfg, provenance))
     }
 
     /// Apply the layer of config we computed from project files
@@ -97,34 +246,132 @@ impl Config {
             self.name.apply_val(custom.name);
             self.readme_path.apply_val(custom.readme_path);
             self.repository.apply_opt(custom.repository);
+            self.no_header.apply_val(custom.no_header);
+            self.license.apply_opt(custom.license);
             self.analytics.apply_layer(custom.analytics);
-            // FIXME: should this get merged with e.g. `extend?`
-            self.additional_pages.apply_opt(custom.additional_pages);
+            self.additional_pages.apply_map(custom.additional_pages);
             self.social.apply_layer(custom.social);
             self.artifacts.apply_val_layer(custom.artifacts);
             self.styles.apply_val_layer(custom.styles);
             self.logo.apply_opt(custom.logo);
             self.favicon.apply_opt(custom.favicon);
             self.path_prefix.apply_opt(custom.path_prefix);
-            self.changelog.apply_val(custom.changelog);
+            // Unlike other features, the changelog is off unless it's asked for
+            match custom.changelog {
+                Some(BoolOr::Bool(true)) => {
+                    self.changelog.get_or_insert_with(ChangelogConfig::default);
+                }
+                Some(BoolOr::Val(changelog)) => {
+                    self.changelog
+                        .get_or_insert_with(ChangelogConfig::default)
+                        .apply_layer(changelog);
+                }
+                Some(BoolOr::Bool(false)) => self.changelog = None,
+                None => {}
+            }
+            self.search.apply_val(custom.search);
+            self.sitemap.apply_val(custom.sitemap);
+            self.robots.apply_bool_layer(custom.robots);
+            self.check.apply_val_layer(custom.check);
             self.mdbook.apply_bool_layer(custom.mdbook);
             self.funding.apply_bool_layer(custom.funding);
+            self.releases.apply_val_layer(custom.releases);
+            self.cache.apply_bool_layer(custom.cache);
+            self.workspace.apply_bool_layer(custom.workspace);
+        }
+    }
+
+    /// Turn off the `sitemap.xml` and `robots.txt` if they weren't asked for and we can't
+    /// build them
+    ///
+    /// Search engines need full URLs, so both need a `homepage`. They also only look for a
+    /// `robots.txt` at the root of a domain, so there's no point in one under a `path_prefix`.
+    fn default_search_engine_files(&mut self, asked_for_sitemap: bool, asked_for_robots: bool) {
+        if !asked_for_sitemap && self.homepage.is_none() {
+            self.sitemap = false;
+        }
+        if !asked_for_robots && (self.homepage.is_none() || self.path_prefix.is_some()) {
+            self.robots = None;
+        }
+    }
+
+    /// Apply the layer of config we computed from a workspace's packages
+    fn apply_workspace_layer(&mut self, members: Vec<ProjectConfig>) {
+        if members.is_empty() {
+            return;
+        }
+        if let Some(workspace) = &self.workspace {
+            self.members = members
+                .into_iter()
+                .filter(|member| workspace.includes(&member.name))
+                .collect();
+        } else {
+            let msg = "Ignoring the workspace's packages, because `workspace` is disabled";
+            Message::new(MessageType::Warning, msg).print();
+        }
+    }
+
+    /// Compute the config for the page of a package in the workspace
+    pub fn for_member(&self, member: &ProjectConfig) -> Config {
+        let mut cfg = self.clone();
+        let package_cfg = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.package(&member.name))
+            .cloned()
+            .unwrap_or_default();
+
+        cfg.name = member.name.clone();
+        cfg.description = member.description.clone();
+        cfg.version = member.version.clone();
+        cfg.homepage.apply_opt(member.homepage.clone());
+        cfg.repository.apply_opt(member.repository.clone());
+        cfg.license.apply_opt(member.license.clone());
+        // A package without a README of its own shouldn't show the workspace's
+        cfg.readme_path = package_cfg
+            .readme_path
+            .or_else(|| member.readme_path.as_ref().map(|p| p.to_string()))
+            .unwrap_or_default();
+        if cfg.artifacts.cargo_dist.is_none() {
+            cfg.artifacts.cargo_dist = member.cargo_dist;
         }
+        cfg.artifacts.app_name = Some(member.name.clone());
+        cfg.releases.tag_prefix = Some(
+            package_cfg
+                .tag_prefix
+                .unwrap_or_else(|| format!("{}-v", member.name)),
+        );
+
+        // Each package is a little site of its own, nested in the workspace's
+        cfg.dist_dir = Utf8Path::new(&self.dist_dir).join(&member.name).to_string();
+        cfg.path_prefix = Some(match &self.path_prefix {
+            Some(prefix) => format!("{prefix}/{}", member.name),
+            None => member.name.clone(),
+        });
+
+        // These are shared by the whole workspace, so they only appear at the top level
+        cfg.additional_pages = None;
+        cfg.mdbook = None;
+        cfg.funding = None;
+        cfg.workspace = None;
+        cfg.members = vec![];
+        cfg
     }
 
-    /// If mdbook is enabled but the path isn't set, we try to find it
+    /// If mdbook is enabled but the path isn't set, we try to find it in the project root
     ///
     /// If we fail, we set mdbook to None to disable it.
-    fn find_mdbook(&mut self) {
+    fn find_mdbook(&mut self, project_root: &Utf8Path) {
         if let Some(mdbook_cfg) = &mut self.mdbook {
             if mdbook_cfg.path.is_none() {
                 // Ok time to auto-detect, try these dirs for a book.toml
                 let possible_paths = vec!["./", "./book/", "./docs/"];
                 for book_dir in possible_paths {
-                    let book_path = Utf8PathBuf::from(book_dir).join("book.toml");
+                    let book_dir = resolve_path(project_root, book_dir);
+                    let book_path = Utf8PathBuf::from(&book_dir).join("book.toml");
                     if book_path.exists() {
                         // nice, use it
-                        mdbook_cfg.path = Some(book_dir.to_owned());
+                        mdbook_cfg.path = Some(book_dir);
                         return;
                     }
                 }
@@ -158,10 +405,74 @@ impl Default for Config {
             static_dir: String::from("static"),
             // Later stages can disable mdbook support by setting this to None
             mdbook: Some(MdBookConfig::default()),
-            changelog: false,
+            changelog: None,
+            search: false,
+            sitemap: true,
+            robots: Some(RobotsConfig::default()),
+            check: CheckConfig::default(),
             funding: Some(FundingConfig::default()),
+            releases: ReleasesConfig::default(),
+            // Later stages can disable caching by setting this to None
+            cache: Some(CacheConfig::default()),
+            // Later stages can disable workspace support by setting this to None
+            workspace: Some(WorkspaceConfig::default()),
+            members: vec![],
+            extended_configs: vec![],
+            offline: false,
+            live_reload: false,
+        }
+    }
+}
+
+/// The names of the config files we look for in the project root
+pub const CONFIG_FILE_NAMES: &[&str] = &["oranda.json", "oranda.toml"];
+
+/// Resolve a path from the config against the directory it's relative to
+///
+/// URLs and absolute paths are left alone. Paths relative to the current dir are too, so
+/// that they stay short in messages.
+pub fn resolve_path(base: &Utf8Path, path: &str) -> String {
+    let is_url = url::Url::parse(path)
+        .map(|url| url.scheme().len() > 1)
+        .unwrap_or(false);
+    let base_is_current_dir = base
+        .components()
+        .all(|c| c == camino::Utf8Component::CurDir);
+    if is_url || base_is_current_dir || Utf8Path::new(path).is_absolute() {
+        path.to_owned()
+    } else {
+        base.join(path).to_string()
+    }
+}
+
+/// Warn about every field that `source` sets to something other than an earlier source did
+///
+/// `set_by` has what the earlier sources set each field to. Configs that a source extends
+/// are meant to be overridden by it, so those only count as part of the source.
+fn warn_about_conflicts(
+    source: &ConfigSource,
+    layers: &[(ConfigSource, OrandaConfig)],
+    set_by: &mut BTreeMap<Vec<String>, (String, Value)>,
+) -> Result<()> {
+    let mut set = BTreeMap::new();
+    for (_, config) in layers {
+        provenance::fields(&serde_json::to_value(config)?, &mut vec![], &mut set);
+    }
+    set.retain(|_, value| !value.is_null() && *value != serde_json::json!({}));
+    for (path, value) in set {
+        if let Some((earlier, earlier_value)) = set_by.get(&path) {
+            if *earlier_value != value {
+                let msg = format!(
+                    "`{}` is set to {earlier_value} in {earlier}, but to {value} in {source}, so using {value}",
+                    path.join("."),
+                );
+                Message::new(MessageType::Warning, &msg).print();
+                tracing::warn!("{}", &msg);
+            }
         }
+        set_by.insert(path, (source.to_string(), value));
     }
+    Ok(())
 }
 
 // Utils for merging things
@@ -273,3 +584,28 @@ impl<T> ApplyOptExt for Option<T> {
         }
     }
 }
+
+/// Extension trait to provide apply_map
+pub trait ApplyMapExt
+where
+    Self: Sized,
+{
+    /// Merges an `Option<Map>` with an `Option<Map>`
+    ///
+    /// Adds the entries of the rhs to the lhs, overwriting any with the same key
+    fn apply_map(&mut self, layer: Self);
+}
+impl<M> ApplyMapExt for Option<M>
+where
+    M: IntoIterator + Extend<<M as IntoIterator>::Item>,
+{
+    fn apply_map(&mut self, layer: Self) {
+        if let Some(val) = layer {
+            if let Some(this) = self {
+                this.extend(val);
+            } else {
+                *self = Some(val);
+            }
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::config::ApplyLayer;
 
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::site::layout::javascript::analytics::{Fathom, Google, Plausible, Unami};
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AnalyticsConfig {
     Google(Google),
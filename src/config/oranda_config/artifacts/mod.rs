@@ -1,5 +1,5 @@
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::config::{ApplyLayer, ApplyOptExt};
 
@@ -34,19 +34,38 @@ enum ArtifactSystem {
     Freebsd,
 }
 
-#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct ArtifactsConfig {
     #[serde(default)]
     pub cargo_dist: Option<bool>,
     #[serde(default)]
     pub package_managers: Option<PackageManagersConfig>,
+    /// How many releases (from the highest version down) get their own install page
+    ///
+    /// Each of these pages lives at `artifacts/<tag>/`. Defaults to every release
+    /// with installers, set this to 0 to only have a page for the latest release.
+    #[serde(default)]
+    pub versioned_pages: Option<usize>,
+    /// Whether to download checksum files to show the hashes of your downloads
+    ///
+    /// Defaults to true. If this is false, the downloads table just links to the
+    /// checksum files.
+    #[serde(default)]
+    pub checksums: Option<bool>,
+    /// The app to show artifacts for, if the releases contain several
+    ///
+    /// (not user-settable, this is set for each package of a workspace)
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub app_name: Option<String>,
 }
 
 impl ApplyLayer for ArtifactsConfig {
     fn apply_layer(&mut self, layer: Self) {
         self.cargo_dist.apply_opt(layer.cargo_dist);
-        // FIXME: should this get merged with e.g. `extend?`
-        self.package_managers.apply_opt(layer.package_managers);
+        self.package_managers.apply_layer(layer.package_managers);
+        self.versioned_pages.apply_opt(layer.versioned_pages);
+        self.checksums.apply_opt(layer.checksums);
     }
 }
 
@@ -58,4 +77,13 @@ impl ArtifactsConfig {
     pub fn cargo_dist(&self) -> bool {
         self.cargo_dist.unwrap_or(false)
     }
+
+    pub fn checksums(&self) -> bool {
+        self.checksums.unwrap_or(true)
+    }
+
+    /// How many releases get their own install page
+    pub fn versioned_pages(&self) -> usize {
+        self.versioned_pages.unwrap_or(usize::MAX)
+    }
 }
@@ -1,10 +1,10 @@
 use indexmap::IndexMap;
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::config::{ApplyLayer, ApplyOptExt};
+use crate::config::{ApplyLayer, ApplyMapExt};
 
-#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct PackageManagersConfig {
     #[serde(default)]
     pub preferred: Option<IndexMap<String, String>>,
@@ -31,8 +31,8 @@ impl PackageManagersConfig {
 
 impl ApplyLayer for PackageManagersConfig {
     fn apply_layer(&mut self, layer: Self) {
-        self.preferred.apply_opt(layer.preferred);
-        self.additional.apply_opt(layer.additional);
+        self.preferred.apply_map(layer.preferred);
+        self.additional.apply_map(layer.additional);
     }
 }
 
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use camino::Utf8PathBuf;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyOptExt};
+
+/// By default, how long a cached response is used before we revalidate it
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+/// The name of the directory (in the system temp dir) we cache things in by default
+const DEFAULT_CACHE_DIR_NAME: &str = "oranda-cache";
+
+/// Config for caching things we fetch over the network
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct CacheConfig {
+    /// Where to store cached responses
+    ///
+    /// Defaults to an `oranda-cache` directory in your system's temp dir.
+    pub dir: Option<String>,
+    /// How long (in seconds) a cached response is used before we ask the server
+    /// whether it has changed
+    ///
+    /// Defaults to 300 (5 minutes). Set this to 0 to always revalidate.
+    pub ttl: Option<u64>,
+}
+
+impl ApplyLayer for CacheConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.dir.apply_opt(layer.dir);
+        self.ttl.apply_opt(layer.ttl);
+    }
+}
+
+impl CacheConfig {
+    /// Get the cache dir
+    pub fn dir(&self) -> Utf8PathBuf {
+        if let Some(dir) = &self.dir {
+            Utf8PathBuf::from(dir)
+        } else {
+            let temp_dir =
+                Utf8PathBuf::from_path_buf(std::env::temp_dir()).expect("temp dir isn't utf8!?");
+            temp_dir.join(DEFAULT_CACHE_DIR_NAME)
+        }
+    }
+    /// Get the ttl
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl.unwrap_or(DEFAULT_CACHE_TTL_SECS))
+    }
+}
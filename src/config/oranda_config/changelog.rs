@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyOptExt};
+
+/// Config for the changelog
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ChangelogConfig {
+    /// Whether to build RSS and Atom feeds of your releases
+    ///
+    /// Defaults to `true`.
+    pub feeds: Option<bool>,
+    /// Whether prereleases show up in the feeds
+    ///
+    /// Defaults to `true`.
+    pub feed_prereleases: Option<bool>,
+}
+
+impl ApplyLayer for ChangelogConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.feeds.apply_opt(layer.feeds);
+        self.feed_prereleases.apply_opt(layer.feed_prereleases);
+    }
+}
+
+impl ChangelogConfig {
+    /// Get whether to build feeds
+    pub fn feeds(&self) -> bool {
+        self.feeds.unwrap_or(true)
+    }
+    /// Get whether prereleases show up in the feeds
+    pub fn feed_prereleases(&self) -> bool {
+        self.feed_prereleases.unwrap_or(true)
+    }
+}
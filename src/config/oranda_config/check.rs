@@ -0,0 +1,19 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyOptExt};
+
+/// Config for checking the links on the built site
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct CheckConfig {
+    /// Glob patterns (e.g. `https://github.com/axodotdev/*`) that links to other sites must match
+    ///
+    /// If this isn't set, links to other sites aren't checked at all.
+    pub external_links: Option<Vec<String>>,
+}
+
+impl ApplyLayer for CheckConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.external_links.apply_opt(layer.external_links);
+    }
+}
@@ -0,0 +1,63 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::CONFIG_FILE_NAMES;
+use crate::errors::*;
+
+/// Other configs that a config builds on
+///
+/// Either one path or a list of them, each to a config file or a directory of them.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Extends {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Extends {
+    pub fn paths(&self) -> &[String] {
+        match self {
+            Extends::One(path) => std::slice::from_ref(path),
+            Extends::Many(paths) => paths,
+        }
+    }
+
+    pub fn paths_mut(&mut self) -> &mut [String] {
+        match self {
+            Extends::One(path) => std::slice::from_mut(path),
+            Extends::Many(paths) => paths,
+        }
+    }
+}
+
+/// Get the config files at `path`, which `source_name` extends
+///
+/// A directory stands for all the config files in it, in order of their names.
+pub fn config_files(path: &Utf8Path, source_name: &str) -> Result<Vec<Utf8PathBuf>> {
+    let not_found = || OrandaError::ConfigExtendsNotFound {
+        path: path.to_string(),
+        source_name: source_name.to_owned(),
+    };
+    if path.is_file() {
+        return Ok(vec![path.to_owned()]);
+    }
+    if !path.is_dir() {
+        return Err(not_found());
+    }
+    let mut files = path
+        .read_dir_utf8()?
+        .map(|entry| entry.map(|entry| entry.into_path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    files.retain(|file| {
+        let is_config = CONFIG_FILE_NAMES
+            .iter()
+            .any(|name| Utf8Path::new(name).extension() == file.extension());
+        file.is_file() && is_config
+    });
+    if files.is_empty() {
+        return Err(not_found());
+    }
+    files.sort();
+    Ok(files)
+}
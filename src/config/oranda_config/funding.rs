@@ -1,13 +1,13 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::config::{ApplyLayer, ApplyOptExt};
+use crate::config::{resolve_path, ApplyLayer, ApplyOptExt};
 use crate::data::funding::FundingType;
 use crate::errors::*;
 
 /// Config for displaying funding information on your page
-#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct FundingConfig {
     pub preferred_funding: Option<FundingType>,
     pub yml_path: Option<String>,
@@ -26,21 +26,22 @@ impl FundingConfig {
     // see if we can find a FUNDING.yml, if so update the config path
 
     /// If we have a FUNDING.yml file, try to find it. If we fail, we disable funding support.
-    pub fn find_paths(config: &mut Option<Self>) -> Result<()> {
-        let Some(this) = config else {
-            return Ok(())
-        };
+    ///
+    /// Files are looked for in the project root.
+    pub fn find_paths(config: &mut Option<Self>, project_root: &Utf8Path) -> Result<()> {
+        let Some(this) = config else { return Ok(()) };
 
         // Try to auto-detect the FUNDING.yml if not specified
         if this.yml_path.is_none() {
-            let default_yml_path = Utf8PathBuf::from("./.github/FUNDING.yml");
+            let default_yml_path =
+                Utf8PathBuf::from(resolve_path(project_root, "./.github/FUNDING.yml"));
             if default_yml_path.exists() {
                 this.yml_path = Some(default_yml_path.to_string());
             }
         }
         // Try to auto-detect funding.md if not specified
         if this.md_path.is_none() {
-            let default_md_path = Utf8PathBuf::from("./funding.md");
+            let default_md_path = Utf8PathBuf::from(resolve_path(project_root, "./funding.md"));
             if default_md_path.exists() {
                 this.md_path = Some(default_md_path.to_string());
             }
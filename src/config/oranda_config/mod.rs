@@ -1,29 +1,52 @@
 use std::collections::HashMap;
 
 use axoasset::SourceFile;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
+use miette::IntoDiagnostic;
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::config::project::EmbeddedConfig;
+use crate::config::resolve_path;
+use crate::config::validate;
 use crate::errors::*;
 use crate::message::{Message, MessageType};
 
 pub use analytics::AnalyticsConfig;
 pub use artifacts::ArtifactsConfig;
+pub use cache::CacheConfig;
+pub use changelog::ChangelogConfig;
+pub use check::CheckConfig;
+pub use extends::Extends;
 pub use funding::FundingConfig;
 pub use mdbook_config::MdBookConfig;
+pub use releases::{LatestReleaseKind, ReleaseSourceKind, ReleasesConfig};
+pub use robots::RobotsConfig;
 pub use social::SocialConfig;
 pub use style::StyleConfig;
+pub use workspace::{WorkspaceConfig, WorkspacePackageConfig};
 
 pub mod analytics;
 pub mod artifacts;
+mod cache;
+mod changelog;
+mod check;
+mod extends;
 mod funding;
 mod mdbook_config;
+mod releases;
+mod robots;
 mod social;
 mod style;
+mod workspace;
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct OrandaConfig {
+    /// Other configs to build this one on top of
+    ///
+    /// Each of these is a path to a config file, or a directory of them. They're applied
+    /// in order, before this config.
+    pub extends: Option<Extends>,
     pub description: Option<String>,
     pub dist_dir: Option<String>,
     pub static_dir: Option<String>,
@@ -47,27 +70,214 @@ pub struct OrandaConfig {
     /// Setting it to `true` is allowed but equivalent to `None`.
     #[serde(alias = "md_book")]
     pub mdbook: Option<BoolOr<MdBookConfig>>,
-    pub changelog: Option<bool>,
+    /// Config for the changelog
+    ///
+    /// Can be set to `true` to build a changelog with the default settings.
+    pub changelog: Option<BoolOr<ChangelogConfig>>,
+    /// Whether to build a search index and put a search box in the header
+    pub search: Option<bool>,
+    /// Whether to build a `sitemap.xml` for search engines
+    pub sitemap: Option<bool>,
+    /// Config for the `robots.txt` we build for search engines
+    ///
+    /// Can be set to `false` to not build one.
+    pub robots: Option<BoolOr<RobotsConfig>>,
+    /// Config for checking the links on the built site
+    pub check: Option<CheckConfig>,
     pub styles: Option<StyleConfig>,
     pub funding: Option<BoolOr<FundingConfig>>,
+    /// Config for where we get release data from
+    pub releases: Option<ReleasesConfig>,
+    /// Config for caching things we fetch over the network
+    ///
+    /// Can be set to `false` to disable caching entirely.
+    pub cache: Option<BoolOr<CacheConfig>>,
+    /// Config for building a site for a whole workspace
+    ///
+    /// Can be set to `false` to stop oranda from building pages for a workspace's packages.
+    pub workspace: Option<BoolOr<WorkspaceConfig>>,
 }
 
 impl OrandaConfig {
-    pub fn load(config_path: &Utf8PathBuf) -> Result<Option<OrandaConfig>> {
-        let msg = format!("Loading config at {}", config_path);
+    /// Load the config from wherever it is, along with all the configs it extends
+    ///
+    /// The configs are in the order they should be applied in, so every config comes after
+    /// the ones it extends.
+    pub fn load_all(source: &ConfigSource) -> Result<Vec<(ConfigSource, OrandaConfig)>> {
+        let mut layers = vec![];
+        Self::load_extended(source.clone(), &mut vec![], &mut layers)?;
+        Ok(layers)
+    }
+
+    /// Load a config and the configs it extends into `layers`, where `chain` is the configs
+    /// that led to this one (so we can tell if we've gone in a loop)
+    fn load_extended(
+        source: ConfigSource,
+        chain: &mut Vec<(Utf8PathBuf, String)>,
+        layers: &mut Vec<(ConfigSource, OrandaConfig)>,
+    ) -> Result<()> {
+        let id = match &source {
+            ConfigSource::File(path) => std::fs::canonicalize(path)
+                .ok()
+                .and_then(|path| Utf8PathBuf::from_path_buf(path).ok())
+                .unwrap_or_else(|| path.clone()),
+            ConfigSource::Embedded(embedded) => embedded.manifest_path.join(&embedded.key),
+        };
+        if let Some(start) = chain.iter().position(|(seen, _)| *seen == id) {
+            let mut cycle = chain[start..]
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>();
+            let name = source.to_string();
+            cycle.push(&name);
+            return Err(OrandaError::ConfigExtendsCycle {
+                cycle: cycle.join(" -> "),
+            });
+        }
+
+        let mut config = Self::load(&source)?;
+        chain.push((id, source.to_string()));
+        if let Some(extends) = config.extends.take() {
+            for path in extends.paths() {
+                for file in extends::config_files(Utf8Path::new(path), &source.to_string())? {
+                    Self::load_extended(ConfigSource::File(file), chain, layers)?;
+                }
+            }
+        }
+        chain.pop();
+        layers.push((source, config));
+        Ok(())
+    }
+
+    /// Load the config from wherever it is, making its paths relative to the current dir
+    pub fn load(source: &ConfigSource) -> Result<OrandaConfig> {
+        let msg = match source {
+            ConfigSource::File(path) => format!("Loading config at {path}"),
+            ConfigSource::Embedded(_) => format!("Loading config from {source}"),
+        };
         Message::new(MessageType::Info, &msg).print();
         tracing::info!("{}", &msg);
-        let config_result = SourceFile::load_local(config_path.as_path());
 
-        match config_result {
-            Ok(config) => {
-                let data: OrandaConfig = config.deserialize_json()?;
-                tracing::debug!("{:?}", data);
-                Ok(Some(data))
+        let parse_error = |details| OrandaError::ConfigParseError {
+            source_name: source.to_string(),
+            details,
+        };
+        let mut data: OrandaConfig = match source {
+            ConfigSource::File(path) if path.extension() == Some("toml") => {
+                let config = SourceFile::load_local(path)?;
+                let value = toml::from_str(config.contents())
+                    .into_diagnostic()
+                    .map_err(parse_error)?;
+                // We can only point at the keys in JSON
+                let problems = validate::unknown_keys(&value, None);
+                check_unknown_keys(source, &config, problems)?;
+                toml::from_str(config.contents())
+                    .into_diagnostic()
+                    .map_err(parse_error)?
+            }
+            ConfigSource::File(path) => {
+                let config = SourceFile::load_local(path)?;
+                let value = config.deserialize_json()?;
+                let problems = validate::unknown_keys(&value, Some(config.contents()));
+                check_unknown_keys(source, &config, problems)?;
+                config.deserialize_json()?
+            }
+            ConfigSource::Embedded(embedded) => {
+                let problems = validate::unknown_keys(&embedded.value, None);
+                let manifest = SourceFile::new_empty(embedded.manifest_path.as_str());
+                check_unknown_keys(source, &manifest, problems)?;
+                serde_json::from_value(embedded.value.clone())
+                    .into_diagnostic()
+                    .map_err(parse_error)?
+            }
+        };
+        tracing::debug!("{:?}", data);
+        data.resolve_paths(source.base_dir());
+        Ok(data)
+    }
+
+    /// Make the paths in this config relative to `base` (the directory the config was in),
+    /// instead of the current dir
+    pub fn resolve_paths(&mut self, base: &Utf8Path) {
+        let resolve = |path: &mut String| *path = resolve_path(base, path);
+        if let Some(extends) = &mut self.extends {
+            extends.paths_mut().iter_mut().for_each(resolve);
+        }
+        self.dist_dir.iter_mut().for_each(resolve);
+        self.static_dir.iter_mut().for_each(resolve);
+        self.readme_path.iter_mut().for_each(resolve);
+        self.logo.iter_mut().for_each(resolve);
+        self.favicon.iter_mut().for_each(resolve);
+        if let Some(additional_pages) = &mut self.additional_pages {
+            additional_pages.values_mut().for_each(resolve);
+        }
+        if let Some(styles) = &mut self.styles {
+            styles.additional_css.iter_mut().for_each(resolve);
+        }
+        if let Some(BoolOr::Val(mdbook)) = &mut self.mdbook {
+            mdbook.path.iter_mut().for_each(resolve);
+        }
+        if let Some(BoolOr::Val(funding)) = &mut self.funding {
+            funding.yml_path.iter_mut().for_each(resolve);
+            funding.md_path.iter_mut().for_each(resolve);
+        }
+        if let Some(releases) = &mut self.releases {
+            releases.snapshot.iter_mut().for_each(resolve);
+        }
+        if let Some(BoolOr::Val(cache)) = &mut self.cache {
+            cache.dir.iter_mut().for_each(resolve);
+        }
+        if let Some(BoolOr::Val(workspace)) = &mut self.workspace {
+            for package in workspace.packages.iter_mut().flat_map(|p| p.values_mut()) {
+                package.readme_path.iter_mut().for_each(resolve);
             }
-            Err(_) => {
-                Message::new(MessageType::Info, "No config found, using default values").print();
-                Ok(None)
+        }
+    }
+}
+
+/// Fail if there were any keys in the config we don't know about
+fn check_unknown_keys(
+    source: &ConfigSource,
+    src: &SourceFile,
+    problems: Vec<validate::UnknownKey>,
+) -> Result<()> {
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(OrandaError::UnknownConfigKeys {
+            source_name: source.to_string(),
+            src: src.clone(),
+            problems,
+        })
+    }
+}
+
+/// Somewhere we read oranda config from
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    /// A config file, either `oranda.json` or `oranda.toml`
+    File(Utf8PathBuf),
+    /// A table in a project manifest, like `[package.metadata.oranda]` in a Cargo.toml
+    Embedded(EmbeddedConfig),
+}
+
+impl ConfigSource {
+    /// The directory that paths in the config are relative to
+    pub fn base_dir(&self) -> &Utf8Path {
+        let path = match self {
+            Self::File(path) => path,
+            Self::Embedded(embedded) => &embedded.manifest_path,
+        };
+        path.parent().unwrap_or(Utf8Path::new(""))
+    }
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{path}"),
+            Self::Embedded(embedded) => {
+                write!(f, "`{}` in {}", embedded.key, embedded.manifest_path)
             }
         }
     }
@@ -77,7 +287,7 @@ impl OrandaConfig {
 ///
 /// This allows us to have a simple yes/no version of a config while still
 /// allowing for a more advanced version to exist.
-#[derive(Deserialize, Debug, JsonSchema)]
+#[derive(Deserialize, Serialize, Debug, JsonSchema)]
 #[serde(untagged)]
 pub enum BoolOr<T> {
     /// They gave the simple bool
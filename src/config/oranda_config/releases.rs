@@ -0,0 +1,103 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyOptExt};
+
+/// Config for where we get your release data from
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ReleasesConfig {
+    /// Path to a local snapshot of your releases
+    ///
+    /// This can either be a JSON file in the format the GitHub Releases API returns,
+    /// or a directory containing such a file named `releases.json`. If set, we will
+    /// never ask the network for release data.
+    pub snapshot: Option<String>,
+    /// What kind of forge your repository is hosted on
+    ///
+    /// By default we guess from the repository URL, assuming GitHub for any host
+    /// we don't recognize. Set this if your project lives on a self-hosted GitLab,
+    /// Gitea, or Forgejo instance.
+    pub source: Option<ReleaseSourceKind>,
+    /// The name of an environment variable containing a GitHub token
+    ///
+    /// Defaults to `GITHUB_TOKEN`, falling back to `GH_TOKEN`. Requests to the GitHub API
+    /// are authenticated with this token, if it's set.
+    pub token_env: Option<String>,
+    /// Only use releases whose tags start with this (e.g. `oranda-v`)
+    ///
+    /// This is also stripped from tags before we parse them as versions.
+    pub tag_prefix: Option<String>,
+    /// Only use releases whose tags match one of these glob patterns (e.g. `v*`)
+    pub include_tags: Option<Vec<String>>,
+    /// Never use releases whose tags match one of these glob patterns (e.g. `css-v*`)
+    pub exclude_tags: Option<Vec<String>>,
+    /// The kinds of release we pick the latest release from, in order of preference
+    ///
+    /// Within each kind, the release with the highest version wins. Defaults to
+    /// `["dist-stable", "stable", "dist-prerelease", "prerelease"]`.
+    pub latest_priority: Option<Vec<LatestReleaseKind>>,
+}
+
+impl ReleasesConfig {
+    /// Get the token to authenticate with GitHub, if there is one
+    pub fn github_token(&self) -> Option<String> {
+        let vars = match &self.token_env {
+            Some(var) => vec![var.as_str()],
+            None => vec!["GITHUB_TOKEN", "GH_TOKEN"],
+        };
+        vars.into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|token| !token.trim().is_empty())
+    }
+
+    /// Get the kinds of release we pick the latest release from, in order of preference
+    pub fn latest_priority(&self) -> &[LatestReleaseKind] {
+        self.latest_priority
+            .as_deref()
+            .unwrap_or(DEFAULT_LATEST_PRIORITY)
+    }
+}
+
+/// The kinds of release that can be picked as the latest one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LatestReleaseKind {
+    /// A stable release with a cargo-dist manifest
+    DistStable,
+    /// Any stable release
+    Stable,
+    /// A prerelease with a cargo-dist manifest
+    DistPrerelease,
+    /// Any prerelease
+    Prerelease,
+}
+
+/// The order we pick the latest release in, if it isn't configured
+const DEFAULT_LATEST_PRIORITY: &[LatestReleaseKind] = &[
+    LatestReleaseKind::DistStable,
+    LatestReleaseKind::Stable,
+    LatestReleaseKind::DistPrerelease,
+    LatestReleaseKind::Prerelease,
+];
+
+/// The kinds of forges we can get releases from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseSourceKind {
+    Github,
+    Gitlab,
+    Gitea,
+    Forgejo,
+}
+
+impl ApplyLayer for ReleasesConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.snapshot.apply_opt(layer.snapshot);
+        self.source.apply_opt(layer.source);
+        self.token_env.apply_opt(layer.token_env);
+        self.tag_prefix.apply_opt(layer.tag_prefix);
+        self.include_tags.apply_opt(layer.include_tags);
+        self.exclude_tags.apply_opt(layer.exclude_tags);
+        self.latest_priority.apply_opt(layer.latest_priority);
+    }
+}
@@ -0,0 +1,24 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyOptExt};
+
+/// Config for the `robots.txt` we build for search engines
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct RobotsConfig {
+    /// Paths on your site that search engines shouldn't crawl (e.g. `/changelog/`)
+    pub disallow: Option<Vec<String>>,
+}
+
+impl ApplyLayer for RobotsConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.disallow.apply_opt(layer.disallow);
+    }
+}
+
+impl RobotsConfig {
+    /// Get the paths search engines shouldn't crawl
+    pub fn disallow(&self) -> &[String] {
+        self.disallow.as_deref().unwrap_or_default()
+    }
+}
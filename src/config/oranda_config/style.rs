@@ -1,11 +1,11 @@
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::config::{ApplyLayer, ApplyOptExt};
 use crate::site::{markdown::SyntaxTheme, oranda_theme::OrandaTheme};
 
 /// Config related to styling your page
-#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct StyleConfig {
     pub theme: Option<OrandaTheme>,
     pub syntax_theme: Option<SyntaxTheme>,
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApplyLayer, ApplyMapExt, ApplyOptExt};
+
+/// Config for building a site for a whole workspace
+///
+/// When oranda is run at the root of a Cargo or npm workspace (rather than in one
+/// of its packages), it builds a page for each package under `/<package>/`, and a
+/// top-level index listing them.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct WorkspaceConfig {
+    /// Packages that shouldn't get a page
+    pub exclude: Option<Vec<String>>,
+    /// Settings for individual packages, keyed by package name
+    pub packages: Option<HashMap<String, WorkspacePackageConfig>>,
+}
+
+/// Settings for one package in a workspace
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct WorkspacePackageConfig {
+    /// Only show releases whose tags start with this
+    ///
+    /// Defaults to `<package>-v`, which is how cargo-dist tags releases of
+    /// individual packages. Set this to `""` to show every release.
+    pub tag_prefix: Option<String>,
+    /// The path to the README to use as the package's landing page
+    ///
+    /// Defaults to the package's own README. Packages without one don't show the
+    /// workspace's README, just their name and description.
+    pub readme_path: Option<String>,
+}
+
+impl ApplyLayer for WorkspaceConfig {
+    fn apply_layer(&mut self, layer: Self) {
+        self.exclude.apply_opt(layer.exclude);
+        self.packages.apply_map(layer.packages);
+    }
+}
+
+impl WorkspaceConfig {
+    /// Get whether a package should get a page
+    pub fn includes(&self, package_name: &str) -> bool {
+        !self
+            .exclude
+            .as_ref()
+            .map(|exclude| exclude.iter().any(|name| name == package_name))
+            .unwrap_or(false)
+    }
+
+    /// Get the settings for a package
+    pub fn package(&self, package_name: &str) -> Option<&WorkspacePackageConfig> {
+        self.packages.as_ref()?.get(package_name)
+    }
+}
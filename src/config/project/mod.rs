@@ -1,13 +1,13 @@
 use std::path::PathBuf;
 
-use axoproject::{PackageIdx, WorkspaceInfo, WorkspaceSearch};
+use axoproject::{PackageIdx, WorkspaceInfo, WorkspaceKind, WorkspaceSearch};
 use camino::{Utf8Path, Utf8PathBuf};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::errors::*;
 use crate::message::{Message, MessageType};
 
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
 pub struct ProjectConfig {
     pub name: String,
     pub description: String,
@@ -17,6 +17,33 @@ pub struct ProjectConfig {
     pub license: Option<String>,
     pub readme_path: Option<Utf8PathBuf>,
     pub cargo_dist: Option<bool>,
+    /// The manifest the project was found in (e.g. its `Cargo.toml`)
+    pub manifest_path: Utf8PathBuf,
+    /// oranda config found in the project's manifests
+    ///
+    /// There's usually at most one of these, but a Cargo package can have config in both
+    /// its own `[package.metadata]` and its workspace's `[workspace.metadata]`.
+    #[serde(skip_serializing)]
+    pub embedded_configs: Vec<EmbeddedConfig>,
+}
+
+/// oranda config embedded in a project manifest, like `[package.metadata.oranda]` in a
+/// Cargo.toml or an `"oranda"` key in a package.json
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+pub struct EmbeddedConfig {
+    /// The manifest the config is in
+    pub manifest_path: Utf8PathBuf,
+    /// Where in the manifest the config is (e.g. `package.metadata.oranda`)
+    pub key: String,
+    /// The config itself
+    pub value: serde_json::Value,
+}
+
+impl EmbeddedConfig {
+    /// Whether this config is for a whole workspace, rather than one package
+    pub fn is_workspace_level(&self) -> bool {
+        self.key.starts_with("workspace.")
+    }
 }
 
 impl ProjectConfig {
@@ -29,29 +56,131 @@ impl ProjectConfig {
 
         if let Some((workspace, pkg)) = ProjectConfig::get_project(&start_dir) {
             // Cool we found the best possible match, now extract all the values we care about from it
-            let package = workspace.package(pkg);
-
-            // If there's a [workspace.metadata.dist] table, we can auto-enable cargo-dist
-            // If there's no [workspace.metadata] table at all, inconclusive.
-            let cargo_dist = workspace
-                .cargo_metadata_table
-                .as_ref()
-                .map(|t| t.get("dist").is_some());
-            Ok(Some(ProjectConfig {
-                name: package.name.clone(),
-                description: package.description.clone().unwrap_or_default(),
-                homepage: package.homepage_url.clone(),
-                repository: package.repository_url.clone(),
-                version: package.version.as_ref().map(|v| v.to_string()),
-                license: package.license.clone(),
-                readme_path: package.readme_file.clone(),
-                cargo_dist,
-            }))
+            Ok(Some(ProjectConfig::from_package(&workspace, pkg)))
         } else {
             Ok(None)
         }
     }
 
+    /// Load the config for every package in the workspace rooted at the project root
+    ///
+    /// This only finds something if the project root is a workspace that isn't itself
+    /// a package (like a virtual Cargo.toml), since otherwise [`ProjectConfig::load`][]
+    /// will pick up that package.
+    pub fn load_workspace(project_root: Option<PathBuf>) -> Result<Vec<ProjectConfig>> {
+        let start_dir = project_root.unwrap_or_else(|| {
+            std::env::current_dir().expect("couldn't get current working dir!?")
+        });
+        let start_dir = Utf8PathBuf::from_path_buf(start_dir).expect("project path isn't utf8!?");
+
+        let workspaces = axoproject::get_workspaces(&start_dir, Some(&start_dir));
+        for search in [workspaces.rust, workspaces.javascript] {
+            let axoproject::WorkspaceSearch::Found(workspace) = search else {
+                continue;
+            };
+            if Self::package_in_dir(&workspace, &start_dir).is_some() {
+                continue;
+            }
+            let message = format!(
+                "Detected {:?} workspace, building a page for each of its packages...",
+                workspace.kind
+            );
+            Message::new(MessageType::Info, &message).print();
+            tracing::info!("{}", message);
+            return Ok(workspace
+                .packages()
+                .filter(|(_, package)| package.publish)
+                .map(|(idx, _)| ProjectConfig::from_package(&workspace, idx))
+                .collect());
+        }
+        Ok(vec![])
+    }
+
+    /// Extract all the values we care about from a package in a workspace
+    fn from_package(workspace: &WorkspaceInfo, pkg: PackageIdx) -> ProjectConfig {
+        let package = workspace.package(pkg);
+
+        // If there's a [workspace.metadata.dist] table, we can auto-enable cargo-dist
+        // If there's no [workspace.metadata] table at all, inconclusive.
+        let cargo_dist = workspace
+            .cargo_metadata_table
+            .as_ref()
+            .map(|t| t.get("dist").is_some());
+        ProjectConfig {
+            name: package.name.clone(),
+            description: package.description.clone().unwrap_or_default(),
+            homepage: package.homepage_url.clone(),
+            repository: package
+                .repository_url
+                .clone()
+                .or_else(|| workspace.repository_url.clone()),
+            version: package.version.as_ref().map(|v| v.to_string()),
+            license: package.license.clone(),
+            readme_path: package.readme_file.clone(),
+            cargo_dist,
+            manifest_path: package.manifest_path.clone(),
+            embedded_configs: Self::embedded_configs(workspace, pkg),
+        }
+    }
+
+    /// Find any oranda config in the manifests of a package and its workspace
+    fn embedded_configs(workspace: &WorkspaceInfo, pkg: PackageIdx) -> Vec<EmbeddedConfig> {
+        let package = workspace.package(pkg);
+        let mut configs = vec![];
+        let mut push = |manifest_path: &Utf8Path, key: &str, value: Option<&serde_json::Value>| {
+            if let Some(value) = value.filter(|value| !value.is_null()) {
+                configs.push(EmbeddedConfig {
+                    manifest_path: manifest_path.to_owned(),
+                    key: key.to_owned(),
+                    value: value.clone(),
+                });
+            }
+        };
+        match workspace.kind {
+            WorkspaceKind::Rust => {
+                let package_table = package.cargo_metadata_table.as_ref();
+                push(
+                    &package.manifest_path,
+                    "package.metadata.oranda",
+                    package_table.and_then(|table| table.get("oranda")),
+                );
+                let workspace_table = workspace.cargo_metadata_table.as_ref();
+                push(
+                    &workspace.manifest_path,
+                    "workspace.metadata.oranda",
+                    workspace_table.and_then(|table| table.get("oranda")),
+                );
+            }
+            WorkspaceKind::Javascript => {
+                // axoproject doesn't keep the rest of the package.json around, so we read
+                // it again (it already parsed fine, so we don't worry about errors)
+                let package_json = std::fs::read_to_string(&package.manifest_path)
+                    .ok()
+                    .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+                push(
+                    &package.manifest_path,
+                    "oranda",
+                    package_json.as_ref().and_then(|json| json.get("oranda")),
+                );
+            }
+        }
+        configs
+    }
+
+    /// Find the package in a workspace that "owns" a dir
+    fn package_in_dir(workspace: &WorkspaceInfo, dir: &Utf8Path) -> Option<PackageIdx> {
+        // We need to use canonicalize here because something in guppy/cargo is
+        // desugarring symlinks in their output, so we need to too.
+        workspace.packages().find_map(|(idx, p)| {
+            let package_dir = p.manifest_path.parent().unwrap();
+            if is_same_path(package_dir, dir) {
+                Some(idx)
+            } else {
+                None
+            }
+        })
+    }
+
     /// Get information about the project workspace (using axoproject)
     ///
     /// The returned value is info about a Workspace and the specific package in that
@@ -125,29 +254,20 @@ impl ProjectConfig {
         match search {
             axoproject::WorkspaceSearch::Found(workspace) => {
                 // Now that we found the workspace, find the actual package that appears
-                // in the dir we're looking at. We need to use canonicalize here because
-                // something in guppy/cargo is desugarring symlinks in their output, so
-                // we need to too.
-                let package = workspace.packages().find_map(|(idx, p)| {
-                    let package_dir = p.manifest_path.parent().unwrap();
-                    if is_same_path(package_dir, start_dir) {
-                        Some(idx)
-                    } else {
-                        None
-                    }
-                });
+                // in the dir we're looking at.
+                let package = Self::package_in_dir(&workspace, start_dir);
 
                 if let Some(pkg_idx) = package {
                     // Nice, this package is a perfect candidate
                     Some((workspace, pkg_idx))
                 } else {
                     // Found a workspace but none of the packages specifically control this dir.
-                    // This can happen if you run oranda in a dir with a virtual Cargo.toml.
-                    Message::new(
-                        MessageType::Warning,
-                        &format!("Ignoring {:?} project, oranda is currently per-package and this looks like a whole workspace", workspace.kind),
-                    )
-                    .print();
+                    // This can happen if you run oranda in a dir with a virtual Cargo.toml,
+                    // in which case `load_workspace` will pick it up instead.
+                    tracing::info!(
+                        "{:?} workspace has no package in {start_dir}, so it's not a single project",
+                        workspace.kind
+                    );
                     None
                 }
             }
@@ -0,0 +1,190 @@
+//! Tracking which layer of config each value came from
+//!
+//! The layers in [`Config::build`][] just overwrite each other's values, so once they're
+//! merged there's no telling where anything came from. To keep track, we take a snapshot of
+//! the config after each layer, and credit that layer with every field it set, along with
+//! any other field that changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::config::Config;
+
+/// The layer of config that set the fields nothing else touched
+pub const DEFAULT_LAYER: &str = "default";
+
+/// Where each field in a [`Config`][] came from
+///
+/// Fields are identified by their path in the config, like `["styles", "theme"]`. Objects
+/// are broken down into their fields, while any other value (including a list) counts as
+/// one field.
+///
+/// A layer that sets a field gets the credit for it, even if it set it to the value it
+/// already had.
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    /// The value of every field as of the last layer we recorded
+    values: BTreeMap<Vec<String>, Value>,
+    /// The layer that set each field
+    layers: BTreeMap<Vec<String>, String>,
+}
+
+impl Provenance {
+    /// Credit `layer` with every field it set, and every field that changed since the last
+    /// layer was recorded
+    ///
+    /// `set` is what the layer itself said, shaped like a [`Config`][] (`null` for fields it
+    /// didn't set). Setting a field that's an object, like `changelog: true`, sets everything
+    /// in it.
+    pub fn record(&mut self, config: &Config, layer: &str, set: &Value) {
+        let mut values = BTreeMap::new();
+        let config = serde_json::to_value(config).expect("config should serialize to JSON");
+        fields(&config, &mut vec![], &mut values);
+        let mut set_fields = BTreeMap::new();
+        fields(set, &mut vec![], &mut set_fields);
+        // (an empty object, like `styles: {}`, doesn't set anything either)
+        set_fields.retain(|_, value| !value.is_null() && *value != serde_json::json!({}));
+        for (path, value) in &values {
+            let was_set = set_fields
+                .keys()
+                .any(|set| path.starts_with(set) || set.starts_with(path));
+            if was_set || self.values.get(path) != Some(value) {
+                self.layers.insert(path.clone(), layer.to_owned());
+            }
+        }
+        // Fields can also disappear, like when a feature gets disabled
+        self.layers.retain(|path, _| values.contains_key(path));
+        self.values = values;
+    }
+
+    /// Get the layer that set the field at `path`
+    pub fn layer(&self, path: &[&str]) -> Option<&str> {
+        let path = path.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        self.layers.get(&path).map(|layer| layer.as_str())
+    }
+
+    /// Get the config as JSON, with every field replaced by an object with its `value` and
+    /// the `source` that set it
+    pub fn annotated_json(&self, config: &Config) -> Value {
+        let config = serde_json::to_value(config).expect("config should serialize to JSON");
+        self.annotate_json(config, &mut vec![])
+    }
+
+    fn annotate_json(&self, value: Value, path: &mut Vec<String>) -> Value {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                let mut annotated = serde_json::Map::new();
+                for (key, value) in map {
+                    path.push(key.clone());
+                    annotated.insert(key, self.annotate_json(value, path));
+                    path.pop();
+                }
+                Value::Object(annotated)
+            }
+            value => serde_json::json!({
+                "value": value,
+                "source": self.layers.get(path),
+            }),
+        }
+    }
+
+    /// Get the config as TOML, with a comment saying where each field came from
+    ///
+    /// TOML has no `null`, so fields that aren't set are left out. Tables that only have
+    /// fields that aren't set still say where they came from, since that's what turned
+    /// them on.
+    pub fn annotated_toml(&self, config: &Config) -> String {
+        let config = serde_json::to_value(config).expect("config should serialize to JSON");
+        let mut doc = toml_edit::Document::new();
+        if let Value::Object(map) = config {
+            *doc.as_table_mut() = self.annotate_toml(map, &mut vec![]);
+        }
+        doc.to_string()
+    }
+
+    fn annotate_toml(
+        &self,
+        map: serde_json::Map<String, Value>,
+        path: &mut Vec<String>,
+    ) -> toml_edit::Table {
+        let mut table = toml_edit::Table::new();
+        for (key, value) in map {
+            path.push(key.clone());
+            match value {
+                Value::Object(map) if !map.is_empty() => {
+                    table.insert(&key, toml_edit::Item::Table(self.annotate_toml(map, path)));
+                }
+                value => {
+                    if let Some(mut value) = toml_value(value) {
+                        if let Some(layer) = self.layers.get(path) {
+                            value.decor_mut().set_suffix(format!(" # {layer}"));
+                        }
+                        table.insert(&key, toml_edit::Item::Value(value));
+                    }
+                }
+            }
+            path.pop();
+        }
+        if !table.is_empty() && table.iter().all(|(_, item)| item.is_table()) {
+            // Don't print a header for tables that only hold other tables
+            table.set_implicit(true);
+        } else if table.is_empty() {
+            // Nothing in here is set, but something still turned it on
+            let layers = self
+                .layers
+                .iter()
+                .filter(|(field, _)| field.starts_with(path))
+                .map(|(_, layer)| layer)
+                .collect::<BTreeSet<_>>();
+            if let [layer] = Vec::from_iter(layers).as_slice() {
+                table.decor_mut().set_suffix(format!(" # {layer}"));
+            }
+        }
+        table
+    }
+}
+
+/// Break a config down into its fields
+pub(crate) fn fields(
+    value: &Value,
+    path: &mut Vec<String>,
+    out: &mut BTreeMap<Vec<String>, Value>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                path.push(key.clone());
+                fields(value, path, out);
+                path.pop();
+            }
+        }
+        value => {
+            out.insert(path.clone(), value.clone());
+        }
+    }
+}
+
+/// Convert a JSON value to TOML, if TOML can represent it
+fn toml_value(value: Value) -> Option<toml_edit::Value> {
+    let value = match value {
+        Value::Null => return None,
+        Value::Bool(b) => b.into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => n.into(),
+            None => n.as_f64()?.into(),
+        },
+        Value::String(s) => s.into(),
+        Value::Array(items) => items
+            .into_iter()
+            .filter_map(toml_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        Value::Object(map) => map
+            .into_iter()
+            .filter_map(|(key, value)| Some((key, toml_value(value)?)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    };
+    Some(value)
+}
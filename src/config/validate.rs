@@ -0,0 +1,251 @@
+//! Checking configs for keys oranda doesn't know about
+//!
+//! serde happily ignores keys it doesn't recognize, so a typo like `"changlog": true` would
+//! otherwise do nothing at all. Instead, we walk the config alongside the JSON Schema for
+//! [`OrandaConfig`][] and report every key the schema doesn't have, suggesting the one the
+//! user probably meant.
+
+use std::collections::HashMap;
+
+use miette::{Diagnostic, SourceSpan};
+use schemars::schema::{RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::config::OrandaConfig;
+
+/// Keys we accept that aren't in the schema, and the key they're another name for
+const KEY_ALIASES: &[(&str, &str)] = &[("md_book", "mdbook")];
+
+/// A key in a config that oranda doesn't know about
+#[derive(Debug, Error, Diagnostic)]
+#[error("`{key}` isn't an oranda config option")]
+#[diagnostic(severity = "error")]
+pub struct UnknownKey {
+    /// The path to the key, like `styles.them`
+    pub key: String,
+    /// Where the key is in the config file, if we know
+    #[label("unknown key")]
+    pub span: Option<SourceSpan>,
+    #[help]
+    help: Option<String>,
+}
+
+/// Find all the keys in `config` that oranda doesn't know about
+///
+/// `source` is the JSON the config was parsed from, if it came from a JSON file, so we can
+/// point at where each key is.
+pub fn unknown_keys(config: &Value, source: Option<&str>) -> Vec<UnknownKey> {
+    let root = schemars::schema_for!(OrandaConfig);
+    let spans = source.map(key_spans).unwrap_or_default();
+    let walker = Walker {
+        root: &root,
+        spans: &spans,
+    };
+    let mut problems = vec![];
+    let schemas = walker.resolve(&Schema::Object(root.schema.clone()));
+    walker.check(config, &schemas, &mut vec![], &mut problems);
+    problems
+}
+
+/// Walks a config alongside the schema for it
+struct Walker<'a> {
+    root: &'a RootSchema,
+    spans: &'a HashMap<Vec<String>, SourceSpan>,
+}
+
+impl<'a> Walker<'a> {
+    /// Get every schema a value has to match, following references and `anyOf`-style
+    /// combinations (our `Option`s and `BoolOr`s are both written that way)
+    fn resolve(&self, schema: &Schema) -> Vec<SchemaObject> {
+        let mut out = vec![];
+        self.resolve_into(schema, &mut out);
+        out
+    }
+
+    fn resolve_into(&self, schema: &Schema, out: &mut Vec<SchemaObject>) {
+        let Schema::Object(obj) = schema else {
+            return;
+        };
+        if let Some(definition) = obj
+            .reference
+            .as_ref()
+            .and_then(|reference| reference.strip_prefix("#/definitions/"))
+            .and_then(|name| self.root.definitions.get(name))
+        {
+            self.resolve_into(definition, out);
+        }
+        if let Some(subschemas) = &obj.subschemas {
+            let all = [&subschemas.all_of, &subschemas.any_of, &subschemas.one_of];
+            for schema in all.into_iter().flatten().flatten() {
+                self.resolve_into(schema, out);
+            }
+        }
+        out.push(obj.clone());
+    }
+
+    fn check(
+        &self,
+        value: &Value,
+        schemas: &[SchemaObject],
+        path: &mut Vec<String>,
+        problems: &mut Vec<UnknownKey>,
+    ) {
+        match value {
+            Value::Object(map) => {
+                let objects = schemas
+                    .iter()
+                    .filter_map(|schema| schema.object.as_deref())
+                    .collect::<Vec<_>>();
+                // If the schema doesn't expect an object here, deserializing will complain
+                if objects.is_empty() {
+                    return;
+                }
+                for (key, value) in map {
+                    let name = KEY_ALIASES
+                        .iter()
+                        .find(|(alias, _)| alias == key)
+                        .map(|(_, name)| *name)
+                        .unwrap_or(key);
+                    let mut inner = vec![];
+                    for object in &objects {
+                        if let Some(property) = object.properties.get(name) {
+                            self.resolve_into(property, &mut inner);
+                        } else if let Some(additional) = &object.additional_properties {
+                            self.resolve_into(additional, &mut inner);
+                        }
+                    }
+                    let known =
+                        objects.iter().any(|object| {
+                            object.properties.contains_key(name)
+                                || object.additional_properties.as_deref().is_some_and(
+                                    |additional| !matches!(additional, Schema::Bool(false)),
+                                )
+                        });
+
+                    path.push(key.clone());
+                    if known {
+                        self.check(value, &inner, path, problems);
+                    } else {
+                        let options = objects
+                            .iter()
+                            .flat_map(|object| object.properties.keys())
+                            .map(|option| option.as_str());
+                        problems.push(UnknownKey {
+                            key: path.join("."),
+                            span: self.spans.get(path).copied(),
+                            help: suggest(key, options)
+                                .map(|option| format!("Did you mean `{option}`?")),
+                        });
+                    }
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                let mut inner = vec![];
+                for schema in schemas {
+                    if let Some(SingleOrVec::Single(item)) =
+                        schema.array.as_ref().and_then(|array| array.items.as_ref())
+                    {
+                        self.resolve_into(item, &mut inner);
+                    }
+                }
+                for (idx, value) in items.iter().enumerate() {
+                    path.push(idx.to_string());
+                    self.check(value, &inner, path, problems);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Find the option the user most likely meant to write instead of `key`
+fn suggest<'o>(key: &str, options: impl Iterator<Item = &'o str>) -> Option<&'o str> {
+    // People often write keys in kebab-case or with capitals, which we can match exactly
+    let normalized = key.to_lowercase().replace('-', "_");
+    options
+        .map(|option| {
+            let score = if option == normalized {
+                1.0
+            } else {
+                strsim::jaro_winkler(&normalized, option)
+            };
+            (score, option)
+        })
+        .filter(|(score, _)| *score >= 0.8)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, option)| option)
+}
+
+/// Find where every key in a JSON document is, by its path (array items are numbered)
+///
+/// This assumes the JSON is valid, which it is by the time we're validating it.
+fn key_spans(source: &str) -> HashMap<Vec<String>, SourceSpan> {
+    enum Frame {
+        /// An object, and the key we're currently in
+        Object(Option<String>),
+        /// An array, and the index we're currently at
+        Array(usize),
+    }
+    let path = |stack: &[Frame]| {
+        stack
+            .iter()
+            .filter_map(|frame| match frame {
+                Frame::Object(key) => key.clone(),
+                Frame::Array(idx) => Some(idx.to_string()),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let bytes = source.as_bytes();
+    let mut spans = HashMap::new();
+    let mut stack = vec![];
+    let mut expecting_key = false;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'"' => {
+                let start = idx;
+                idx += 1;
+                while idx < bytes.len() && bytes[idx] != b'"' {
+                    if bytes[idx] == b'\\' {
+                        idx += 1;
+                    }
+                    idx += 1;
+                }
+                let end = (idx + 1).min(bytes.len());
+                if expecting_key {
+                    if let Ok(key) = serde_json::from_str::<String>(&source[start..end]) {
+                        if let Some(Frame::Object(current)) = stack.last_mut() {
+                            *current = Some(key);
+                        }
+                        spans
+                            .entry(path(&stack))
+                            .or_insert_with(|| SourceSpan::from((start, end - start)));
+                    }
+                    expecting_key = false;
+                }
+                idx = end;
+                continue;
+            }
+            b'{' => {
+                stack.push(Frame::Object(None));
+                expecting_key = true;
+            }
+            b'[' => stack.push(Frame::Array(0)),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Object(_)) => expecting_key = true,
+                Some(Frame::Array(item)) => *item += 1,
+                None => {}
+            },
+            _ => {}
+        }
+        idx += 1;
+    }
+    spans
+}
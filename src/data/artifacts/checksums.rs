@@ -0,0 +1,212 @@
+//! Logic for finding checksum files in a release and reading the hashes out of them
+//!
+//! Checksums come in two flavours: a file per artifact (`app.tar.gz.sha256`, like cargo-dist
+//! makes), or one file listing every artifact (`SHA256SUMS`, `checksums.txt`...). Both are
+//! usually in the format `sha256sum` writes, which is what we mostly expect here.
+
+use serde::Serialize;
+
+use super::*;
+
+/// Extensions for files containing the checksum of another file
+const CHECKSUM_EXTS: &[&str] = &[".sha256", ".sha512", ".sha256sum", ".sha512sum"];
+/// Names (lowercased, ignoring a `.txt` extension) of files containing the checksums of
+/// lots of other files
+const AGGREGATE_CHECKSUM_NAMES: &[&str] = &[
+    "sha256sums",
+    "sha512sums",
+    "sha256sum",
+    "sha512sum",
+    "checksums",
+];
+
+/// A hash of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Checksum {
+    /// The algorithm that made it
+    pub algorithm: ChecksumAlgorithm,
+    /// The hash, as lowercase hex
+    pub value: String,
+}
+
+/// The hash algorithms we understand
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Guess the algorithm from the length of a hex hash
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            64 => Some(Self::Sha256),
+            128 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// The name of the algorithm, as used by `shasum -a` and `Get-FileHash -Algorithm`
+    pub fn bits(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "256",
+            Self::Sha512 => "512",
+        }
+    }
+}
+
+/// A line of a checksum file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// The file the hash is for (files with just a hash in them don't say)
+    pub name: Option<String>,
+    /// The hash
+    pub checksum: Checksum,
+}
+
+/// Whether the name of a file looks like it contains checksums for other files
+pub fn is_checksum_file(name: &str) -> bool {
+    CHECKSUM_EXTS.iter().any(|ext| name.ends_with(ext)) || is_aggregate_checksum_file(name)
+}
+
+/// Whether the name of a file looks like it contains checksums for lots of other files
+fn is_aggregate_checksum_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let stem = name.strip_suffix(".txt").unwrap_or(&name);
+    AGGREGATE_CHECKSUM_NAMES.contains(&stem)
+        || stem.ends_with("_checksums")
+        || stem.ends_with("-checksums")
+}
+
+/// Parse the contents of a checksum file
+///
+/// This understands the GNU format (`<hash>  <name>`, optionally with a `*` before the name),
+/// the BSD format (`SHA256 (<name>) = <hash>`), and files that are just a hash.
+pub fn parse_checksums(contents: &str) -> std::result::Result<Vec<ChecksumEntry>, String> {
+    let mut entries = vec![];
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, hash) = if let Some((name, hash)) = parse_bsd_line(line) {
+            (Some(name), hash)
+        } else {
+            let mut parts = line.splitn(2, char::is_whitespace);
+            let hash = parts.next().unwrap_or_default();
+            let name = parts
+                .next()
+                .map(|name| name.trim().trim_start_matches('*'))
+                .filter(|name| !name.is_empty());
+            (name, hash)
+        };
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("`{hash}` isn't a hex hash"));
+        }
+        let Some(algorithm) = ChecksumAlgorithm::from_hex_len(hash.len()) else {
+            return Err(format!("`{hash}` isn't a SHA-256 or SHA-512 hash"));
+        };
+        entries.push(ChecksumEntry {
+            // Some tools write paths, but release assets are all in one place
+            name: name.map(|name| name.rsplit('/').next().unwrap_or(name).to_owned()),
+            checksum: Checksum {
+                algorithm,
+                value: hash.to_lowercase(),
+            },
+        });
+    }
+    if entries.is_empty() {
+        return Err("there aren't any hashes in it".to_owned());
+    }
+    Ok(entries)
+}
+
+/// Parse a line like `SHA256 (app.tar.gz) = <hash>`
+fn parse_bsd_line(line: &str) -> Option<(&str, &str)> {
+    let (algorithm, rest) = line.split_once(" (")?;
+    if !algorithm.starts_with("SHA") {
+        return None;
+    }
+    let (name, hash) = rest.rsplit_once(") = ")?;
+    Some((name, hash.trim()))
+}
+
+impl ReleaseArtifacts {
+    /// Find checksum files that weren't registered by a more precise source (like cargo-dist)
+    ///
+    /// Files named after another file (like `app.tar.gz.sha256`) are registered here, but files
+    /// with lots of checksums (like `SHA256SUMS`) need to be read first, see
+    /// [`ReleaseArtifacts::add_checksums`][].
+    pub fn add_checksum_inference(&mut self) {
+        for checksum_idx in self.file_indices() {
+            let checksum_name = &self.file(checksum_idx).name;
+            if !is_checksum_file(checksum_name) {
+                continue;
+            }
+            // Don't let inference mistake `app.deb.sha256` for a .deb
+            self.file_mut(checksum_idx).infer = false;
+
+            let checksum_name = &self.file(checksum_idx).name;
+            let Some(name) = CHECKSUM_EXTS
+                .iter()
+                .find_map(|ext| checksum_name.strip_suffix(ext))
+            else {
+                continue;
+            };
+            let Some(file_idx) = self.file_idx(&name.to_owned()) else {
+                continue;
+            };
+            let file = self.file_mut(file_idx);
+            if file.checksum_file.is_none() {
+                file.checksum_file = Some(checksum_idx);
+            }
+        }
+    }
+
+    /// Get the files that we should read checksums out of
+    pub fn checksum_files(&self) -> Vec<FileIdx> {
+        let mut checksum_files = vec![];
+        for file in self.files() {
+            if let Some(checksum_file) = file.checksum_file {
+                if !checksum_files.contains(&checksum_file) {
+                    checksum_files.push(checksum_file);
+                }
+            }
+        }
+        for idx in self.file_indices() {
+            if is_aggregate_checksum_file(&self.file(idx).name) && !checksum_files.contains(&idx) {
+                checksum_files.push(idx);
+            }
+        }
+        checksum_files
+    }
+
+    /// Record the checksums listed in the contents of a checksum file
+    pub fn add_checksums(
+        &mut self,
+        checksum_idx: FileIdx,
+        contents: &str,
+    ) -> std::result::Result<(), String> {
+        let entries = parse_checksums(contents)?;
+        for file_idx in self.file_indices() {
+            let file = self.file_mut(file_idx);
+            if file.checksum.is_some() || file_idx == checksum_idx {
+                continue;
+            }
+            let entry = entries
+                .iter()
+                .find(|entry| entry.name.as_deref() == Some(file.name.as_str()))
+                .or_else(|| {
+                    // A file with just a hash is for whichever file points at it
+                    let is_ours = file.checksum_file == Some(checksum_idx);
+                    (is_ours && entries.len() == 1 && entries[0].name.is_none())
+                        .then(|| &entries[0])
+                });
+            if let Some(entry) = entry {
+                file.checksum = Some(entry.checksum.clone());
+                file.checksum_file.get_or_insert(checksum_idx);
+            }
+        }
+        Ok(())
+    }
+}
@@ -112,6 +112,11 @@ const KNOWN_BUNDLE_EXTS: &[&str] = &[
     EXT_BUNDLE_PACMAN,
 ];
 
+// Various extensions for debug symbols
+//
+// (these are often archived, so we need to use "contains" instead of "ends_with")
+const KNOWN_SYMBOLS_EXTS: &[&str] = &[".pdb", ".dSYM", ".dwp", ".debug"];
+
 // Various extensions for
 const EXT_SCRIPT_SHELL: &str = ".sh";
 const EXT_SCRIPT_POWERSHELL: &str = ".ps1";
@@ -154,7 +159,11 @@ impl ReleaseArtifacts {
             let preference;
 
             // Try to detect what kind of file this is
-            if file.name.contains("install")
+            if KNOWN_SYMBOLS_EXTS.iter().any(|ext| file.name.contains(ext)) {
+                // Looks like debug symbols, which aren't an installer, but get listed separately
+                self.add_symbols(file_idx, targets);
+                continue;
+            } else if file.name.contains("install")
                 && KNOWN_SCRIPT_EXTS.iter().any(|ext| file.name.ends_with(ext))
             {
                 // Looks like an installer script! Recommend a ~curl|sh for it.
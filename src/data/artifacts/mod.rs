@@ -6,7 +6,10 @@
 //!
 //! * [`ReleaseArtifacts::add_github`][] (in different file)
 //! * [`ReleaseArtifacts::add_cargo_dist`][] (in a different file)
+//! * [`ReleaseArtifacts::add_checksum_inference`][] (in a different file)
+//! * [`ReleaseArtifacts::add_signature_inference`][] (in a different file)
 //! * [`ReleaseArtifacts::add_inference`][] (in a different file)
+//! * [`ReleaseArtifacts::add_local_assets`][] (in a different file)
 //! * [`ReleaseArtifacts::add_package_managers`][]
 //!
 //! One you've added all the data you want, call [`ReleaseArtifacts::select_installers`][]
@@ -14,18 +17,24 @@
 
 use std::collections::{BTreeMap, HashMap};
 
-use axoasset::{LocalAsset, RemoteAsset};
+use axoasset::LocalAsset;
 use camino::Utf8PathBuf;
 use indexmap::IndexMap;
 use serde::{Serialize, Serializer};
 
 use crate::config::ArtifactsConfig;
 use crate::config::Config;
+use crate::data::cache::HttpCache;
 use crate::errors::*;
 
 use inference::{KNOWN_SCRIPT_EXTS, KNOWN_TARGET_TRIPLES};
 
+pub use checksums::{Checksum, ChecksumAlgorithm};
+pub use signatures::{Signature, SignatureKind};
+
+pub mod checksums;
 pub mod inference;
+pub mod signatures;
 
 /// A Target Triple like x86_64-pc-windows-msvc
 pub type TargetTriple = String;
@@ -51,6 +60,10 @@ pub struct ReleaseArtifacts {
     /// What installers to use for each target, in descending order
     /// (so recommend the first one, potentially show the others in tabs)
     targets: BTreeMap<TargetTriple, Vec<InstallerIdx>>,
+    /// Debug symbols for the builds in the Release
+    symbols: Vec<DebugSymbols>,
+    /// SLSA provenance for every file in the Release
+    release_provenance: Option<FileIdx>,
 }
 
 /// A handle to a File (equivalent to a pointer into `ReleaseArtifacts::files`)
@@ -68,6 +81,13 @@ pub struct File {
     pub view_path: Option<String>,
     /// A file containing checksums for this one
     pub checksum_file: Option<FileIdx>,
+    /// The checksum of this file, if we managed to read it from the checksum file
+    pub checksum: Option<Checksum>,
+    /// Signatures and attestations for this file
+    pub signatures: Vec<Signature>,
+    /// A local copy of the file (e.g. from a release snapshot)
+    #[serde(skip)]
+    pub local_path: Option<Utf8PathBuf>,
     /// Whether artifact_inference should process this file
     ///
     /// Starts true, but can be set to false by other steps to avoid suggesting an installer twice
@@ -75,6 +95,15 @@ pub struct File {
     pub infer: bool,
 }
 
+/// Debug symbols for a build, which aren't an installer, but are worth listing
+#[derive(Debug, Clone, Serialize)]
+pub struct DebugSymbols {
+    /// The file with the symbols in it
+    pub file: FileIdx,
+    /// The targets of the build the symbols are for
+    pub targets: Vec<TargetTriple>,
+}
+
 /// A handle to an Installer (equivalent to a pointer into [`ReleaseArtifacts::installers`][])
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize)]
 pub struct InstallerIdx(pub usize);
@@ -201,6 +230,17 @@ impl ReleaseArtifacts {
             .enumerate()
             .map(|(idx, ins)| (InstallerIdx(idx), ins))
     }
+    /// Record that a file contains debug symbols
+    pub fn add_symbols(&mut self, file: FileIdx, targets: Vec<TargetTriple>) {
+        if !self.symbols.iter().any(|symbols| symbols.file == file) {
+            self.symbols.push(DebugSymbols { file, targets });
+        }
+        self.file_mut(file).infer = false;
+    }
+    /// Get all the debug symbols
+    pub fn symbols(&self) -> &[DebugSymbols] {
+        &self.symbols
+    }
     /// Get all target -> installer mappings
     pub fn installers_by_target(&self) -> &BTreeMap<TargetTriple, Vec<InstallerIdx>> {
         &self.targets
@@ -272,11 +312,30 @@ impl ReleaseArtifacts {
     }
 
     /// Make shell scripts viewable by copying the files to be statically hosted instead of hotlinked
-    pub fn make_scripts_viewable(&mut self, config: &Config) -> Result<()> {
+    pub fn make_scripts_viewable(&mut self, config: &Config, cache: &HttpCache) -> Result<()> {
+        self.make_scripts_viewable_in(config, cache, "")
+    }
+
+    /// Make shell scripts viewable like `make_scripts_viewable`, but host the copies in
+    /// a subdirectory of the site
+    ///
+    /// (used for the install pages of specific releases, whose scripts have the same
+    /// names as the latest release's)
+    pub fn make_scripts_viewable_in(
+        &mut self,
+        config: &Config,
+        cache: &HttpCache,
+        dir: &str,
+    ) -> Result<()> {
         for file in self.files.values_mut() {
             if KNOWN_SCRIPT_EXTS.iter().any(|ext| file.name.ends_with(ext)) {
-                let path = write_source(config, file)?;
-                file.view_path = Some(path);
+                match write_source(config, cache, dir, file) {
+                    Ok(path) => file.view_path = Some(path),
+                    // If we're offline and have no copy of the file, just keep linking
+                    // to the original download
+                    Err(OrandaError::OfflineCacheMiss { .. }) => {}
+                    Err(e) => return Err(e),
+                }
             }
         }
         Ok(())
@@ -314,14 +373,24 @@ where
     files.serialize(s)
 }
 
-/// Make the source of a file available on the server
-fn write_source(config: &Config, file: &File) -> Result<String> {
-    let file_path = format!("{}.txt", &file.name);
+/// Make the source of a file available on the server, in the given directory
+///
+/// The copy is only rewritten if the file changed, so an incremental build leaves it alone.
+fn write_source(config: &Config, cache: &HttpCache, dir: &str, file: &File) -> Result<String> {
+    let file_path = if dir.is_empty() {
+        format!("{}.txt", &file.name)
+    } else {
+        format!("{}/{}.txt", dir.trim_end_matches('/'), &file.name)
+    };
     let full_file_path = Utf8PathBuf::from(&config.dist_dir).join(&file_path);
-    if !full_file_path.exists() {
-        let file_string_future = RemoteAsset::load_string(&file.download_url);
-        let file_string = tokio::runtime::Handle::current().block_on(file_string_future)?;
-        LocalAsset::write_new(&file_string, &full_file_path)?;
+    let file_string = if let Some(local_path) = &file.local_path {
+        LocalAsset::load_string(local_path)?
+    } else {
+        let file_string_future = cache.fetch(&file.download_url);
+        tokio::runtime::Handle::current().block_on(file_string_future)?
+    };
+    if LocalAsset::load_string(&full_file_path).ok().as_ref() != Some(&file_string) {
+        LocalAsset::write_new_all(&file_string, full_file_path)?;
     }
     Ok(file_path)
 }
@@ -0,0 +1,146 @@
+//! Logic for finding the signatures and attestations that come with files in a release
+//!
+//! These are always separate files named after the one they're for (`app.tar.gz.sig`,
+//! `app.tar.gz.minisig`...), except for SLSA provenance, which usually covers a whole
+//! release in one `.intoto.jsonl` file.
+
+use serde::Serialize;
+
+use super::*;
+
+/// Extensions for a detached GPG signature
+const GPG_EXTS: &[&str] = &[".asc", ".sig", ".gpg"];
+/// Extensions for a minisign signature
+const MINISIGN_EXTS: &[&str] = &[".minisig"];
+/// Extensions for a sigstore bundle made by cosign
+const COSIGN_BUNDLE_EXTS: &[&str] = &[".sigstore.json", ".sigstore", ".bundle"];
+/// Extensions for the certificate that goes with a cosign signature
+const COSIGN_CERTIFICATE_EXTS: &[&str] = &[".pem", ".crt", ".cert"];
+/// Extensions for an in-toto attestation, like SLSA provenance
+const PROVENANCE_EXTS: &[&str] = &[".intoto.jsonl"];
+
+/// A file that can be used to check another file is legit
+#[derive(Debug, Clone, Serialize)]
+pub struct Signature {
+    /// What kind of signature this is
+    pub kind: SignatureKind,
+    /// The file with the signature in it
+    pub file: FileIdx,
+    /// For cosign signatures that aren't bundles, the file with the signing certificate
+    pub certificate: Option<FileIdx>,
+}
+
+/// The kinds of signatures/attestations we know how to verify
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum SignatureKind {
+    /// A detached GPG signature
+    Gpg,
+    /// A minisign signature
+    Minisign,
+    /// A sigstore signature (or bundle), as made by cosign
+    Cosign,
+    /// SLSA provenance
+    Provenance,
+}
+
+impl SignatureKind {
+    /// A brief label for this kind of signature
+    pub fn label(&self) -> &'static str {
+        match self {
+            SignatureKind::Gpg => "gpg",
+            SignatureKind::Minisign => "minisign",
+            SignatureKind::Cosign => "cosign",
+            SignatureKind::Provenance => "provenance",
+        }
+    }
+}
+
+/// Whether the name of a file looks like it's a signature, attestation, or certificate
+pub fn is_signature_file(name: &str) -> bool {
+    [
+        GPG_EXTS,
+        MINISIGN_EXTS,
+        COSIGN_BUNDLE_EXTS,
+        COSIGN_CERTIFICATE_EXTS,
+        PROVENANCE_EXTS,
+    ]
+    .iter()
+    .copied()
+    .flatten()
+    .any(|ext| name.ends_with(ext))
+}
+
+/// Split a file name into the name of the file it's a signature for, and the kind of signature
+fn split_signature_name(name: &str) -> Option<(&str, SignatureKind)> {
+    let kinds: &[(&[&str], SignatureKind)] = &[
+        (MINISIGN_EXTS, SignatureKind::Minisign),
+        (COSIGN_BUNDLE_EXTS, SignatureKind::Cosign),
+        (PROVENANCE_EXTS, SignatureKind::Provenance),
+        (GPG_EXTS, SignatureKind::Gpg),
+    ];
+    kinds.iter().find_map(|(exts, kind)| {
+        exts.iter()
+            .find_map(|ext| name.strip_suffix(ext))
+            .map(|base| (base, *kind))
+    })
+}
+
+impl ReleaseArtifacts {
+    /// Find signatures and attestations for each file, based on their names
+    pub fn add_signature_inference(&mut self) {
+        for sig_idx in self.file_indices() {
+            let sig_name = self.file(sig_idx).name.clone();
+            if !is_signature_file(&sig_name) {
+                continue;
+            }
+            // Don't let inference mistake `app.deb.sig` for a .deb
+            self.file_mut(sig_idx).infer = false;
+
+            let Some((base, mut kind)) = split_signature_name(&sig_name) else {
+                continue;
+            };
+            let Some(file_idx) = self.file_idx(&base.to_owned()) else {
+                // Provenance that isn't named after a file is for the whole release
+                if kind == SignatureKind::Provenance {
+                    self.release_provenance.get_or_insert(sig_idx);
+                }
+                continue;
+            };
+
+            // A `.sig` with a certificate next to it is from cosign, not gpg
+            let mut certificate = None;
+            if kind == SignatureKind::Gpg {
+                certificate = COSIGN_CERTIFICATE_EXTS
+                    .iter()
+                    .find_map(|ext| self.file_idx(&format!("{base}{ext}")));
+                if certificate.is_some() {
+                    kind = SignatureKind::Cosign;
+                }
+            }
+            self.file_mut(file_idx).signatures.push(Signature {
+                kind,
+                file: sig_idx,
+                certificate,
+            });
+        }
+    }
+
+    /// Get the signatures of a file, including provenance for the whole release
+    pub fn signatures(&self, idx: FileIdx) -> Vec<Signature> {
+        let mut signatures = self.file(idx).signatures.clone();
+        if let Some(provenance) = self.release_provenance {
+            if !signatures
+                .iter()
+                .any(|sig| sig.kind == SignatureKind::Provenance)
+            {
+                signatures.push(Signature {
+                    kind: SignatureKind::Provenance,
+                    file: provenance,
+                    certificate: None,
+                });
+            }
+        }
+        signatures.sort_by_key(|sig| sig.kind);
+        signatures
+    }
+}
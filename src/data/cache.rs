@@ -0,0 +1,335 @@
+//! A persistent on-disk cache for things we fetch over HTTP
+//!
+//! Entries are keyed by URL. An entry younger than the configured TTL is used without
+//! touching the network at all. Older entries are revalidated with the `ETag` and
+//! `Last-Modified` headers the server originally gave us, which is much cheaper than
+//! refetching (and for GitHub, doesn't count against the API rate limit).
+//!
+//! If we're offline, any cached entry is used regardless of its age.
+//!
+//! This is also where we deal with the forge APIs' quirks: authenticating with a token,
+//! following their pagination links, and noticing when we've hit GitHub's rate limit.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+use octolotl::Requestable;
+use reqwest::header::{
+    HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK, USER_AGENT,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::errors::*;
+use crate::message::{Message, MessageType};
+
+/// Info about a cached response, stored next to its body
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// The URL this is a response for
+    url: String,
+    /// The `ETag` header of the response
+    etag: Option<String>,
+    /// The `Last-Modified` header of the response
+    last_modified: Option<String>,
+    /// When we last confirmed this response was up to date (seconds since the unix epoch)
+    fetched_at: u64,
+    /// The URL of the next page of results, from the `Link` (or `X-Next-Page`) header of the
+    /// response
+    #[serde(default)]
+    next_page: Option<String>,
+}
+
+/// One page of a paginated API response
+pub struct Page {
+    /// The contents of the page
+    pub body: String,
+    /// The URL of the next page, if there is one
+    pub next: Option<String>,
+}
+
+/// Fetches things over HTTP, consulting an on-disk cache first
+pub struct HttpCache {
+    /// Where entries are stored (or None if caching is disabled)
+    dir: Option<Utf8PathBuf>,
+    /// How long an entry can be used without revalidating it
+    ttl: Duration,
+    /// Whether we're forbidden from touching the network
+    offline: bool,
+    /// A token to authenticate requests to the GitHub API with
+    github_token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpCache {
+    pub fn new(config: &Config) -> Self {
+        let (dir, ttl) = match &config.cache {
+            Some(cache) => (Some(cache.dir()), cache.ttl()),
+            None => (None, Duration::ZERO),
+        };
+        Self {
+            dir,
+            ttl,
+            offline: config.offline,
+            github_token: config.releases.github_token(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Fetch something from GitHub, preferring the axo proxy but falling back to GitHub itself
+    pub async fn fetch_github(&self, item: &impl Requestable) -> Result<String> {
+        match self.fetch(&item.proxy_url()).await {
+            Ok(text) => Ok(text),
+            Err(e) => {
+                tracing::warn!("Fetching from proxy encountered an error: {e}. Falling back to GitHub directly...");
+                self.fetch(&item.github_url()).await
+            }
+        }
+    }
+
+    /// Whether we have a token to authenticate requests to the GitHub API with
+    pub fn has_github_token(&self) -> bool {
+        self.github_token.is_some()
+    }
+
+    /// Fetch one page of a GitHub API response, authenticating if we have a token
+    ///
+    /// Only ever call this with URLs on the GitHub API, so the token doesn't leak elsewhere.
+    pub async fn fetch_github_page(&self, url: &str) -> Result<Page> {
+        self.fetch_page(url, self.github_token.as_deref()).await
+    }
+
+    /// Fetch the contents of a URL as text
+    pub async fn fetch(&self, url: &str) -> Result<String> {
+        Ok(self.fetch_page(url, None).await?.body)
+    }
+
+    /// Fetch one page of a paginated API response, without authenticating
+    pub async fn fetch_unauthenticated_page(&self, url: &str) -> Result<Page> {
+        self.fetch_page(url, None).await
+    }
+
+    /// Fetch a URL, optionally with a bearer token
+    async fn fetch_page(&self, url: &str, token: Option<&str>) -> Result<Page> {
+        let cached = self.load(url);
+        if let Some((entry, body)) = &cached {
+            if self.offline || now().saturating_sub(entry.fetched_at) < self.ttl.as_secs() {
+                tracing::debug!("using cached response for {url}");
+                return Ok(Page {
+                    body: body.clone(),
+                    next: entry.next_page.clone(),
+                });
+            }
+        }
+        if self.offline {
+            return Err(OrandaError::OfflineCacheMiss {
+                url: url.to_owned(),
+            });
+        }
+
+        let mut request = self.client.get(url).header(USER_AGENT, user_agent());
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        if let Some((entry, _)) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = match request.send().await {
+            Ok(response) => match rate_limit_reset(&response) {
+                Some(reset) => Err(OrandaError::GithubRateLimit {
+                    reset,
+                    authenticated: token.is_some(),
+                }),
+                None => response.error_for_status().map_err(OrandaError::from),
+            },
+            Err(e) => Err(e.into()),
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                // A stale answer is better than no answer
+                if let Some((entry, body)) = cached {
+                    if let OrandaError::GithubRateLimit { .. } = e {
+                        // Unlike a flaky network, this won't go away by itself, so say so
+                        let message = format!("{e}, so using an older cached copy of {url}");
+                        Message::new(MessageType::Warning, &message).print();
+                    } else {
+                        tracing::warn!(
+                            "failed to revalidate {url}, using stale cached response: {e}"
+                        );
+                    }
+                    return Ok(Page {
+                        body,
+                        next: entry.next_page,
+                    });
+                }
+                return Err(e);
+            }
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some((mut entry, body)) = cached {
+                tracing::debug!("cached response for {url} is still valid");
+                entry.fetched_at = now();
+                self.store(&entry, None);
+                return Ok(Page {
+                    body,
+                    next: entry.next_page,
+                });
+            }
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned())
+        };
+        let entry = CacheEntry {
+            url: url.to_owned(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched_at: now(),
+            next_page: next_page(url, response.headers()),
+        };
+        let body = response.text().await?;
+        self.store(&entry, Some(&body));
+        Ok(Page {
+            body,
+            next: entry.next_page,
+        })
+    }
+
+    /// Get the paths of the entry and body files for a URL
+    fn entry_paths(&self, url: &str) -> Option<(Utf8PathBuf, Utf8PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        Some((
+            dir.join(format!("{key}.json")),
+            dir.join(format!("{key}.body")),
+        ))
+    }
+
+    /// Load a cached response, if we have one
+    fn load(&self, url: &str) -> Option<(CacheEntry, String)> {
+        let (entry_path, body_path) = self.entry_paths(url)?;
+        if !entry_path.exists() || !body_path.exists() {
+            return None;
+        }
+        let entry = LocalAsset::load_string(&entry_path).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&entry).ok()?;
+        // Guard against the (astronomically unlikely) hash collision
+        if entry.url != url {
+            return None;
+        }
+        let body = LocalAsset::load_string(&body_path).ok()?;
+        Some((entry, body))
+    }
+
+    /// Store a response (or just update its entry, if the body is None)
+    ///
+    /// Failing to write to the cache isn't worth failing the build over, so we just log it.
+    fn store(&self, entry: &CacheEntry, body: Option<&str>) {
+        let Some((entry_path, body_path)) = self.entry_paths(&entry.url) else {
+            return;
+        };
+        let result = (|| -> Result<()> {
+            if let Some(body) = body {
+                LocalAsset::write_new_all(body, &body_path)?;
+            }
+            LocalAsset::write_new_all(&serde_json::to_string(entry)?, &entry_path)?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            tracing::warn!("failed to cache response for {}: {e}", entry.url);
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// If a response says we've run out of API requests, get when the limit resets
+fn rate_limit_reset(response: &reqwest::Response) -> Option<String> {
+    if !matches!(
+        response.status(),
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ) {
+        return None;
+    }
+    let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok());
+    if header("x-ratelimit-remaining")? != "0" {
+        return None;
+    }
+    let reset = header("x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok());
+    let reset = reset
+        .and_then(|secs| chrono::NaiveDateTime::from_timestamp_opt(secs, 0))
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "an unknown time".to_owned());
+    Some(reset)
+}
+
+/// Get the URL of the next page of results from a response's headers
+///
+/// GitHub and Gitea send a `Link` header that looks like
+/// `<https://api.github.com/...?page=2>; rel="next", <...>; rel="last"`. GitLab sends that
+/// too, but can leave it out for big result sets, so we also understand its `X-Next-Page`
+/// header, which is just the number of the next page (or empty on the last one).
+fn next_page(url: &str, headers: &HeaderMap) -> Option<String> {
+    let from_link = headers
+        .get(LINK)
+        .and_then(|link| link.to_str().ok())
+        .and_then(next_page_link);
+    if from_link.is_some() {
+        return from_link;
+    }
+
+    let page = headers.get("x-next-page")?.to_str().ok()?.trim();
+    if page.is_empty() {
+        return None;
+    }
+    let mut next = url::Url::parse(url).ok()?;
+    let query = next
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("page", page);
+    Some(next.to_string())
+}
+
+/// Find the `rel="next"` URL in a `Link` header
+fn next_page_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"");
+        is_next.then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_owned()
+        })
+    })
+}
+
+fn user_agent() -> String {
+    format!("oranda-{}", env!("CARGO_PKG_VERSION"))
+}
@@ -86,9 +86,16 @@ impl ReleaseArtifacts {
                             Utf8PathBuf::from(id).extension().unwrap_or(id).to_owned()
                         };
                     }
+                    ArtifactKind::Symbols => {
+                        // These aren't something you install, but they're listed separately
+                        if let Some(file) = file {
+                            self.add_symbols(file, artifact.target_triples.clone());
+                        }
+                        continue;
+                    }
                     _ => {
                         // We don't care about these *yet*
-                        // (notably skipped: Symbols, Checksum)
+                        // (notably skipped: Checksum, which we get from the artifacts that have them)
                         continue;
                     }
                 };
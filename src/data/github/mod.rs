@@ -1,6 +1,10 @@
+use crate::data::cache::HttpCache;
+use crate::data::source::ReleaseSource;
 use crate::errors::*;
 
+use async_trait::async_trait;
 use axoasset::SourceFile;
+use octolotl::Requestable;
 use serde::{Deserialize, Serialize};
 
 mod repo;
@@ -15,7 +19,9 @@ pub struct GithubRelease {
     pub url: String,
     pub assets_url: String,
     pub html_url: String,
-    pub id: i64,
+    /// Missing for forges that don't give releases an id of their own (like GitLab)
+    #[serde(default)]
+    pub id: Option<i64>,
     pub tag_name: String,
     pub target_commitish: String,
     pub name: Option<String>,
@@ -48,19 +54,45 @@ pub struct GithubReleaseAsset {
 }
 
 impl GithubRelease {
-    pub async fn fetch_all(repo: &GithubRepo) -> Result<Vec<GithubRelease>> {
-        let request = octolotl::request::Releases::new(&repo.owner, &repo.name);
-        match octolotl::Request::send(&request, true).await {
-            Ok(r) => {
-                let res: serde_json::Value = serde_json::from_str(&r.text().await?)?;
-                let pretty_response = serde_json::to_string_pretty(&res)?;
-                Ok(
-                    SourceFile::new("", pretty_response)
-                        .deserialize_json::<Vec<GithubRelease>>()?,
-                )
-            }
-            Err(e) => Err(OrandaError::GithubReleasesFetchError { details: e }),
+    /// Fetch every page of a repository's releases
+    pub async fn fetch_all(repo: &GithubRepo, cache: &HttpCache) -> Result<Vec<GithubRelease>> {
+        let mut releases = vec![];
+        let mut next = Some(format!(
+            "{}/repos/{}/{}/releases?per_page=100",
+            repo.api_url, repo.owner, repo.name
+        ));
+        while let Some(url) = next {
+            let page = match cache.fetch_github_page(&url).await {
+                Ok(page) => page,
+                Err(OrandaError::GithubRateLimit { reset, .. })
+                    if releases.is_empty() && !cache.has_github_token() =>
+                {
+                    // The axo proxy doesn't share our rate limit, but only gives us the first page
+                    tracing::warn!("Hit the GitHub API rate limit (resets at {reset}), falling back to the proxy for your latest releases...");
+                    let request = octolotl::request::Releases::new(&repo.owner, &repo.name);
+                    let text = cache.fetch(&request.proxy_url()).await.map_err(|_| {
+                        OrandaError::GithubRateLimit {
+                            reset,
+                            authenticated: false,
+                        }
+                    })?;
+                    return Self::parse_page(&text);
+                }
+                Err(OrandaError::Reqwest(e)) => {
+                    return Err(OrandaError::GithubReleasesFetchError { details: e })
+                }
+                Err(e) => return Err(e),
+            };
+            releases.extend(Self::parse_page(&page.body)?);
+            next = page.next;
         }
+        Ok(releases)
+    }
+
+    fn parse_page(text: &str) -> Result<Vec<GithubRelease>> {
+        let res: serde_json::Value = serde_json::from_str(text)?;
+        let pretty_response = serde_json::to_string_pretty(&res)?;
+        Ok(SourceFile::new("", pretty_response).deserialize_json::<Vec<GithubRelease>>()?)
     }
 
     pub fn has_dist_manifest(&self) -> bool {
@@ -77,6 +109,13 @@ impl GithubRelease {
     }
 }
 
+#[async_trait]
+impl ReleaseSource for GithubRepo {
+    async fn fetch_releases(&self, cache: &HttpCache) -> Result<Vec<GithubRelease>> {
+        GithubRelease::fetch_all(self, cache).await
+    }
+}
+
 impl ReleaseArtifacts {
     pub fn add_github(&mut self, release: &GithubRelease) {
         for asset in &release.assets {
@@ -86,6 +125,9 @@ impl ReleaseArtifacts {
                 // The rest of these are filled in later
                 view_path: None,
                 checksum_file: None,
+                checksum: None,
+                signatures: vec![],
+                local_path: None,
                 infer: true,
             };
             self.add_file(file);
@@ -3,6 +3,8 @@ use crate::errors::*;
 use miette::{miette, IntoDiagnostic};
 use url::Url;
 
+const GITHUB_API_URL: &str = "https://api.github.com";
+
 /// Represents a GitHub repository that we can query things about.
 #[derive(Debug, Clone)]
 pub struct GithubRepo {
@@ -10,6 +12,8 @@ pub struct GithubRepo {
     pub owner: String,
     /// The repository name.
     pub name: String,
+    /// The root of the GitHub REST API.
+    pub api_url: String,
 }
 
 impl GithubRepo {
@@ -33,6 +37,7 @@ impl GithubRepo {
                     return Ok(Self {
                         owner,
                         name,
+                        api_url: GITHUB_API_URL.to_owned(),
                     });
                 } else {
                     return Err(OrandaError::RepoParseError {
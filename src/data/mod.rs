@@ -1,20 +1,29 @@
-use crate::config::ArtifactsConfig;
-use crate::data::github::{GithubRelease, GithubRepo};
+use camino::Utf8Path;
+
+use crate::config::{ArtifactsConfig, Config, LatestReleaseKind};
+use crate::data::cache::HttpCache;
+use crate::data::snapshot::ReleaseSnapshot;
+use crate::data::source::{ReleaseSource, Repo};
+use crate::data::tags::{ReleaseLine, TagFilter};
 use crate::errors::*;
 use crate::message::{Message, MessageType};
 
 pub mod artifacts;
+pub mod cache;
 pub mod cargo_dist;
 pub mod funding;
 pub mod github;
 mod release;
+pub mod snapshot;
+pub mod source;
+pub mod tags;
 
 pub use release::Release;
 
 pub struct Context {
-    /// Info from Github
-    pub repo: GithubRepo,
-    /// All of the releases, currently from newest to oldest
+    /// The repository the releases come from
+    pub repo: Repo,
+    /// All of the releases, from highest to lowest version
     pub releases: Vec<Release>,
     /// Whether any of the 'releases` are prereleases
     ///
@@ -22,17 +31,72 @@ pub struct Context {
     pub has_prereleases: bool,
     /// Index into `releases` for the "best" latest release
     ///
-    /// (e.g. prefers stable releases over prereleases)
+    /// (picked according to `releases.latest_priority`, which by default prefers
+    /// stable releases over prereleases)
     pub latest_release: Option<usize>,
+    /// The release lines (e.g. `1.x` and `2.x`), from highest to lowest version
+    pub release_lines: Vec<ReleaseLine>,
     /// Whether any of the `releases` have anything useful for
     /// the artifacts subsystem.
     pub has_artifacts: bool,
 }
 
 impl Context {
-    pub fn new(repo_url: &str, artifacts_config: &ArtifactsConfig) -> Result<Self> {
-        let repo = GithubRepo::from_url(repo_url)?;
-        Self::fetch_all_releases(repo, artifacts_config)
+    /// Get all the release data for a repo
+    ///
+    /// This comes from the snapshot in the config if there is one, or the network otherwise.
+    pub fn new(repo_url: &str, config: &Config) -> Result<Self> {
+        let repo = Repo::from_url(repo_url, config.releases.source)?;
+        if let Some(snapshot) = &config.releases.snapshot {
+            let msg = format!("Loading releases from snapshot at {}", snapshot);
+            Message::new(MessageType::Info, &msg).print();
+            tracing::info!("{}", &msg);
+            return Self::new_from_snapshot(repo, Utf8Path::new(snapshot), config);
+        }
+
+        let cache = HttpCache::new(config);
+        match Self::fetch_all_releases(repo.clone(), config, &cache) {
+            Err(OrandaError::OfflineCacheMiss { .. }) if config.offline => {
+                let msg = "Running offline without a release snapshot or cached releases, so no release data will be shown";
+                Message::new(MessageType::Warning, msg).print();
+                tracing::warn!("{}", msg);
+                Ok(Self::new_empty(repo))
+            }
+            result => result,
+        }
+    }
+
+    /// Load all the releases from a local snapshot instead of asking the network
+    pub fn new_from_snapshot(
+        repo: Repo,
+        snapshot_path: &Utf8Path,
+        config: &Config,
+    ) -> Result<Self> {
+        let ReleaseSnapshot {
+            releases,
+            assets_dir,
+        } = ReleaseSnapshot::load(snapshot_path)?;
+        let filter = TagFilter::new(&config.releases)?;
+        let all = releases
+            .into_iter()
+            .filter(|release| filter.matches(&release.release.tag_name))
+            .map(|release| Release::from_snapshot(release, &assets_dir, &config.artifacts))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::with_releases(repo, all, config))
+    }
+
+    /// Make a Context that has no releases at all
+    ///
+    /// (used when we're offline and have nowhere to get releases from)
+    pub fn new_empty(repo: Repo) -> Self {
+        Self {
+            repo,
+            releases: vec![],
+            has_prereleases: false,
+            latest_release: None,
+            release_lines: vec![],
+            has_artifacts: false,
+        }
     }
 
     /// Get the latest release, if it exists
@@ -46,27 +110,69 @@ impl Context {
             .and_then(|idx| self.releases.get_mut(idx))
     }
 
-    /// Fetch and process all the Github Releases to produce a final result
-    #[allow(clippy::unnecessary_unwrap)]
-    pub fn fetch_all_releases(
-        repo: GithubRepo,
-        artifacts_config: &ArtifactsConfig,
-    ) -> Result<Self> {
-        let gh_releases =
-            tokio::runtime::Handle::current().block_on(GithubRelease::fetch_all(&repo))?;
+    /// Fetch and process all the releases from a source to produce a final result
+    pub fn fetch_all_releases<S>(repo: S, config: &Config, cache: &HttpCache) -> Result<Self>
+    where
+        S: ReleaseSource + Into<Repo>,
+    {
+        let filter = TagFilter::new(&config.releases)?;
+        let gh_releases = tokio::runtime::Handle::current().block_on(repo.fetch_releases(cache))?;
         let all =
             tokio::runtime::Handle::current().block_on(futures_util::future::try_join_all(
                 gh_releases
                     .into_iter()
-                    .map(|gh_release| Release::new(gh_release, &repo, artifacts_config)),
+                    .filter(|gh_release| filter.matches(&gh_release.tag_name))
+                    .map(|gh_release| Release::new(gh_release, &repo, &config.artifacts, cache)),
             ))?;
 
-        // Walk through all the releases (from newest to oldest) to find the latest ones
-        //
-        // FIXME?: I think this is essentially deferring to Release Date over Version Number.
-        // In most cases those things are in agreement, but anyone who does patch releases
-        // of older versions will have a more chaotic history. When we have more robust
-        // handling of release tags we should try to sort/filter this better.
+        let mut context = Self::with_releases(repo.into(), all, config);
+        if config.artifacts.checksums() {
+            context.fetch_checksums(&config.artifacts, cache);
+        }
+        Ok(context)
+    }
+
+    /// Get the indices of the releases that get their own install page
+    ///
+    /// These are the highest versions that have something to install, up to
+    /// `artifacts.versioned_pages` of them.
+    pub fn versioned_releases(&self, artifacts_config: &ArtifactsConfig) -> Vec<usize> {
+        self.releases
+            .iter()
+            .enumerate()
+            .filter(|(_, release)| release.has_installers())
+            .map(|(idx, _)| idx)
+            .take(artifacts_config.versioned_pages())
+            .collect()
+    }
+
+    /// Download the checksum files of the releases whose hashes we actually show
+    ///
+    /// That's the latest release and the ones with install pages. Every other release
+    /// would just be more requests (and more of the GitHub rate limit) for nothing.
+    fn fetch_checksums(&mut self, artifacts_config: &ArtifactsConfig, cache: &HttpCache) {
+        let mut shown = self.versioned_releases(artifacts_config);
+        shown.extend(self.latest_release);
+        let repo = &self.repo;
+        let fetches = self
+            .releases
+            .iter_mut()
+            .enumerate()
+            .filter(|(idx, _)| shown.contains(idx))
+            .map(|(_, release)| release.fetch_checksums(repo, cache));
+        tokio::runtime::Handle::current().block_on(futures_util::future::join_all(fetches));
+    }
+
+    /// Process a list of releases to produce a final result
+    #[allow(clippy::unnecessary_unwrap)]
+    fn with_releases(repo: Repo, mut all: Vec<Release>, config: &Config) -> Self {
+        let artifacts_config = &config.artifacts;
+        // Sort by version, so that backports of older versions don't get mistaken for the
+        // latest release. Tags that aren't versions stay in the order the source gave us
+        // (newest to oldest), after all the ones that are.
+        tags::sort_by_version(&mut all, config.releases.tag_prefix.as_deref());
+
+        // Walk through all the releases (from highest to lowest version) to find the latest ones
         let mut has_prereleases = false;
         let mut has_artifacts = false;
         let mut warned = false;
@@ -117,40 +223,45 @@ impl Context {
             }
         }
 
-        // If we found a stable cargo-dist release, but there's even newer stable releases
-        // that don't use cargo-dist, we're going to prefer the cargo-dist one, but we should
-        // warn the user that things are wonky
-        if latest_dist_stable_release.is_some()
+        // To select the latest release, we go through the configured priority (by default:
+        // dist stable, normal stable, dist prerelease, normal prerelease), and the first
+        // kind we have a release of wins.
+        //
+        // The reason we single out dist releases as special/better by default is a bit of a
+        // legacy hack from before you could filter out "other" releases (like oranda-css) with
+        // `releases.exclude_tags` or `releases.tag_prefix`.
+        let latest_release = config
+            .releases
+            .latest_priority()
+            .iter()
+            .find_map(|kind| match kind {
+                LatestReleaseKind::DistStable => latest_dist_stable_release,
+                LatestReleaseKind::Stable => latest_stable_release,
+                LatestReleaseKind::DistPrerelease => latest_dist_prerelease,
+                LatestReleaseKind::Prerelease => latest_prerelease,
+            });
+
+        // If we picked a stable cargo-dist release, but there's even newer stable releases
+        // that don't use cargo-dist, we should warn the user that things are wonky
+        if latest_release.is_some()
+            && latest_release == latest_dist_stable_release
             && latest_dist_stable_release != latest_stable_release
         {
             let dist_rel = &all[latest_dist_stable_release.unwrap()].source.tag_name;
             let stable_rel = &all[latest_stable_release.unwrap()].source.tag_name;
-            let msg = format!("You have newer stable Github Releases ({}) than your latest cargo-dist Release ({}). Is this intended? (We're going to prefer the cargo-dist one.)", stable_rel, dist_rel);
+            let msg = format!("You have newer stable Github Releases ({}) than your latest cargo-dist Release ({}). Is this intended? (We're going to prefer the cargo-dist one, set `releases.latest_priority` to change this.)", stable_rel, dist_rel);
             Message::new(MessageType::Warning, &msg).print();
         }
 
-        // To select the latest release, we use the following priority: (first valid entry wins)
-        //
-        // * dist stable
-        // * normal stable
-        // * dist prerelease
-        // * normal prerelease
-        //
-        // The reason we single out dist releases as special/better is a bit of
-        // a legacy hack to keep things working while we don't have a good mechanism for filtering
-        // out "other" github release like oranda-css. In the future we will only care about
-        // stable vs unstable.
-        let latest_release = latest_dist_stable_release
-            .or(latest_stable_release)
-            .or(latest_dist_prerelease)
-            .or(latest_prerelease);
-
-        Ok(Self {
+        let release_lines = tags::release_lines(&all, config.releases.tag_prefix.as_deref());
+
+        Self {
             repo,
             releases: all,
             has_prereleases,
             has_artifacts,
             latest_release,
-        })
+            release_lines,
+        }
     }
 }
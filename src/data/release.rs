@@ -1,13 +1,19 @@
 use axoasset::SourceFile;
+use camino::Utf8Path;
 use cargo_dist_schema::DistManifest;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
 use crate::config::ArtifactsConfig;
-use crate::data::{cargo_dist, github::GithubRelease, GithubRepo};
+use crate::data::cache::HttpCache;
+use crate::data::snapshot::SnapshotRelease;
+use crate::data::source::ReleaseSource;
+use crate::data::{cargo_dist, github::GithubRelease};
 use crate::errors::*;
 
-use super::artifacts::ReleaseArtifacts;
+use super::artifacts::{FileIdx, ReleaseArtifacts};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Release {
     pub manifest: Option<DistManifest>,
     pub source: GithubRelease,
@@ -17,37 +23,104 @@ pub struct Release {
 impl Release {
     pub async fn new(
         gh_release: GithubRelease,
-        repo: &GithubRepo,
+        repo: &impl ReleaseSource,
         artifacts_config: &ArtifactsConfig,
+        cache: &HttpCache,
     ) -> Result<Self> {
         let manifest = if artifacts_config.cargo_dist() {
-            Self::fetch_manifest(&gh_release, repo).await?
+            Self::fetch_manifest(&gh_release, repo, cache).await?
         } else {
             None
         };
 
-        // Compute the artifacts for this release
-        //
-        // In the future with multi-tenant oranda support, this None
-        // can be replaced with the name of the app we want to focus in on
-        let mut artifacts = ReleaseArtifacts::new(None);
+        Ok(Self::with_manifest(
+            gh_release,
+            manifest,
+            None,
+            artifacts_config,
+        ))
+    }
+
+    /// Create a Release from a local snapshot, without touching the network
+    pub fn from_snapshot(
+        snapshot_release: SnapshotRelease,
+        snapshot_assets_dir: &Utf8Path,
+        artifacts_config: &ArtifactsConfig,
+    ) -> Result<Self> {
+        let SnapshotRelease {
+            release: gh_release,
+            dist_manifest,
+        } = snapshot_release;
+        let tag = &gh_release.tag_name;
+        let assets_dir = snapshot_assets_dir.join(tag);
+
+        let manifest = if !artifacts_config.cargo_dist() {
+            None
+        } else if let Some(inline_manifest) = dist_manifest {
+            Self::parse_manifest(serde_json::to_string(&inline_manifest)?, tag)
+        } else {
+            let manifest_path = assets_dir.join(cargo_dist::MANIFEST_FILENAME);
+            if manifest_path.exists() {
+                let contents = axoasset::LocalAsset::load_string(&manifest_path)?;
+                Self::parse_manifest(contents, tag)
+            } else {
+                if gh_release.has_dist_manifest() {
+                    tracing::warn!(
+                        "{tag} has a {} but the snapshot has no copy of it at {manifest_path}",
+                        cargo_dist::MANIFEST_FILENAME
+                    );
+                }
+                None
+            }
+        };
+
+        let mut release =
+            Self::with_manifest(gh_release, manifest, Some(&assets_dir), artifacts_config);
+        if artifacts_config.checksums() {
+            release.load_checksums();
+        }
+        Ok(release)
+    }
+
+    /// Compute the final Release from all of its already-acquired parts
+    fn with_manifest(
+        gh_release: GithubRelease,
+        manifest: Option<DistManifest>,
+        local_assets_dir: Option<&Utf8Path>,
+        artifacts_config: &ArtifactsConfig,
+    ) -> Self {
+        // Compute the artifacts for this release, focusing on one app if we're
+        // building the page for a package in a workspace
+        let mut artifacts = ReleaseArtifacts::new(artifacts_config.app_name.clone());
 
         // Add data from various sources
         artifacts.add_github(&gh_release);
+        if let Some(local_assets_dir) = local_assets_dir {
+            artifacts.add_local_assets(local_assets_dir);
+        }
         if let Some(manifest) = &manifest {
             artifacts.add_cargo_dist(manifest);
         }
         artifacts.add_package_managers(artifacts_config);
+        artifacts.add_checksum_inference();
+        artifacts.add_signature_inference();
         artifacts.add_inference();
 
         // Compute the final result
         artifacts.select_installers();
 
-        Ok(Self {
+        Self {
             manifest,
             source: gh_release,
             artifacts,
-        })
+        }
+    }
+
+    /// When the release was published, if we know
+    pub fn published_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.source.published_at)
+            .ok()
+            .map(|date| date.with_timezone(&Utc))
     }
 
     /// Gets whether any platform has actual targets to suggest
@@ -55,35 +128,79 @@ impl Release {
         !self.artifacts.installers_by_target().is_empty()
     }
 
+    /// Download the release's checksum files and read the hashes out of them
+    ///
+    /// Problems with checksums aren't worth failing the build over, so this just warns.
+    pub(crate) async fn fetch_checksums(&mut self, repo: &impl ReleaseSource, cache: &HttpCache) {
+        for checksum_idx in self.artifacts.checksum_files() {
+            let name = self.artifacts.file(checksum_idx).name.clone();
+            match repo.fetch_asset(&self.source, &name, cache).await {
+                Ok(contents) => self.add_checksums(checksum_idx, &contents),
+                // If we're offline, the hashes just won't be shown
+                Err(OrandaError::OfflineCacheMiss { .. }) => {}
+                Err(e) => {
+                    let err = OrandaError::ChecksumFetch {
+                        tag: self.source.tag_name.clone(),
+                        name,
+                        details: Box::new(e),
+                    };
+                    eprintln!("{:?}", miette::Report::new(err));
+                }
+            }
+        }
+    }
+
+    /// Read the hashes out of the local copies of the release's checksum files
+    fn load_checksums(&mut self) {
+        for checksum_idx in self.artifacts.checksum_files() {
+            let Some(local_path) = self.artifacts.file(checksum_idx).local_path.clone() else {
+                continue;
+            };
+            match axoasset::LocalAsset::load_string(&local_path) {
+                Ok(contents) => self.add_checksums(checksum_idx, &contents),
+                Err(e) => {
+                    let err = OrandaError::ChecksumFetch {
+                        tag: self.source.tag_name.clone(),
+                        name: self.artifacts.file(checksum_idx).name.clone(),
+                        details: Box::new(e.into()),
+                    };
+                    eprintln!("{:?}", miette::Report::new(err));
+                }
+            }
+        }
+    }
+
+    fn add_checksums(&mut self, checksum_idx: FileIdx, contents: &str) {
+        if let Err(details) = self.artifacts.add_checksums(checksum_idx, contents) {
+            let err = OrandaError::ChecksumParse {
+                tag: self.source.tag_name.clone(),
+                name: self.artifacts.file(checksum_idx).name.clone(),
+                details,
+            };
+            eprintln!("{:?}", miette::Report::new(err));
+        }
+    }
+
     async fn fetch_manifest(
         gh_release: &GithubRelease,
-        repo: &GithubRepo,
+        repo: &impl ReleaseSource,
+        cache: &HttpCache,
     ) -> Result<Option<DistManifest>> {
         let tag = &gh_release.tag_name;
         if gh_release.has_dist_manifest() {
-            let request = octolotl::request::ReleaseAsset::new(
-                &repo.owner,
-                &repo.name,
-                tag,
-                cargo_dist::MANIFEST_FILENAME,
-            );
-            let response = octolotl::Request::send(&request, true)
-                .await?
-                .error_for_status()?;
-
-            Ok(Self::parse_response(response, tag).await?)
+            let contents = repo
+                .fetch_asset(gh_release, cargo_dist::MANIFEST_FILENAME, cache)
+                .await?;
+
+            Ok(Self::parse_manifest(contents, tag))
         } else {
             Ok(None)
         }
     }
 
-    async fn parse_response(
-        response: reqwest::Response,
-        tag: &str,
-    ) -> Result<Option<DistManifest>> {
-        let res = response.text().await?;
-        let src = SourceFile::new("dist-manifest.json", res);
-        Ok(match src.deserialize_json::<DistManifest>() {
+    fn parse_manifest(contents: String, tag: &str) -> Option<DistManifest> {
+        let src = SourceFile::new(cargo_dist::MANIFEST_FILENAME, contents);
+        match src.deserialize_json::<DistManifest>() {
             Ok(manifest) => Some(manifest),
             Err(e) => {
                 // Try partially parsing the manifest to get schema version info
@@ -112,6 +229,6 @@ impl Release {
                 }
                 None
             }
-        })
+        }
     }
 }
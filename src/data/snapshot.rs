@@ -0,0 +1,83 @@
+//! Loading release data from a local snapshot instead of the network
+//!
+//! A snapshot is a JSON array of releases in the same format the GitHub Releases API
+//! returns (e.g. the output of `gh api repos/{owner}/{repo}/releases`). The snapshot
+//! can either be given as that file directly, or as a directory containing it under
+//! the name `releases.json`.
+//!
+//! Release assets (including `dist-manifest.json`) are looked up in a directory named
+//! after the release's tag, next to the JSON file. A release can also inline its
+//! manifest in a `dist_manifest` field, which is handy for hand-written snapshots.
+
+use axoasset::SourceFile;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+use crate::data::github::GithubRelease;
+use crate::errors::*;
+
+use super::artifacts::ReleaseArtifacts;
+
+/// The name of the JSON file we look for when given a snapshot directory
+pub const SNAPSHOT_FILENAME: &str = "releases.json";
+
+/// A release as it appears in a snapshot
+#[derive(Debug, Deserialize)]
+pub struct SnapshotRelease {
+    /// The release itself
+    #[serde(flatten)]
+    pub release: GithubRelease,
+    /// An inline copy of the release's `dist-manifest.json`
+    #[serde(default)]
+    pub dist_manifest: Option<serde_json::Value>,
+}
+
+/// A local copy of all of a project's releases
+#[derive(Debug)]
+pub struct ReleaseSnapshot {
+    /// The releases, in the order the snapshot listed them (newest to oldest)
+    pub releases: Vec<SnapshotRelease>,
+    /// The directory containing per-tag asset directories
+    pub assets_dir: Utf8PathBuf,
+}
+
+impl ReleaseSnapshot {
+    /// Load a snapshot from either a JSON file or a directory containing `releases.json`
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let (json_path, assets_dir) = if path.is_dir() {
+            (path.join(SNAPSHOT_FILENAME), path.to_owned())
+        } else {
+            let parent = path.parent().unwrap_or_else(|| Utf8Path::new(""));
+            (path.to_owned(), parent.to_owned())
+        };
+        let src =
+            SourceFile::load_local(&json_path).map_err(|details| OrandaError::SnapshotLoad {
+                path: json_path.clone(),
+                details,
+            })?;
+        let releases = src
+            .deserialize_json()
+            .map_err(|details| OrandaError::SnapshotLoad {
+                path: json_path,
+                details,
+            })?;
+
+        Ok(Self {
+            releases,
+            assets_dir,
+        })
+    }
+}
+
+impl ReleaseArtifacts {
+    /// Register local copies of files that exist in the given directory
+    pub fn add_local_assets(&mut self, assets_dir: &Utf8Path) {
+        for file_idx in self.file_indices() {
+            let file = self.file_mut(file_idx);
+            let local_path = assets_dir.join(&file.name);
+            if local_path.exists() {
+                file.local_path = Some(local_path);
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use async_trait::async_trait;
+use miette::miette;
+use serde::Deserialize;
+
+use super::{fetch_all_pages, split_repo_url, ReleaseSource};
+use crate::data::cache::HttpCache;
+use crate::data::github::{GithubRelease, GithubReleaseAsset};
+use crate::errors::*;
+
+/// Represents a repository on a Gitea (or Forgejo) instance
+#[derive(Debug, Clone)]
+pub struct GiteaRepo {
+    /// The root of the instance's REST API (e.g. `https://codeberg.org/api/v1`)
+    pub api_url: String,
+    /// The repository owner.
+    pub owner: String,
+    /// The repository name.
+    pub name: String,
+}
+
+impl GiteaRepo {
+    /// Constructs a Gitea repository from its URL. Instances can be served from a
+    /// subpath, so the last two parts of the URL are taken to be the owner and name.
+    pub fn from_url(repo_url: &str) -> Result<Self> {
+        let (origin, mut segments) = split_repo_url(repo_url)?;
+        if segments.len() < 2 {
+            return Err(OrandaError::RepoParseError {
+                repo: repo_url.to_owned(),
+                details: miette!("This URL has less parts than we expected"),
+            });
+        }
+        let name = segments.pop().unwrap_or_default();
+        let owner = segments.pop().unwrap_or_default();
+        let mut api_url = origin;
+        for segment in segments {
+            api_url.push('/');
+            api_url.push_str(&segment);
+        }
+        api_url.push_str("/api/v1");
+        Ok(Self {
+            api_url,
+            owner,
+            name,
+        })
+    }
+}
+
+/// From the Gitea Releases API
+/// as documented here: <https://gitea.com/api/swagger#/repository/repoListReleases>
+///
+/// This is nearly the same as GitHub's, but with fewer fields.
+#[derive(Debug, Deserialize)]
+struct GiteaRelease {
+    id: i64,
+    tag_name: String,
+    #[serde(default)]
+    target_commitish: String,
+    name: Option<String>,
+    body: Option<String>,
+    url: String,
+    html_url: String,
+    #[serde(default)]
+    tarball_url: String,
+    #[serde(default)]
+    zipball_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    created_at: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GiteaAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaAsset {
+    id: i64,
+    name: String,
+    #[serde(default)]
+    size: i64,
+    #[serde(default)]
+    download_count: i64,
+    created_at: String,
+    browser_download_url: String,
+}
+
+impl From<GiteaRelease> for GithubRelease {
+    fn from(release: GiteaRelease) -> Self {
+        let assets = release
+            .assets
+            .into_iter()
+            .map(|asset| GithubReleaseAsset {
+                url: asset.browser_download_url.clone(),
+                id: asset.id,
+                node_id: String::new(),
+                name: asset.name,
+                label: None,
+                content_type: "application/octet-stream".to_owned(),
+                state: "uploaded".to_owned(),
+                size: asset.size,
+                download_count: asset.download_count,
+                updated_at: asset.created_at.clone(),
+                created_at: asset.created_at,
+                browser_download_url: asset.browser_download_url,
+            })
+            .collect();
+        GithubRelease {
+            assets_url: format!("{}/assets", release.url),
+            url: release.url,
+            html_url: release.html_url,
+            id: Some(release.id),
+            tag_name: release.tag_name,
+            target_commitish: release.target_commitish,
+            name: release.name,
+            draft: release.draft,
+            prerelease: release.prerelease,
+            published_at: release
+                .published_at
+                .unwrap_or_else(|| release.created_at.clone()),
+            created_at: release.created_at,
+            assets,
+            tarball_url: release.tarball_url,
+            zipball_url: release.zipball_url,
+            body: release.body,
+        }
+    }
+}
+
+#[async_trait]
+impl ReleaseSource for GiteaRepo {
+    async fn fetch_releases(&self, cache: &HttpCache) -> Result<Vec<GithubRelease>> {
+        let url = format!(
+            "{}/repos/{}/{}/releases?limit=50",
+            self.api_url, self.owner, self.name
+        );
+        let releases = fetch_all_pages::<GiteaRelease>("Gitea", url, cache).await?;
+        Ok(releases.into_iter().map(GithubRelease::from).collect())
+    }
+}
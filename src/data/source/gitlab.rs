@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use miette::miette;
+use serde::Deserialize;
+
+use super::{fetch_all_pages, is_prerelease_tag, split_repo_url, ReleaseSource};
+use crate::data::cache::HttpCache;
+use crate::data::github::{GithubRelease, GithubReleaseAsset};
+use crate::errors::*;
+
+/// Represents a GitLab project (on gitlab.com or a self-hosted instance)
+#[derive(Debug, Clone)]
+pub struct GitlabRepo {
+    /// The root of the instance's REST API (e.g. `https://gitlab.com/api/v4`)
+    pub api_url: String,
+    /// The URL of the project's page
+    pub web_url: String,
+    /// The full path of the project, including any (nested) groups
+    pub path: String,
+}
+
+impl GitlabRepo {
+    /// Constructs a GitLab project from its URL. Unlike GitHub, projects can be nested
+    /// in any number of groups, so everything up to GitLab's `/-/` separator is taken
+    /// to be the project path.
+    pub fn from_url(repo_url: &str) -> Result<Self> {
+        let (origin, segments) = split_repo_url(repo_url)?;
+        let path = segments
+            .into_iter()
+            .take_while(|s| s != "-")
+            .collect::<Vec<_>>();
+        if path.len() < 2 {
+            return Err(OrandaError::RepoParseError {
+                repo: repo_url.to_owned(),
+                details: miette!("This URL has less parts than we expected"),
+            });
+        }
+        let path = path.join("/");
+        Ok(Self {
+            api_url: format!("{origin}/api/v4"),
+            web_url: format!("{origin}/{path}"),
+            path,
+        })
+    }
+}
+
+/// From the GitLab Releases API
+/// as documented here: <https://docs.gitlab.com/ee/api/releases/>
+#[derive(Debug, Deserialize)]
+struct GitlabRelease {
+    tag_name: String,
+    name: Option<String>,
+    description: Option<String>,
+    created_at: String,
+    released_at: Option<String>,
+    #[serde(default)]
+    upcoming_release: bool,
+    commit: Option<GitlabCommit>,
+    assets: GitlabAssets,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabCommit {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabAssets {
+    #[serde(default)]
+    sources: Vec<GitlabSource>,
+    #[serde(default)]
+    links: Vec<GitlabLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabSource {
+    format: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabLink {
+    id: i64,
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+impl GitlabRepo {
+    fn to_github(&self, release: GitlabRelease) -> GithubRelease {
+        let source_url = |format: &str| {
+            release
+                .assets
+                .sources
+                .iter()
+                .find(|s| s.format == format)
+                .map(|s| s.url.clone())
+                .unwrap_or_default()
+        };
+        let tarball_url = source_url("tar.gz");
+        let zipball_url = source_url("zip");
+        let html_url = format!("{}/-/releases/{}", self.web_url, release.tag_name);
+        let assets = release
+            .assets
+            .links
+            .into_iter()
+            .map(|link| {
+                let url = link.direct_asset_url.unwrap_or(link.url);
+                link_asset(link.id, link.name, url, &release.created_at)
+            })
+            .collect();
+        GithubRelease {
+            url: html_url.clone(),
+            assets_url: html_url.clone(),
+            html_url,
+            // GitLab releases are identified by their tag, and don't have an id of their own
+            id: None,
+            prerelease: release.upcoming_release || is_prerelease_tag(&release.tag_name),
+            tag_name: release.tag_name,
+            target_commitish: release.commit.map(|c| c.id).unwrap_or_default(),
+            name: release.name,
+            draft: false,
+            published_at: release
+                .released_at
+                .unwrap_or_else(|| release.created_at.clone()),
+            created_at: release.created_at,
+            assets,
+            tarball_url,
+            zipball_url,
+            body: release.description,
+        }
+    }
+}
+
+#[async_trait]
+impl ReleaseSource for GitlabRepo {
+    async fn fetch_releases(&self, cache: &HttpCache) -> Result<Vec<GithubRelease>> {
+        let project: String = url::form_urlencoded::byte_serialize(self.path.as_bytes()).collect();
+        let url = format!("{}/projects/{project}/releases?per_page=100", self.api_url);
+        let releases = fetch_all_pages::<GitlabRelease>("GitLab", url, cache).await?;
+        Ok(releases
+            .into_iter()
+            .map(|release| self.to_github(release))
+            .collect())
+    }
+}
+
+/// Make an asset for a forge that only tells us where to download it from
+fn link_asset(id: i64, name: String, url: String, created_at: &str) -> GithubReleaseAsset {
+    GithubReleaseAsset {
+        url: url.clone(),
+        id,
+        node_id: String::new(),
+        name,
+        label: None,
+        content_type: "application/octet-stream".to_owned(),
+        state: "uploaded".to_owned(),
+        size: 0,
+        download_count: 0,
+        created_at: created_at.to_owned(),
+        updated_at: created_at.to_owned(),
+        browser_download_url: url,
+    }
+}
@@ -0,0 +1,210 @@
+//! Where we get release data from
+//!
+//! oranda's model of a release is the one GitHub Releases uses ([`GithubRelease`][]),
+//! so every other forge we support maps its own API into that shape. This keeps
+//! everything downstream of [`Context`][crate::data::Context] forge-agnostic.
+
+use async_trait::async_trait;
+use axoasset::SourceFile;
+use miette::IntoDiagnostic;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::config::ReleaseSourceKind;
+use crate::data::cache::HttpCache;
+use crate::data::github::{GithubRelease, GithubRepo};
+use crate::errors::*;
+
+mod gitea;
+mod gitlab;
+
+pub use gitea::GiteaRepo;
+pub use gitlab::GitlabRepo;
+
+/// Somewhere we can get releases (and their assets) from
+#[async_trait]
+pub trait ReleaseSource: Sync {
+    /// Fetch all the releases, from newest to oldest
+    async fn fetch_releases(&self, cache: &HttpCache) -> Result<Vec<GithubRelease>>;
+
+    /// Fetch the contents of one of a release's assets
+    async fn fetch_asset(
+        &self,
+        release: &GithubRelease,
+        asset_name: &str,
+        cache: &HttpCache,
+    ) -> Result<String> {
+        match release.asset_url(asset_name) {
+            Some(url) => cache.fetch(url).await,
+            None => Err(OrandaError::ReleaseAssetMissing {
+                tag: release.tag_name.clone(),
+                name: asset_name.to_owned(),
+            }),
+        }
+    }
+}
+
+/// A repository on any of the forges we know how to get releases from
+#[derive(Debug, Clone)]
+pub enum Repo {
+    Github(GithubRepo),
+    Gitlab(GitlabRepo),
+    Gitea(GiteaRepo),
+}
+
+impl Repo {
+    /// Work out which forge a repository URL points at and parse it accordingly
+    ///
+    /// If the kind of forge isn't specified we guess based on the host, assuming
+    /// GitHub for anything we don't recognize.
+    pub fn from_url(repo_url: &str, kind: Option<ReleaseSourceKind>) -> Result<Self> {
+        let kind = kind.unwrap_or_else(|| ReleaseSourceKind::guess(repo_url));
+        Ok(match kind {
+            ReleaseSourceKind::Github => Self::Github(GithubRepo::from_url(repo_url)?),
+            ReleaseSourceKind::Gitlab => Self::Gitlab(GitlabRepo::from_url(repo_url)?),
+            ReleaseSourceKind::Gitea | ReleaseSourceKind::Forgejo => {
+                Self::Gitea(GiteaRepo::from_url(repo_url)?)
+            }
+        })
+    }
+
+    /// Get the URL of a file in the repository's default branch, for the forges where we
+    /// know what that looks like
+    ///
+    /// Gitea only links to files by branch name, and we don't know the default branch, so
+    /// this is `None` for Gitea and Forgejo.
+    pub fn file_url(&self, path: &str) -> Option<String> {
+        let path = path.trim_start_matches('/');
+        match self {
+            Self::Github(repo) => Some(format!(
+                "https://github.com/{}/{}/blob/HEAD/{path}",
+                repo.owner, repo.name
+            )),
+            Self::Gitlab(repo) => Some(format!("{}/-/blob/HEAD/{path}", repo.web_url)),
+            Self::Gitea(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl ReleaseSource for Repo {
+    async fn fetch_releases(&self, cache: &HttpCache) -> Result<Vec<GithubRelease>> {
+        match self {
+            Self::Github(repo) => repo.fetch_releases(cache).await,
+            Self::Gitlab(repo) => repo.fetch_releases(cache).await,
+            Self::Gitea(repo) => repo.fetch_releases(cache).await,
+        }
+    }
+
+    async fn fetch_asset(
+        &self,
+        release: &GithubRelease,
+        asset_name: &str,
+        cache: &HttpCache,
+    ) -> Result<String> {
+        match self {
+            Self::Github(repo) => repo.fetch_asset(release, asset_name, cache).await,
+            Self::Gitlab(repo) => repo.fetch_asset(release, asset_name, cache).await,
+            Self::Gitea(repo) => repo.fetch_asset(release, asset_name, cache).await,
+        }
+    }
+}
+
+impl From<GithubRepo> for Repo {
+    fn from(repo: GithubRepo) -> Self {
+        Self::Github(repo)
+    }
+}
+
+impl From<GitlabRepo> for Repo {
+    fn from(repo: GitlabRepo) -> Self {
+        Self::Gitlab(repo)
+    }
+}
+
+impl From<GiteaRepo> for Repo {
+    fn from(repo: GiteaRepo) -> Self {
+        Self::Gitea(repo)
+    }
+}
+
+impl ReleaseSourceKind {
+    /// Guess the kind of forge from a repository URL
+    fn guess(repo_url: &str) -> Self {
+        let host = Url::parse(repo_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_owned()));
+        match host.as_deref() {
+            Some("gitlab.com") => Self::Gitlab,
+            Some("codeberg.org") => Self::Forgejo,
+            _ => Self::Github,
+        }
+    }
+}
+
+/// Split a repository URL into its origin (e.g. `https://gitlab.com`) and its path segments
+///
+/// Empty segments and a trailing `.git` are ignored.
+fn split_repo_url(repo_url: &str) -> Result<(String, Vec<String>)> {
+    let url = Url::parse(repo_url)
+        .into_diagnostic()
+        .map_err(|e| OrandaError::RepoParseError {
+            repo: repo_url.to_owned(),
+            details: e,
+        })?;
+    let mut segments = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_owned())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if let Some(last) = segments.last_mut() {
+        if let Some(stripped) = last.strip_suffix(".git") {
+            *last = stripped.to_owned();
+        }
+    }
+    Ok((url.origin().ascii_serialization(), segments))
+}
+
+/// Whether a tag looks like a prerelease version (e.g. `v1.0.0-beta.1`)
+///
+/// Used for forges that don't have an explicit notion of prereleases.
+fn is_prerelease_tag(tag: &str) -> bool {
+    let version = tag.strip_prefix('v').unwrap_or(tag);
+    semver::Version::parse(version)
+        .map(|v| !v.pre.is_empty())
+        .unwrap_or(false)
+}
+
+/// Fetch every page of a forge's paginated list of releases, starting from `url`
+async fn fetch_all_pages<T: DeserializeOwned>(
+    forge: &str,
+    url: String,
+    cache: &HttpCache,
+) -> Result<Vec<T>> {
+    let mut items = vec![];
+    let mut next = Some(url);
+    while let Some(url) = next {
+        let page = cache
+            .fetch_unauthenticated_page(&url)
+            .await
+            .map_err(|e| fetch_error(forge, e))?;
+        items.extend(SourceFile::new(&url, page.body).deserialize_json::<Vec<T>>()?);
+        next = page.next;
+    }
+    Ok(items)
+}
+
+/// Report a failure to fetch a forge's releases
+fn fetch_error(forge: &str, e: OrandaError) -> OrandaError {
+    match e {
+        OrandaError::Reqwest(details) => OrandaError::ReleasesFetchError {
+            forge: forge.to_owned(),
+            details,
+        },
+        e => e,
+    }
+}
@@ -0,0 +1,140 @@
+//! Logic for working out which releases we care about, and what versions they're for
+//!
+//! Repositories often have releases for several things (an app, its libraries, its CSS...),
+//! and tag them all sorts of ways (`v1.0.0`, `oranda-v1.0.0`, `css-v0.1.0`...). The
+//! `releases` config tells us which tags are relevant and how to get a version out of them.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::config::ReleasesConfig;
+use crate::data::Release;
+use crate::errors::*;
+
+/// Decides which releases to use, based on their tags
+pub struct TagFilter {
+    /// Tags have to start with this
+    prefix: Option<String>,
+    /// If set, tags have to match one of these
+    include: Option<GlobSet>,
+    /// Tags can't match any of these
+    exclude: Option<GlobSet>,
+}
+
+impl TagFilter {
+    pub fn new(config: &ReleasesConfig) -> Result<Self> {
+        Ok(Self {
+            prefix: config.tag_prefix.clone(),
+            include: build_glob_set(&config.include_tags, invalid_tag_pattern)?,
+            exclude: build_glob_set(&config.exclude_tags, invalid_tag_pattern)?,
+        })
+    }
+
+    /// Get whether we want to use a release with this tag
+    pub fn matches(&self, tag: &str) -> bool {
+        if let Some(prefix) = &self.prefix {
+            if !tag.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(include) = &self.include {
+            if !include.is_match(tag) {
+                return false;
+            }
+        }
+        if let Some(exclude) = &self.exclude {
+            if exclude.is_match(tag) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Build a set out of some glob patterns, reporting a bad one with `error`
+pub(crate) fn build_glob_set(
+    patterns: &Option<Vec<String>>,
+    error: fn(String, globset::Error) -> OrandaError,
+) -> Result<Option<GlobSet>> {
+    let Some(patterns) = patterns else {
+        return Ok(None);
+    };
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|details| error(pattern.clone(), details))?;
+        builder.add(glob);
+    }
+    let set = builder
+        .build()
+        .map_err(|details| error(patterns.join(", "), details))?;
+    Ok(Some(set))
+}
+
+fn invalid_tag_pattern(pattern: String, details: globset::Error) -> OrandaError {
+    OrandaError::InvalidTagPattern { pattern, details }
+}
+
+/// Parse the version out of a tag
+///
+/// The prefix (if any) is stripped first, then a leading `v`, so with a prefix of `oranda-`
+/// both `oranda-v1.2.3` and `oranda-1.2.3` are version 1.2.3.
+pub fn parse_version(tag: &str, prefix: Option<&str>) -> Option<semver::Version> {
+    let tag = prefix
+        .and_then(|prefix| tag.strip_prefix(prefix))
+        .unwrap_or(tag);
+    let tag = tag.strip_prefix('v').unwrap_or(tag);
+    semver::Version::parse(tag).ok()
+}
+
+/// Sort releases from highest to lowest version
+///
+/// Releases without a version in their tag are put after all the ones with versions,
+/// keeping their relative order.
+pub fn sort_by_version(releases: &mut [Release], prefix: Option<&str>) {
+    releases.sort_by_cached_key(|release| {
+        std::cmp::Reverse(parse_version(&release.source.tag_name, prefix))
+    });
+}
+
+/// A series of releases that are compatible with each other (e.g. `1.x`)
+#[derive(Clone, Debug)]
+pub struct ReleaseLine {
+    /// The name of the line, like `2.x`, or `0.4.x` for versions before 1.0
+    pub name: String,
+    /// Index into the releases for the latest release in this line
+    ///
+    /// (prefers stable releases over prereleases)
+    pub latest_release: usize,
+}
+
+/// Get the name of the release line a version belongs to
+pub fn release_line_name(version: &semver::Version) -> String {
+    if version.major == 0 {
+        format!("0.{}.x", version.minor)
+    } else {
+        format!("{}.x", version.major)
+    }
+}
+
+/// Group releases (already sorted by version) into release lines, from highest to lowest
+pub fn release_lines(releases: &[Release], prefix: Option<&str>) -> Vec<ReleaseLine> {
+    let mut lines: Vec<ReleaseLine> = vec![];
+    for (idx, release) in releases.iter().enumerate() {
+        let Some(version) = parse_version(&release.source.tag_name, prefix) else {
+            continue;
+        };
+        let name = release_line_name(&version);
+        match lines.iter_mut().find(|line| line.name == name) {
+            Some(line) => {
+                // A stable release beats any prerelease of a higher version
+                if releases[line.latest_release].source.prerelease && !release.source.prerelease {
+                    line.latest_release = idx;
+                }
+            }
+            None => lines.push(ReleaseLine {
+                name,
+                latest_release: idx,
+            }),
+        }
+    }
+    lines
+}
@@ -3,6 +3,9 @@ use camino::Utf8PathBuf;
 use miette::Diagnostic;
 use thiserror::Error;
 
+use crate::config::validate::UnknownKey;
+use crate::site::check::LinkProblem;
+
 pub type Result<T> = std::result::Result<T, OrandaError>;
 
 #[derive(Debug, Diagnostic, Error)]
@@ -33,6 +36,39 @@ pub enum OrandaError {
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
+    #[error("Couldn't parse the config in {source_name}")]
+    ConfigParseError {
+        source_name: String,
+        #[diagnostic_source]
+        details: miette::Report,
+    },
+
+    #[error("Found {} unknown key{} in the config in {source_name}", problems.len(), if problems.len() == 1 { "" } else { "s" })]
+    #[diagnostic(help(
+        "See https://opensource.axo.dev/oranda/book/configuration.html for all the options."
+    ))]
+    UnknownConfigKeys {
+        source_name: String,
+        #[source_code]
+        src: axoasset::SourceFile,
+        #[related]
+        problems: Vec<UnknownKey>,
+    },
+
+    #[error("Couldn't find any config at {path}, which {source_name} extends")]
+    #[diagnostic(help(
+        "`extends` should point to config files, or directories of them, relative to the config it's in."
+    ))]
+    ConfigExtendsNotFound { path: String, source_name: String },
+
+    #[error("Configs extend each other in a loop: {cycle}")]
+    #[diagnostic(help("Remove one of these from the `extends` of the config before it."))]
+    ConfigExtendsCycle { cycle: String },
+
+    #[error("Couldn't find the project root `{path}`")]
+    #[diagnostic(help("Make sure `--project-root` is the path to a directory."))]
+    ProjectRootNotFound { path: String },
+
     #[error("Failed to create a directory, `{dist_path}` to build your project in.")]
     DistDirCreationError {
         dist_path: String,
@@ -47,9 +83,75 @@ pub enum OrandaError {
     #[error("Failed fetching releases from Github.")]
     GithubReleasesFetchError {
         #[source]
-        details: octolotl::OctolotlError,
+        details: reqwest::Error,
+    },
+
+    #[error("Hit the GitHub API rate limit, which resets at {reset}")]
+    #[diagnostic(help("{}", if *authenticated {
+        "Wait for the limit to reset, or use a token with a higher limit."
+    } else {
+        "Set GITHUB_TOKEN or GH_TOKEN to make authenticated requests, which have a much higher limit."
+    }))]
+    GithubRateLimit { reset: String, authenticated: bool },
+
+    #[error("Couldn't parse the tag pattern {pattern}")]
+    #[diagnostic(help("This should be a glob pattern like `v*` or `oranda-v*`."))]
+    InvalidTagPattern {
+        pattern: String,
+        #[source]
+        details: globset::Error,
+    },
+
+    #[error("Couldn't parse the link pattern {pattern}")]
+    #[diagnostic(help("This should be a glob pattern like `https://github.com/*`."))]
+    InvalidLinkPattern {
+        pattern: String,
+        #[source]
+        details: globset::Error,
+    },
+
+    #[error("Found {} broken link{} in {dist_dir}", problems.len(), if problems.len() == 1 { "" } else { "s" })]
+    BrokenLinks {
+        dist_dir: String,
+        #[related]
+        problems: Vec<LinkProblem>,
     },
 
+    #[error("Failed fetching releases from {forge}.")]
+    ReleasesFetchError {
+        forge: String,
+        #[source]
+        details: reqwest::Error,
+    },
+
+    #[error("Release {tag} doesn't have an asset named {name}")]
+    ReleaseAssetMissing { tag: String, name: String },
+
+    #[error("Couldn't parse the checksum file {name} in release {tag}: {details}")]
+    #[diagnostic(help(
+        "We expect lines like `<hash>  <file name>`, as written by `sha256sum`. The downloads table will link to the checksum file instead of showing the hash."
+    ))]
+    ChecksumParse {
+        tag: String,
+        name: String,
+        details: String,
+    },
+
+    #[error("Couldn't fetch the checksum file {name} in release {tag}")]
+    #[diagnostic(help(
+        "The downloads table will link to the checksum file instead of showing the hash."
+    ))]
+    ChecksumFetch {
+        tag: String,
+        name: String,
+        #[source]
+        details: Box<OrandaError>,
+    },
+
+    #[error("Couldn't fetch {url} because we're offline, and it isn't cached")]
+    #[diagnostic(help("Try building once with network access to populate the cache."))]
+    OfflineCacheMiss { url: String },
+
     #[error("Failed parsing response when fetching releases from Github.")]
     GithubReleaseParseError {
         #[source]
@@ -62,6 +164,16 @@ pub enum OrandaError {
         repo_name: String,
     },
 
+    #[error("Couldn't load your release snapshot at {path}")]
+    #[diagnostic(help(
+        "This should be a JSON array in the format of the GitHub Releases API, or a directory containing one named `releases.json`."
+    ))]
+    SnapshotLoad {
+        path: Utf8PathBuf,
+        #[diagnostic_source]
+        details: AxoassetError,
+    },
+
     #[error(transparent)]
     FSExtra(#[from] fs_extra::error::Error),
 
@@ -5,8 +5,10 @@ use clap::{Parser, Subcommand};
 use miette::Report;
 use tracing::level_filters::LevelFilter;
 
+// The command impls all return the library's `Result`, whose error type is big
+#[allow(clippy::result_large_err)]
 mod commands;
-use commands::{Build, ConfigSchema, Dev, Serve};
+use commands::{Build, Check, ConfigCommand, ConfigSchema, Dev, Serve};
 
 pub mod message;
 use message::OutputFormat;
@@ -34,6 +36,8 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Command {
     Build(Build),
+    Check(Check),
+    Config(ConfigCommand),
     Dev(Dev),
     Serve(Serve),
     #[clap(hide = true)]
@@ -60,6 +64,8 @@ fn run(cli: &axocli::CliApp<Cli>) -> Result<(), Report> {
 
     match &cli.config.command {
         Command::Build(cmd) => cmd.run()?,
+        Command::Check(cmd) => cmd.run()?,
+        Command::Config(cmd) => cmd.run()?,
         Command::Dev(cmd) => cmd.clone().run()?,
         Command::Serve(cmd) => cmd.run()?,
         Command::ConfigSchema(cmd) => cmd.run()?,
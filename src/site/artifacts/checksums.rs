@@ -0,0 +1,100 @@
+use axohtml::elements::div;
+use axohtml::{html, text};
+
+use crate::config::Config;
+use crate::data::artifacts::{Checksum, ChecksumAlgorithm, File};
+use crate::data::Release;
+
+use super::{command_html, table};
+
+/// The kinds of platform we have different verification commands for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Platform {
+    Linux,
+    Mac,
+    Windows,
+}
+
+impl Platform {
+    fn of_target(target: &str) -> Self {
+        if target.contains("windows") {
+            Platform::Windows
+        } else if target.contains("apple") {
+            Platform::Mac
+        } else {
+            Platform::Linux
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Platform::Linux => "Linux",
+            Platform::Mac => "macOS",
+            Platform::Windows => "Windows",
+        }
+    }
+
+    /// A command that checks a downloaded file against its checksum
+    fn verify_command(&self, file: &File, checksum: &Checksum) -> String {
+        let bits = checksum.algorithm.bits();
+        let name = &file.name;
+        let hash = &checksum.value;
+        match self {
+            Platform::Linux => {
+                let tool = match checksum.algorithm {
+                    ChecksumAlgorithm::Sha256 => "sha256sum",
+                    ChecksumAlgorithm::Sha512 => "sha512sum",
+                };
+                format!("echo \"{hash}  {name}\" | {tool} -c -")
+            }
+            Platform::Mac => format!("echo \"{hash}  {name}\" | shasum -a {bits} -c -"),
+            Platform::Windows => format!(
+                "(Get-FileHash \"{name}\" -Algorithm SHA{bits}).Hash -eq \"{}\"",
+                hash.to_uppercase()
+            ),
+        }
+    }
+}
+
+/// Build a section explaining how to check each download against its checksum
+pub fn build(release: &Release, config: &Config) -> Option<Box<div<String>>> {
+    let mut sections = vec![];
+    for (_, (file, targets)) in table::downloads(release) {
+        let Some(checksum) = &file.checksum else {
+            continue;
+        };
+        let mut platforms = targets
+            .iter()
+            .map(|target| Platform::of_target(target))
+            .collect::<Vec<_>>();
+        platforms.sort();
+        platforms.dedup();
+
+        let commands = platforms.into_iter().map(|platform| {
+            let command = platform.verify_command(file, checksum);
+            html!(
+            <div class="checksum-verify-platform">
+                <span>{text!(platform.name())}</span>
+                {command_html(&command, config)}
+            </div>
+            )
+        });
+        sections.push(html!(
+        <div class="checksum-verify">
+            <h4>{text!(&file.name)}</h4>
+            {commands}
+        </div>
+        ));
+    }
+    if sections.is_empty() {
+        return None;
+    }
+
+    Some(html!(
+    <div class="checksums">
+        <h3>{text!("Verifying downloads")}</h3>
+        <p>{text!("After downloading a file, you can check that it matches the checksum published with this release by running:")}</p>
+        {sections}
+    </div>
+    ))
+}
@@ -14,8 +14,13 @@ use crate::site::{icons, link, markdown};
 
 type Platforms = HashMap<TargetTriple, Vec<InstallerIdx>>;
 
-pub fn build_header(release: &Release, config: &Config) -> Result<Box<div<String>>> {
-    let downloads_href = link::generate(&config.path_prefix, "artifacts/");
+/// Builds the install widget for a release, with fallbacks linking to the page at `downloads_path`
+pub fn build_header(
+    release: &Release,
+    config: &Config,
+    downloads_path: &str,
+) -> Result<Box<div<String>>> {
+    let downloads_href = link::generate(&config.path_prefix, downloads_path);
     let tag = &release.source.tag_name;
     let platforms_we_want = filter_platforms(release);
     if platforms_we_want.is_empty() {
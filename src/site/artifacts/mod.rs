@@ -4,21 +4,28 @@ use crate::config::Config;
 use crate::data::artifacts::{DisplayPreference, InstallMethod};
 use crate::data::{Context, Release};
 use crate::errors::*;
+use crate::site::{icons, link, markdown};
 
+mod checksums;
 mod installers;
+mod signatures;
 mod table;
 
-use axohtml::elements::div;
-use axohtml::{html, text};
+use axohtml::elements::{div, li};
+use axohtml::{html, text, unsafe_text};
 
 pub fn page(context: &Context, config: &Config) -> Result<String> {
     let Some(release) = context.latest() else {
         return Ok(String::new());
     };
 
-    let header = installers::build_header(release, config)?;
+    let header = installers::build_header(release, config, "artifacts/")?;
     let installer_scripts = scripts(release, config)?;
     let artifact_table = table::build(release, config)?;
+    let verify = checksums::build(release, config);
+    let signatures = signatures::build(release, config);
+    let symbols = table::build_symbols(release);
+    let other_versions = versions_list(context, config);
 
     Ok(html!(
     <div>
@@ -31,11 +38,92 @@ pub fn page(context: &Context, config: &Config) -> Result<String> {
         <div>
             {artifact_table}
         </div>
+        {verify}
+        {signatures}
+        {symbols}
+        {other_versions}
     </div>
     )
     .to_string())
 }
 
+/// Builds the install page for one specific release (at `artifacts/<tag>/`)
+pub fn release_page(context: &Context, idx: usize, config: &Config) -> Result<String> {
+    let release = &context.releases[idx];
+    let header = installers::build_header(release, config, &release_path(release))?;
+    let installer_scripts = scripts(release, config)?;
+    let artifact_table = table::build(release, config)?;
+    let verify = checksums::build(release, config);
+    let signatures = signatures::build(release, config);
+    let symbols = table::build_symbols(release);
+
+    let notice = match context.latest() {
+        Some(latest) if context.latest_release != Some(idx) => {
+            let latest_href = link::generate(&config.path_prefix, "artifacts/");
+            Some(html!(
+            <div class="versioned-artifacts-notice">
+                {text!("These are the installers for {}. ", release.source.tag_name)}
+                <a href=latest_href>{text!("Install the latest release ({}) instead.", latest.source.tag_name)}</a>
+            </div>
+            ))
+        }
+        _ => None,
+    };
+
+    Ok(html!(
+    <div>
+        {notice}
+        <div>
+            {header}
+        </div>
+        <div class="package-managers-downloads">
+            {installer_scripts}
+        </div>
+        <div>
+            {artifact_table}
+        </div>
+        {verify}
+        {signatures}
+        {symbols}
+    </div>
+    )
+    .to_string())
+}
+
+/// Get the indices of the releases that get their own install page
+pub fn versioned_releases(context: &Context, config: &Config) -> Vec<usize> {
+    context.versioned_releases(&config.artifacts)
+}
+
+/// Get the path of a release's install page, relative to the root of the site
+pub fn release_path(release: &Release) -> String {
+    format!("artifacts/{}/", release.source.tag_name)
+}
+
+/// Lists links to the install pages of every other release that has one
+fn versions_list(context: &Context, config: &Config) -> Option<Box<div<String>>> {
+    let links = versioned_releases(context, config)
+        .into_iter()
+        .filter(|idx| context.latest_release != Some(*idx))
+        .map(|idx| {
+            let release = &context.releases[idx];
+            let href = link::generate(&config.path_prefix, &release_path(release));
+            html!(<li><a href=href>{text!(&release.source.tag_name)}</a></li>)
+        })
+        .collect::<Vec<Box<li<String>>>>();
+    if links.is_empty() {
+        return None;
+    }
+    Some(html!(
+    <div class="versioned-artifacts">
+        <h3>{text!("Other versions")}</h3>
+        <ul>
+            {links}
+        </ul>
+    </div>
+    ))
+}
+
 pub fn scripts(release: &Release, config: &Config) -> Result<Vec<Box<div<String>>>> {
     // We only display runnable scripts here
     let mut scripts = HashMap::new();
@@ -75,6 +163,18 @@ pub fn header(context: &Context, config: &Config) -> Result<String> {
         return Ok(String::new());
     };
 
-    let header = installers::build_header(release, config)?;
+    let header = installers::build_header(release, config, "artifacts/")?;
     Ok(header.to_string())
 }
+
+/// Get the html for a command to copy-paste into a terminal
+fn command_html(command: &str, config: &Config) -> Box<div<String>> {
+    let code = markdown::syntax_highlight(Some("sh"), command, &config.styles.syntax_theme())
+        .unwrap_or_else(|_| format!("<code class='inline-code'>{}</code>", command));
+    html!(
+        <div class="install-code-wrapper">
+            {unsafe_text!(code)}
+            <button class="button copy-clipboard-button primary" data-copy=command>{icons::copy()}</button>
+        </div>
+    )
+}
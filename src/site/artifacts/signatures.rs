@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use axohtml::elements::div;
+use axohtml::{html, text};
+
+use crate::config::Config;
+use crate::data::artifacts::{File, Signature, SignatureKind};
+use crate::data::Release;
+
+use super::{command_html, table};
+
+/// The OIDC issuer for keyless signing in GitHub Actions
+const GITHUB_ACTIONS_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+/// Explain what you need to check a kind of signature
+fn description(kind: SignatureKind) -> &'static str {
+    match kind {
+        SignatureKind::Gpg => "These files are signed with GPG. After importing the project's public key, you can check a download's signature by running:",
+        SignatureKind::Minisign => "These files are signed with minisign. With the project's public key saved as minisign.pub, you can check a download's signature by running:",
+        SignatureKind::Cosign => "These files are signed with Sigstore. With cosign installed, you can check a download's signature by running:",
+        SignatureKind::Provenance => "These files have SLSA provenance, describing how they were built. With slsa-verifier installed, you can check a download's provenance by running:",
+    }
+}
+
+/// A command that checks a downloaded file against its signature
+fn verify_command(
+    release: &Release,
+    file: &File,
+    signature: &Signature,
+    config: &Config,
+) -> String {
+    let name = &file.name;
+    let sig = &release.artifacts.file(signature.file).name;
+    let repo = config
+        .repository
+        .as_deref()
+        .unwrap_or_default()
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    match signature.kind {
+        SignatureKind::Gpg => format!("gpg --verify {sig} {name}"),
+        SignatureKind::Minisign => format!("minisign -Vm {name} -x {sig}"),
+        SignatureKind::Cosign => {
+            let proof = if let Some(certificate) = signature.certificate {
+                let certificate = &release.artifacts.file(certificate).name;
+                format!("--signature {sig} --certificate {certificate}")
+            } else {
+                format!("--bundle {sig}")
+            };
+            // Keyless signatures from GitHub Actions are tied to the repository
+            let identity = if repo.starts_with("https://github.com/") {
+                format!("--certificate-identity-regexp '^{repo}/' --certificate-oidc-issuer {GITHUB_ACTIONS_ISSUER}")
+            } else {
+                "--certificate-identity <identity> --certificate-oidc-issuer <issuer>".to_owned()
+            };
+            format!("cosign verify-blob {name} {proof} {identity}")
+        }
+        SignatureKind::Provenance => {
+            let source = repo
+                .trim_start_matches("https://")
+                .trim_start_matches("http://");
+            format!("slsa-verifier verify-artifact {name} --provenance-path {sig} --source-uri {source}")
+        }
+    }
+}
+
+/// Build a section explaining how to check each download's signatures
+pub fn build(release: &Release, config: &Config) -> Option<Box<div<String>>> {
+    let mut commands_by_kind = BTreeMap::<SignatureKind, Vec<String>>::new();
+    for (idx, (file, _)) in table::downloads(release) {
+        for signature in release.artifacts.signatures(idx) {
+            let command = verify_command(release, file, &signature, config);
+            commands_by_kind
+                .entry(signature.kind)
+                .or_default()
+                .push(command);
+        }
+    }
+    if commands_by_kind.is_empty() {
+        return None;
+    }
+
+    let sections = commands_by_kind.into_iter().map(|(kind, commands)| {
+        let commands = commands
+            .iter()
+            .map(|command| command_html(command, config))
+            .collect::<Vec<_>>();
+        html!(
+        <div class="signature-verify">
+            <h4>{text!(kind.label())}</h4>
+            <p>{text!(description(kind))}</p>
+            {commands}
+        </div>
+        )
+    });
+    Some(html!(
+    <div class="signatures">
+        <h3>{text!("Verifying signatures")}</h3>
+        {sections}
+    </div>
+    ))
+}
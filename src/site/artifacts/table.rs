@@ -1,19 +1,19 @@
 use std::collections::HashMap;
 
-use axohtml::elements::{div, tr};
+use axohtml::elements::{div, li, tr};
 use axohtml::{html, text};
 
 use crate::config::Config;
 use crate::data::artifacts::inference::triple_to_display_name;
-use crate::data::artifacts::InstallMethod;
+use crate::data::artifacts::{File, FileIdx, InstallMethod, TargetTriple};
 use crate::data::Release;
 use crate::errors::*;
+use crate::site::icons;
 
-/// Build a downloads table for this release
-pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
-    let mut table = vec![];
-
-    // We only display files that were detected to be a downloadable archive
+/// Get the files that were detected to be a downloadable archive, with the targets they're for
+///
+/// (sorted by file name)
+pub fn downloads(release: &Release) -> Vec<(FileIdx, (&File, Vec<&TargetTriple>))> {
     // this kinda messy code is just gathering those up, deduplicating, and sorting
     let mut files = HashMap::new();
     for (_, installer) in release.artifacts.installers() {
@@ -24,23 +24,31 @@ pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
             file,
             (
                 release.artifacts.file(file),
-                installer
-                    .targets
-                    .keys()
-                    .map(|s| triple_to_display_name(s))
-                    .collect::<Vec<_>>(),
+                installer.targets.keys().collect::<Vec<_>>(),
             ),
         );
     }
     let mut files: Vec<_> = files.into_iter().collect();
     files.sort_by_key(|(_, (f, _))| &f.name);
+    files
+}
+
+/// Build a downloads table for this release
+pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
+    let mut table = vec![];
+
+    // We only display files that were detected to be a downloadable archive
+    let files = downloads(release);
 
     if files.is_empty() {
         return Ok(html!(<div>{text!("No Additional Downloads")}</div>));
     }
 
-    // If any files have checksums, add a column for that
+    // If any files have checksums or signatures, add columns for those
     let has_checksum_files = files.iter().any(|(_, (f, _))| f.checksum_file.is_some());
+    let has_signatures = files
+        .iter()
+        .any(|(idx, _)| !release.artifacts.signatures(*idx).is_empty());
 
     // Add the headings
     {
@@ -50,11 +58,14 @@ pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
         if has_checksum_files {
             row.push(html!(<th>{text!("Checksum")}</th>));
         }
+        if has_signatures {
+            row.push(html!(<th>{text!("Signatures")}</th>));
+        }
         table.push(html!(<tr>{row}</tr>));
     }
 
     // Now add the rows
-    for (_, (file, platforms)) in files {
+    for (idx, (file, platforms)) in files {
         let mut row = vec![];
 
         // Link the file
@@ -66,7 +77,7 @@ pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
         let mut platform_list = String::new();
         let mut multi_platform = false;
         for platform in platforms {
-            let Some(platform) = platform else {
+            let Some(platform) = triple_to_display_name(platform) else {
                 continue;
             };
             if multi_platform {
@@ -79,20 +90,70 @@ pub fn build(release: &Release, _config: &Config) -> Result<Box<div<String>>> {
 
         // Optionally include checksums
         if has_checksum_files {
-            let checksum_entry = if let Some(checksum) = file.checksum_file {
-                let checksum_url = &release.artifacts.file(checksum).download_url;
-                html!(<td><a href=checksum_url>{text!("checksum")}</a></td>)
+            let checksum_entry = if let Some(checksum_file) = file.checksum_file {
+                let checksum_url = &release.artifacts.file(checksum_file).download_url;
+                if let Some(checksum) = &file.checksum {
+                    let algorithm = format!("sha{}", checksum.algorithm.bits());
+                    html!(<td class="checksum">
+                        <code class="checksum-value">{text!(&checksum.value)}</code>
+                        <button class="button copy-clipboard-button primary" data-copy=&checksum.value>{icons::copy()}</button>
+                        <a href=checksum_url>{text!(algorithm)}</a>
+                    </td>)
+                } else {
+                    html!(<td><a href=checksum_url>{text!("checksum")}</a></td>)
+                }
             } else {
                 html!(<td></td>)
             };
             row.push(checksum_entry);
         }
+
+        // Optionally include signatures
+        if has_signatures {
+            let links = release
+                .artifacts
+                .signatures(idx)
+                .into_iter()
+                .map(|signature| {
+                    let url = &release.artifacts.file(signature.file).download_url;
+                    html!(<li><a href=url>{text!(signature.kind.label())}</a></li>)
+                })
+                .collect::<Vec<Box<li<String>>>>();
+            row.push(html!(<td><ul class="signature-links">{links}</ul></td>));
+        }
         table.push(html!(<tr>{row}</tr>));
     }
 
     Ok(html(table))
 }
 
+/// Build a table of the debug symbols in this release, if there are any
+pub fn build_symbols(release: &Release) -> Option<Box<div<String>>> {
+    let symbols = release.artifacts.symbols();
+    if symbols.is_empty() {
+        return None;
+    }
+    let rows = symbols.iter().map(|symbols| {
+        let file = release.artifacts.file(symbols.file);
+        let platforms = symbols
+            .targets
+            .iter()
+            .filter_map(|target| triple_to_display_name(target))
+            .collect::<Vec<_>>()
+            .join(", ");
+        html!(<tr><td><a href=&file.download_url>{text!(&file.name)}</a></td><td>{text!(platforms)}</td></tr>)
+    });
+    Some(html!(
+    <div class="debug-symbols">
+        <h3>{text!("Debug symbols")}</h3>
+        <table>
+            <tr><th>{text!("File")}</th><th>{text!("Platform")}</th></tr>
+            {rows}
+        </table>
+    </div>
+    ))
+}
+
 // False positive duplicate allocation warning
 // https://github.com/rust-lang/rust-clippy/issues?q=is%3Aissue+redundant_allocation+sort%3Aupdated-desc
 #[allow(clippy::vec_box)]
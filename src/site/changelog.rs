@@ -6,7 +6,7 @@ use chrono::DateTime;
 use crate::config::Config;
 use crate::data::{Context, Release};
 use crate::errors::*;
-use crate::site::{icons, markdown};
+use crate::site::{artifacts, icons, link, markdown};
 
 pub fn build(context: &Context, config: &Config) -> Result<String> {
     let mut releases_html: Vec<Box<section<String>>> = vec![];
@@ -32,6 +32,7 @@ pub fn build(context: &Context, config: &Config) -> Result<String> {
             <h1>{text!("Releases")}</h1>
             <div class="releases-wrapper">
                 <nav class="releases-nav">
+                    {build_release_lines(context)}
                     {build_prerelease_toggle(context.has_prereleases)}
                     <ul>
                         {releases_nav}
@@ -49,28 +50,53 @@ pub fn build(context: &Context, config: &Config) -> Result<String> {
 /// the page itself.
 pub fn build_all(context: &Context, config: &Config) -> Result<Vec<(String, String)>> {
     let mut releases = vec![];
-    for release in context.releases.iter() {
+    let install_pages = install_pages(context, config);
+    for (idx, release) in context.releases.iter().enumerate() {
         releases.push((
             release.source.tag_name.clone(),
-            build_single_release(config, release)?,
+            build_single_release(config, release, install_pages.contains(&idx))?,
         ))
     }
 
     Ok(releases)
 }
 
+/// The releases that have an install page for their changelog entries to link to
+pub fn install_pages(context: &Context, config: &Config) -> Vec<usize> {
+    if context.latest().is_some() {
+        artifacts::versioned_releases(context, config)
+    } else {
+        vec![]
+    }
+}
+
 /// Builds a single, standalone release page.
-pub fn build_single_release(config: &Config, release: &Release) -> Result<String> {
+///
+/// If the release has its own install page, this links to it.
+pub fn build_single_release(
+    config: &Config,
+    release: &Release,
+    has_install_page: bool,
+) -> Result<String> {
     let preview = build_page_preview(release, config, false);
     let title = release
         .source
         .name
         .as_ref()
         .unwrap_or(&release.source.tag_name);
+    let install_link = if has_install_page {
+        let href = link::generate(&config.path_prefix, &artifacts::release_path(release));
+        Some(html!(
+            <a class="button primary install-release" href=href>{text!("Install {}", release.source.tag_name)}</a>
+        ))
+    } else {
+        None
+    };
 
     Ok(html!(
          <div>
             <h1>{text!(title)}</h1>
+            {install_link}
             <div class="releases-body">
                 {preview}
             </div>
@@ -102,7 +128,7 @@ pub fn build_page_preview(
     let link = if is_page {
         format!("{}/", &tag_name)
     } else {
-        format!("#{}", &tag_name)
+        format!("#tag-{}", &tag_name)
     };
     let body = build_release_body(release, config)?;
     let header_class = if is_page { "" } else { "hidden" };
@@ -125,7 +151,8 @@ pub fn build_page_preview(
     ))
 }
 
-fn build_release_body(release: &Release, config: &Config) -> Result<String> {
+/// Render the notes for a release
+pub fn build_release_body(release: &Release, config: &Config) -> Result<String> {
     let contents = if let Some(manifest) = &release.manifest {
         manifest
             .announcement_changelog
@@ -138,6 +165,31 @@ fn build_release_body(release: &Release, config: &Config) -> Result<String> {
     markdown::to_html(&contents, &config.styles.syntax_theme())
 }
 
+/// Lists the latest release in each release line (e.g. `1.x` and `2.x`), if there's
+/// more than one of them
+fn build_release_lines(context: &Context) -> Option<Box<div<String>>> {
+    if context.release_lines.len() < 2 {
+        return None;
+    }
+    let lines = context.release_lines.iter().map(|line| {
+        let tag_name = &context.releases[line.latest_release].source.tag_name;
+        let link = format!("{}/", tag_name);
+        html!(
+            <li>
+                <a href=link>{text!(&line.name)}</a>
+                <span class="release-line-version">{text!(tag_name)}</span>
+            </li>
+        )
+    });
+    Some(html!(
+    <div class="release-lines">
+        <h4>{text!("Release lines")}</h4>
+        <ul>
+            {lines}
+        </ul>
+    </div>))
+}
+
 fn build_prerelease_toggle(has_prereleases: bool) -> Option<Box<div<String>>> {
     if has_prereleases {
         Some(html!(
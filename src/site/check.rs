@@ -0,0 +1,387 @@
+//! Checking that the links on the built site go somewhere
+//!
+//! We look at every HTML file in the dist dir, and resolve each link and image the way a host
+//! would (see [`hosting`][]). Links to other sites are only checked against the allowlist in
+//! the `check` config, since we don't want to ask the network about them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use globset::GlobSet;
+use miette::Diagnostic;
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+use url::Url;
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::data::tags::build_glob_set;
+use crate::errors::*;
+use crate::site::hosting::{self, Resolution};
+use crate::site::link;
+use crate::site::search::decode_entities;
+
+/// The attributes that link to other things, by the tags they're on
+const LINK_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("href", &["a", "area", "link"]),
+    (
+        "src",
+        &[
+            "img", "script", "iframe", "source", "video", "audio", "embed",
+        ],
+    ),
+];
+/// A made up origin, to resolve the paths of links against
+const BASE_ORIGIN: &str = "http://oranda.invalid";
+
+/// A link on the site that doesn't go where it should
+#[derive(Debug, Error, Diagnostic)]
+#[error("`{link}` on {} {kind}", summarize(pages))]
+#[diagnostic(severity = "error")]
+pub struct LinkProblem {
+    /// The link, as it appears in the HTML
+    pub link: String,
+    /// What's wrong with it
+    pub kind: LinkProblemKind,
+    /// The pages it appears on, relative to the dist dir
+    pub pages: Vec<String>,
+    #[help]
+    help: Option<String>,
+}
+
+/// The ways a link can be broken
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Error)]
+pub enum LinkProblemKind {
+    #[error("doesn't point to anything on the site")]
+    NotFound,
+    #[error("points to an anchor that isn't on {page}")]
+    AnchorNotFound { page: String, anchor: String },
+    #[error("points outside of the site's `path_prefix`")]
+    OutsidePrefix,
+    #[error("points to another site that isn't in `check.external_links`")]
+    ExternalNotAllowed,
+}
+
+/// Check every link on the built site, failing if any are broken
+pub fn run(config: &Config) -> Result<usize> {
+    let (pages, problems) = find_problems(config)?;
+    if problems.is_empty() {
+        Ok(pages)
+    } else {
+        Err(OrandaError::BrokenLinks {
+            dist_dir: config.dist_dir.clone(),
+            problems,
+        })
+    }
+}
+
+/// Find the broken links on the built site
+///
+/// Returns the number of pages we checked, and the problems with their links. Each broken
+/// link is only reported once, along with all the pages it's on.
+pub fn find_problems(config: &Config) -> Result<(usize, Vec<LinkProblem>)> {
+    let dist = Utf8Path::new(&config.dist_dir);
+    let mut checker = Checker::new(dist, config)?;
+    let mut found: BTreeMap<(String, LinkProblemKind), Vec<String>> = BTreeMap::new();
+
+    let mut pages = 0;
+    for entry in WalkDir::new(dist).sort_by_file_name() {
+        let entry = entry.map_err(|e| OrandaError::Other(e.to_string()))?;
+        let Some(path) = Utf8Path::from_path(entry.path()) else {
+            continue;
+        };
+        if !entry.file_type().is_file() || path.extension() != Some("html") {
+            continue;
+        }
+        pages += 1;
+        let page = path
+            .strip_prefix(dist)
+            .unwrap_or(path)
+            .as_str()
+            .replace('\\', "/");
+        let html = std::fs::read_to_string(path)?;
+        for link in links(&html) {
+            if let Some(kind) = checker.check(&page, &link) {
+                found.entry((link, kind)).or_default().push(page.clone());
+            }
+        }
+    }
+
+    let problems = found
+        .into_iter()
+        .map(|((link, kind), mut pages)| {
+            pages.dedup();
+            let help = help(&link, &kind);
+            LinkProblem {
+                link,
+                kind,
+                pages,
+                help,
+            }
+        })
+        .collect();
+    Ok((pages, problems))
+}
+
+/// List the first few pages a link is on
+fn summarize(pages: &[String]) -> String {
+    const SHOWN: usize = 3;
+    let shown = pages[..pages.len().min(SHOWN)].join(", ");
+    if pages.len() > SHOWN {
+        format!("{shown} (and {} more)", pages.len() - SHOWN)
+    } else {
+        shown
+    }
+}
+
+fn help(link: &str, kind: &LinkProblemKind) -> Option<String> {
+    let mut help = vec![];
+    if *kind == LinkProblemKind::NotFound {
+        let path = link.split(['#', '?']).next().unwrap_or_default();
+        if path.ends_with(".md") {
+            help.push(String::from(
+                "Markdown files are built into pages, so link to the page (e.g. `page/`) instead.",
+            ));
+        } else if !path.starts_with('/') && !path.starts_with("http") {
+            help.push(String::from(
+                "Relative links are resolved from the page's own directory (pages are built as `page/index.html`).",
+            ));
+        }
+    }
+    if *kind == LinkProblemKind::OutsidePrefix {
+        help.push(String::from(
+            "Links to pages on this site need to start with the `path_prefix`.",
+        ));
+    }
+    if *kind == LinkProblemKind::ExternalNotAllowed {
+        help.push(String::from(
+            "If this link is fine, add a pattern that matches it to `check.external_links`.",
+        ));
+    }
+    Some(help.join(" ")).filter(|help| !help.is_empty())
+}
+
+struct Checker<'a> {
+    dist: &'a Utf8Path,
+    config: &'a Config,
+    /// The homepage, so we can tell when full URLs point to this site
+    homepage: Option<Url>,
+    external_links: Option<GlobSet>,
+    /// The ids in each HTML file we've looked at, for checking anchors
+    ids: HashMap<Utf8PathBuf, HashSet<String>>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(dist: &'a Utf8Path, config: &'a Config) -> Result<Self> {
+        Ok(Checker {
+            dist,
+            config,
+            homepage: config
+                .homepage
+                .as_ref()
+                .and_then(|homepage| Url::parse(homepage).ok()),
+            external_links: build_glob_set(&config.check.external_links, |pattern, details| {
+                OrandaError::InvalidLinkPattern { pattern, details }
+            })?,
+            ids: HashMap::new(),
+        })
+    }
+
+    /// Check a link on `page` (a path relative to the dist dir)
+    fn check(&mut self, page: &str, link: &str) -> Option<LinkProblemKind> {
+        let link = link.trim();
+        if link.is_empty() {
+            return None;
+        }
+        let page_url = link::generate(&self.config.path_prefix, page);
+        let base = Url::parse(BASE_ORIGIN).ok()?.join(&page_url).ok()?;
+
+        let url = match Url::parse(link) {
+            // Full URLs are fine if they're to this site, and otherwise need to be allowed
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                match self.homepage.as_ref().and_then(|home| on_site(home, &url)) {
+                    Some(url) => url,
+                    None => return self.check_external(link),
+                }
+            }
+            // Other schemes (like `mailto:`) aren't ours to check
+            Ok(_) => return None,
+            Err(_) if link.starts_with("//") => return self.check_external(link),
+            Err(_) => base.join(link).ok()?,
+        };
+
+        let file = match hosting::resolve(self.dist, &self.config.path_prefix, url.path()) {
+            Resolution::File(file) => file,
+            Resolution::Redirect(path) => {
+                match hosting::resolve(self.dist, &self.config.path_prefix, &path) {
+                    Resolution::File(file) => file,
+                    _ => return Some(LinkProblemKind::NotFound),
+                }
+            }
+            Resolution::NotFound(_) => {
+                let prefix = self.config.path_prefix.as_deref().unwrap_or_default();
+                let prefix = format!("/{}", prefix.trim_matches('/'));
+                let in_prefix = prefix == "/"
+                    || url
+                        .path()
+                        .strip_prefix(&prefix)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+                if in_prefix {
+                    return Some(LinkProblemKind::NotFound);
+                }
+                return Some(LinkProblemKind::OutsidePrefix);
+            }
+        };
+
+        let anchor = url.fragment().unwrap_or_default();
+        let anchor = percent_decode_str(anchor).decode_utf8_lossy();
+        if anchor.is_empty() || file.extension() != Some("html") {
+            return None;
+        }
+        if self.ids_in(&file).contains(anchor.as_ref()) {
+            None
+        } else {
+            let page = file
+                .strip_prefix(self.dist)
+                .unwrap_or(&file)
+                .as_str()
+                .replace('\\', "/");
+            Some(LinkProblemKind::AnchorNotFound {
+                page,
+                anchor: anchor.into_owned(),
+            })
+        }
+    }
+
+    fn check_external(&self, link: &str) -> Option<LinkProblemKind> {
+        let allowed = self.external_links.as_ref()?;
+        if allowed.is_match(link) {
+            None
+        } else {
+            Some(LinkProblemKind::ExternalNotAllowed)
+        }
+    }
+
+    fn ids_in(&mut self, file: &Utf8Path) -> &HashSet<String> {
+        self.ids.entry(file.to_owned()).or_insert_with(|| {
+            let html = std::fs::read_to_string(file).unwrap_or_default();
+            tags(&html)
+                .into_iter()
+                .flat_map(|tag| {
+                    let name = (tag.name == "a").then(|| tag.attr("name")).flatten();
+                    [tag.attr("id"), name]
+                })
+                .flatten()
+                .collect()
+        })
+    }
+}
+
+/// If `url` is on the site at `homepage`, get it relative to our made up origin
+fn on_site(homepage: &Url, url: &Url) -> Option<Url> {
+    if url.origin() != homepage.origin() {
+        return None;
+    }
+    let mut on_site = Url::parse(BASE_ORIGIN).ok()?.join(url.path()).ok()?;
+    on_site.set_fragment(url.fragment());
+    Some(on_site)
+}
+
+/// A start tag in some HTML
+struct Tag {
+    name: String,
+    attrs: Vec<(String, String)>,
+}
+
+impl Tag {
+    fn attr(&self, name: &str) -> Option<String> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Get every link and image source in some HTML
+pub fn links(html: &str) -> Vec<String> {
+    tags(html)
+        .into_iter()
+        .flat_map(|tag| {
+            LINK_ATTRIBUTES
+                .iter()
+                .filter(|(_, tags)| tags.contains(&tag.name.as_str()))
+                .filter_map(|(attr, _)| tag.attr(attr))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Read the start tags out of some HTML
+///
+/// This is only as clever as it needs to be for the HTML we build: it skips comments, and the
+/// contents of scripts and styles.
+fn tags(html: &str) -> Vec<Tag> {
+    let mut tags = vec![];
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            continue;
+        }
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = &rest[name_len..];
+
+        let mut attrs = vec![];
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+            if rest.is_empty() || rest.starts_with('>') {
+                break;
+            }
+            let attr_len = rest
+                .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+                .unwrap_or(rest.len());
+            let attr = rest[..attr_len].to_ascii_lowercase();
+            rest = rest[attr_len..].trim_start();
+            let mut value = String::new();
+            if let Some(after_equals) = rest.strip_prefix('=') {
+                rest = after_equals.trim_start();
+                let (raw, after) = match rest.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let inner = &rest[1..];
+                        let end = inner.find(quote).unwrap_or(inner.len());
+                        (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+                    }
+                    _ => {
+                        let end = rest
+                            .find(|c: char| c.is_whitespace() || c == '>')
+                            .unwrap_or(rest.len());
+                        (&rest[..end], &rest[end..])
+                    }
+                };
+                value = decode_entities(raw);
+                rest = after;
+            }
+            if attr_len == 0 {
+                // Not something we understand, so move on rather than loop forever
+                rest = rest.get(1..).unwrap_or("");
+                continue;
+            }
+            attrs.push((attr, value));
+        }
+
+        // Scripts and styles can have `<` in them, which isn't a tag
+        if name == "script" || name == "style" {
+            let end_tag = format!("</{name}");
+            rest = rest.find(&end_tag).map_or("", |end| &rest[end..]);
+        }
+        tags.push(Tag { name, attrs });
+    }
+    tags
+}
@@ -0,0 +1,157 @@
+//! RSS and Atom feeds of a project's releases
+//!
+//! Each release becomes an item linking to its page in the changelog, with the same rendered
+//! notes the changelog shows.
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::config::{ChangelogConfig, Config};
+use crate::data::{Context, Release};
+use crate::errors::*;
+use crate::site::{changelog, link};
+
+/// The name of the RSS feed, at the root of the site
+pub const RSS_FEED_NAME: &str = "changelog.rss";
+/// The name of the Atom feed, at the root of the site
+pub const ATOM_FEED_NAME: &str = "changelog.atom";
+
+/// A release, ready to be put in a feed
+struct FeedItem {
+    title: String,
+    url: String,
+    published: Option<DateTime<FixedOffset>>,
+    body: String,
+}
+
+/// Build the RSS and Atom feeds for the releases in the changelog
+///
+/// Returns the name of each feed (relative to the dist dir) along with its contents.
+pub fn build(
+    context: &Context,
+    changelog_config: &ChangelogConfig,
+    config: &Config,
+) -> Result<Vec<(String, String)>> {
+    let items = context
+        .releases
+        .iter()
+        .filter(|release| changelog_config.feed_prereleases() || !release.source.prerelease)
+        .map(|release| feed_item(release, config))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(vec![
+        (RSS_FEED_NAME.to_owned(), rss(&items, config)),
+        (ATOM_FEED_NAME.to_owned(), atom(&items, config)),
+    ])
+}
+
+fn feed_item(release: &Release, config: &Config) -> Result<FeedItem> {
+    let tag_name = &release.source.tag_name;
+    Ok(FeedItem {
+        title: release.source.name.clone().unwrap_or(tag_name.clone()),
+        url: link::absolute(config, &format!("changelog/{tag_name}/")),
+        published: DateTime::parse_from_rfc3339(&release.source.published_at).ok(),
+        body: changelog::build_release_body(release, config)?,
+    })
+}
+
+fn rss(items: &[FeedItem], config: &Config) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>"#);
+    push_element(&mut xml, "title", &feed_title(config));
+    push_element(&mut xml, "link", &link::absolute(config, "changelog/"));
+    push_element(&mut xml, "description", &feed_description(config));
+    xml.push_str(&format!(
+        r#"<atom:link href="{}" rel="self" type="application/rss+xml"/>"#,
+        escape_xml(&link::absolute(config, RSS_FEED_NAME))
+    ));
+    for item in items {
+        xml.push_str("<item>");
+        push_element(&mut xml, "title", &item.title);
+        push_element(&mut xml, "link", &item.url);
+        xml.push_str(&format!(
+            r#"<guid isPermaLink="true">{}</guid>"#,
+            escape_xml(&item.url)
+        ));
+        if let Some(published) = item.published {
+            push_element(&mut xml, "pubDate", &published.to_rfc2822());
+        }
+        push_element(&mut xml, "description", &item.body);
+        xml.push_str("</item>");
+    }
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn atom(items: &[FeedItem], config: &Config) -> String {
+    // Atom needs to know when things were last updated. For releases without a date, the
+    // newest date we know of is the best we can do.
+    let updated = items
+        .iter()
+        .filter_map(|item| item.published)
+        .max()
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_else(|| String::from("1970-01-01T00:00:00+00:00"));
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    push_element(&mut xml, "id", &link::absolute(config, ATOM_FEED_NAME));
+    push_element(&mut xml, "title", &feed_title(config));
+    push_element(&mut xml, "subtitle", &feed_description(config));
+    push_element(&mut xml, "updated", &updated);
+    xml.push_str(&format!(
+        r#"<link href="{}" rel="self" type="application/atom+xml"/>"#,
+        escape_xml(&link::absolute(config, ATOM_FEED_NAME))
+    ));
+    xml.push_str(&format!(
+        r#"<link href="{}" rel="alternate" type="text/html"/>"#,
+        escape_xml(&link::absolute(config, "changelog/"))
+    ));
+    xml.push_str("<author>");
+    push_element(&mut xml, "name", &config.name);
+    xml.push_str("</author>");
+    for item in items {
+        let published = item.published.map(|date| date.to_rfc3339());
+        xml.push_str("<entry>");
+        push_element(&mut xml, "id", &item.url);
+        push_element(&mut xml, "title", &item.title);
+        push_element(&mut xml, "updated", published.as_ref().unwrap_or(&updated));
+        if let Some(published) = &published {
+            push_element(&mut xml, "published", published);
+        }
+        xml.push_str(&format!(
+            r#"<link href="{}" rel="alternate" type="text/html"/>"#,
+            escape_xml(&item.url)
+        ));
+        xml.push_str(&format!(
+            r#"<content type="html">{}</content>"#,
+            escape_xml(&item.body)
+        ));
+        xml.push_str("</entry>");
+    }
+    xml.push_str("</feed>");
+    xml
+}
+
+fn feed_title(config: &Config) -> String {
+    format!("{} releases", config.name)
+}
+
+fn feed_description(config: &Config) -> String {
+    if config.description.is_empty() {
+        format!("New releases of {}", config.name)
+    } else {
+        config.description.clone()
+    }
+}
+
+fn push_element(xml: &mut String, name: &str, contents: &str) {
+    xml.push_str(&format!("<{name}>{}</{name}>", escape_xml(contents)));
+}
+
+/// Escape text for use in XML
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,86 @@
+//! How static hosts map the paths people request to files in the dist dir
+//!
+//! This follows what GitHub Pages does, so that `oranda serve` behaves like where most sites
+//! get deployed:
+//!
+//! - a project site lives under its `path_prefix`, and nothing outside it belongs to it
+//! - `/page/` serves `page/index.html`
+//! - `/page` redirects to `/page/` if that's a directory with an `index.html` in it
+//! - `/page` serves `page.html` if there's no `page` file or directory
+//! - anything else gets the site's `404.html`, if it has one
+
+use camino::{Utf8Path, Utf8PathBuf};
+use percent_encoding::percent_decode_str;
+
+/// The page hosts show when nothing matches the requested path
+pub const NOT_FOUND_PAGE: &str = "404.html";
+
+/// What to respond to a request with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Serve this file
+    File(Utf8PathBuf),
+    /// Permanently redirect to this path
+    Redirect(String),
+    /// Nothing matched, so respond with a 404, using this page (if the site has one)
+    NotFound(Option<Utf8PathBuf>),
+}
+
+/// Work out what a request for `request_path` (as it appears in the URL) should get
+pub fn resolve(
+    dist_dir: &Utf8Path,
+    path_prefix: &Option<String>,
+    request_path: &str,
+) -> Resolution {
+    let not_found_page = Some(dist_dir.join(NOT_FOUND_PAGE)).filter(|page| page.is_file());
+    let Ok(path) = percent_decode_str(request_path).decode_utf8() else {
+        return Resolution::NotFound(not_found_page);
+    };
+
+    // Everything outside the prefix belongs to some other site
+    let prefix = path_prefix
+        .as_deref()
+        .map(|prefix| prefix.trim_matches('/'))
+        .filter(|prefix| !prefix.is_empty());
+    let path = match prefix {
+        Some(prefix) => {
+            let base = format!("/{prefix}");
+            if path == base {
+                return Resolution::Redirect(format!("{request_path}/"));
+            }
+            match path.strip_prefix(&base) {
+                Some(path) if path.starts_with('/') => path,
+                _ => return Resolution::NotFound(None),
+            }
+        }
+        None => &path,
+    };
+
+    let mut file = dist_dir.to_owned();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        // Don't let anyone wander out of the dist dir
+        if segment == "." || segment == ".." || segment.contains('\\') {
+            return Resolution::NotFound(not_found_page);
+        }
+        file.push(segment);
+    }
+
+    if path.ends_with('/') {
+        let index = file.join("index.html");
+        if index.is_file() {
+            return Resolution::File(index);
+        }
+    } else if file.is_file() {
+        return Resolution::File(file);
+    } else if file.is_dir() {
+        if file.join("index.html").is_file() {
+            return Resolution::Redirect(format!("{request_path}/"));
+        }
+    } else {
+        let html = file.with_file_name(format!("{}.html", file.file_name().unwrap_or_default()));
+        if html.is_file() {
+            return Resolution::File(html);
+        }
+    }
+    Resolution::NotFound(not_found_page)
+}
@@ -0,0 +1,336 @@
+//! Keeping track of what each build wrote to the dist dir, so that the next one only has to
+//! touch what changed
+//!
+//! Instead of wiping the dist dir before every build, we keep a manifest in it that lists every
+//! file we wrote along with a hash of its contents. Files whose contents are the same as last
+//! time are left alone, and anything that isn't part of the new build is deleted.
+//!
+//! Pages and expensive steps (like building the mdbook) are also keyed on a hash of their
+//! inputs: the source files they're made from, the parts of the config they use, and the
+//! release data they show. If that hash hasn't changed, they aren't rendered again.
+
+use std::collections::BTreeMap;
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::*;
+use crate::site::markdown::LocalMedia;
+use crate::site::page::Page;
+
+/// The name of the manifest we keep in the dist dir
+pub const MANIFEST_NAME: &str = ".oranda-build.json";
+
+/// Bump this whenever the manifest changes in a way old ones can't be read as
+const MANIFEST_VERSION: u32 = 2;
+
+/// The record of a build that we keep in the dist dir
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildManifest {
+    version: u32,
+    /// The oranda that did the build (a new one might render everything differently)
+    oranda_version: String,
+    /// The hash of each file in the build, by its path relative to the dist dir
+    outputs: BTreeMap<String, String>,
+    /// The hash of the inputs of each step we can skip, by the name of the step
+    steps: BTreeMap<String, String>,
+    /// What each page was rendered from, by the path of its file relative to the dist dir
+    pages: BTreeMap<String, PageRecord>,
+}
+
+/// What we need to know about a rendered page to use it again without rendering it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageRecord {
+    /// The hash of the inputs the page was rendered from
+    inputs: String,
+    /// The page's own content, before it was put in the layout (for the search index)
+    body: String,
+    last_modified: Option<DateTime<Utc>>,
+    /// The media the page shows, along with the hash of each file when the page was rendered
+    media: Vec<(LocalMedia, String)>,
+}
+
+/// Everything a page (or step) is made from, hashed together
+///
+/// Each input is added with its length, so inputs can't run into each other.
+#[derive(Debug, Default, Clone)]
+pub struct Inputs {
+    hasher: Sha256,
+}
+
+impl Inputs {
+    /// Add some bytes (or a string)
+    pub fn with(mut self, input: impl AsRef<[u8]>) -> Self {
+        let input = input.as_ref();
+        self.hasher.update((input.len() as u64).to_le_bytes());
+        self.hasher.update(input);
+        self
+    }
+
+    /// Add something serializable, like part of the config or some release data
+    pub fn with_json(self, input: &impl Serialize) -> Result<Self> {
+        Ok(self.with(serde_json::to_vec(input)?))
+    }
+
+    /// Add the contents of a file (a missing file counts as an input too)
+    pub fn with_file(self, path: impl AsRef<Utf8Path>) -> Self {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(contents) => self.with(path.as_str()).with(contents),
+            Err(_) => self.with(path.as_str()).with([]),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+/// The outputs of a build in progress, and what we know about the previous build
+#[derive(Debug)]
+pub struct BuildGraph {
+    dist: Utf8PathBuf,
+    previous: BuildManifest,
+    current: BuildManifest,
+}
+
+impl BuildGraph {
+    /// Start a build in the given dist dir, reading the manifest of the previous one
+    ///
+    /// If there isn't a (readable) manifest from the same version of oranda, everything
+    /// gets written from scratch.
+    pub fn load(dist_dir: &str) -> Result<Self> {
+        let dist = Utf8PathBuf::from(dist_dir);
+        let manifest_path = dist.join(MANIFEST_NAME);
+        let previous = LocalAsset::load_string(&manifest_path)
+            .ok()
+            .and_then(|manifest| serde_json::from_str::<BuildManifest>(&manifest).ok())
+            .filter(|manifest| {
+                manifest.version == MANIFEST_VERSION
+                    && manifest.oranda_version == env!("CARGO_PKG_VERSION")
+            })
+            .unwrap_or_default();
+        Ok(Self {
+            dist,
+            previous,
+            current: BuildManifest {
+                version: MANIFEST_VERSION,
+                oranda_version: env!("CARGO_PKG_VERSION").to_owned(),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Write a file, unless the previous build already wrote the exact same thing
+    ///
+    /// Returns whether the file was actually written.
+    pub fn write(&mut self, path: &Utf8Path, contents: impl AsRef<[u8]>) -> Result<bool> {
+        let contents = contents.as_ref();
+        let key = self.key(path);
+        let hash = hash(contents);
+        let unchanged = self.previous.outputs.get(&key) == Some(&hash) && path.exists();
+        if !unchanged {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+        self.current.outputs.insert(key, hash);
+        Ok(!unchanged)
+    }
+
+    /// Record a file that something else already wrote to the dist dir during this build
+    ///
+    /// (files that don't exist are ignored)
+    pub fn keep(&mut self, path: &Utf8Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = std::fs::read(path)?;
+        self.current.outputs.insert(self.key(path), hash(&contents));
+        Ok(())
+    }
+
+    /// Copy the contents of a directory into the dist dir
+    pub fn copy_dir(&mut self, src: &Utf8Path, dest: &Utf8Path) -> Result<()> {
+        for entry in walkdir::WalkDir::new(src) {
+            let entry = entry.map_err(|e| OrandaError::Io(e.into()))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(path) = Utf8Path::from_path(entry.path()) else {
+                continue;
+            };
+            let relative = path.strip_prefix(src)?;
+            let contents = std::fs::read(path)?;
+            self.write(&dest.join(relative), contents)?;
+        }
+        Ok(())
+    }
+
+    /// Check whether a step that writes everything in `dir` can be skipped
+    ///
+    /// This is the case if the previous build ran it with the same inputs, and its outputs
+    /// are all still there. If so, they're carried over into this build.
+    pub fn step_is_fresh(&mut self, name: &str, inputs: &str, dir: &Utf8Path) -> bool {
+        if self.previous.steps.get(name).map(String::as_str) != Some(inputs) {
+            return false;
+        }
+        let prefix = format!("{}/", self.key(dir));
+        let outputs = self
+            .previous
+            .outputs
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, hash)| (key.clone(), hash.clone()))
+            .collect::<Vec<_>>();
+        if outputs.is_empty() || outputs.iter().any(|(key, _)| !self.dist.join(key).exists()) {
+            return false;
+        }
+        self.current.outputs.extend(outputs);
+        self.record_step(name, inputs);
+        true
+    }
+
+    /// Record the inputs of a step that ran, so the next build can tell if it needs to
+    pub fn record_step(&mut self, name: &str, inputs: &str) {
+        self.current
+            .steps
+            .insert(name.to_owned(), inputs.to_owned());
+    }
+
+    /// Get a page that the previous build rendered from the same inputs, if its file is
+    /// still there (and the media it shows hasn't changed)
+    ///
+    /// The page gets carried over into this build, so it has to be written again like any
+    /// other page. That leaves the file alone, since its contents won't have changed.
+    pub fn fresh_page(
+        &mut self,
+        path: &Utf8Path,
+        filename: &str,
+        inputs: &str,
+    ) -> Result<Option<Page>> {
+        let key = self.key(path);
+        let Some(record) = self.previous.pages.get(&key) else {
+            return Ok(None);
+        };
+        if record.inputs != inputs || !path.exists() {
+            return Ok(None);
+        }
+        let media_changed = record.media.iter().any(|(media, media_hash)| {
+            std::fs::read(&media.source)
+                .ok()
+                .map(|contents| hash(&contents))
+                .as_ref()
+                != Some(media_hash)
+        });
+        if media_changed {
+            return Ok(None);
+        }
+        let record = record.clone();
+        let page = Page {
+            contents: std::fs::read_to_string(path)?,
+            body: record.body.clone(),
+            filename: filename.to_owned(),
+            last_modified: record.last_modified,
+            media: record
+                .media
+                .iter()
+                .map(|(media, _)| media.clone())
+                .collect(),
+        };
+        self.current.pages.insert(key, record);
+        Ok(Some(page))
+    }
+
+    /// Record the inputs a page was rendered from, so the next build can tell if it needs to
+    /// render it again
+    pub fn record_page(&mut self, path: &Utf8Path, inputs: &str, page: &Page) -> Result<()> {
+        let mut media = vec![];
+        for item in &page.media {
+            let contents = std::fs::read(&item.source)?;
+            media.push((item.clone(), hash(&contents)));
+        }
+        let record = PageRecord {
+            inputs: inputs.to_owned(),
+            body: page.body.clone(),
+            last_modified: page.last_modified,
+            media,
+        };
+        self.current.pages.insert(self.key(path), record);
+        Ok(())
+    }
+
+    /// Finish the build: delete everything in the dist dir that isn't part of it, and save
+    /// the manifest for next time
+    pub fn finish(self) -> Result<()> {
+        let mut dirs = vec![];
+        for entry in walkdir::WalkDir::new(&self.dist).min_depth(1) {
+            let entry = entry.map_err(|e| OrandaError::Io(e.into()))?;
+            let Some(path) = Utf8Path::from_path(entry.path()) else {
+                continue;
+            };
+            if entry.file_type().is_dir() {
+                dirs.push(path.to_owned());
+                continue;
+            }
+            let key = self.key(path);
+            if key != MANIFEST_NAME && !self.current.outputs.contains_key(&key) {
+                tracing::info!("Removing stale output {}", path);
+                std::fs::remove_file(path)?;
+            }
+        }
+        // Children come after their parents, so go backwards to clean up nested empty dirs
+        for dir in dirs.iter().rev() {
+            let is_empty = std::fs::read_dir(dir)?.next().is_none();
+            if is_empty {
+                std::fs::remove_dir(dir)?;
+            }
+        }
+
+        let manifest = serde_json::to_string_pretty(&self.current)?;
+        LocalAsset::write_new(&manifest, self.dist.join(MANIFEST_NAME))?;
+        Ok(())
+    }
+
+    /// The key we use for a file in the manifest: its path relative to the dist dir
+    fn key(&self, path: &Utf8Path) -> String {
+        let relative = path.strip_prefix(&self.dist).unwrap_or(path);
+        relative
+            .components()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Hash some inputs or outputs of a build
+pub fn hash(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Hash the contents of every file in a directory (and their paths)
+///
+/// Anything under `exclude` is skipped, for things like output dirs that live in the
+/// directory of their inputs.
+pub fn hash_dir(dir: &Utf8Path, exclude: &[Utf8PathBuf]) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let walker = walkdir::WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !exclude.iter().any(|path| entry.path() == path));
+    for entry in walker {
+        let entry = entry.map_err(|e| OrandaError::Io(e.into()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        hasher.update(entry.path().to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(std::fs::read(entry.path())?);
+        hasher.update([0]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
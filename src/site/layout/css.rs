@@ -1,7 +1,9 @@
 use std::env;
 
+use crate::data::cache::HttpCache;
 use crate::errors::*;
 use crate::message::{Message, MessageType};
+use crate::site::incremental::BuildGraph;
 
 use axoasset::{Asset, LocalAsset};
 use axohtml::elements::link;
@@ -26,22 +28,41 @@ fn concat_minify(css_files: &[String]) -> Result<String> {
     Ok(css)
 }
 
+/// Link to oranda's CSS, fetching it into the dist dir (unless an earlier build already did)
+///
+/// Also returns the name of the file it was written to. If we're offline and don't have a
+/// copy of the CSS, the site is built without it instead of failing.
 pub fn build_oranda(
     dist_dir: &str,
     path_prefix: &Option<String>,
     oranda_css_version: &Option<String>,
-) -> Result<Box<link<String>>> {
+    cache: &HttpCache,
+) -> Result<Option<(Box<link<String>>, String)>> {
     let dist_dir = dist_dir;
     let version = match oranda_css_version {
         Some(version) => version,
         None => LATEST_ORANDA_CSS,
     };
-    let filename = fetch_css(dist_dir, version)?;
+    let filename = match fetch_css(dist_dir, version, cache) {
+        Ok(filename) => filename,
+        Err(OrandaError::OfflineCacheMiss { .. }) => {
+            let msg = format!(
+                "Running offline without a cached copy of oranda's CSS (v{version}), so the site won't be styled. Set ORANDA_CSS to the path of a local copy to use that instead."
+            );
+            Message::new(MessageType::Warning, &msg).print();
+            tracing::warn!("{}", &msg);
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
     let abs_path = crate::site::link::generate(path_prefix, &filename);
-    Ok(html!(<link rel="stylesheet" href=abs_path></link>))
+    Ok(Some((
+        html!(<link rel="stylesheet" href=abs_path></link>),
+        filename,
+    )))
 }
 
-fn fetch_css(dist_dir: &str, version: &str) -> Result<String> {
+fn fetch_css(dist_dir: &str, version: &str, cache: &HttpCache) -> Result<String> {
     match env::var("ORANDA_CSS") {
         Ok(path) => {
             let filename = "oranda.css".to_string();
@@ -53,22 +74,23 @@ fn fetch_css(dist_dir: &str, version: &str) -> Result<String> {
         Err(_) => {
             let filename = format!("oranda-v{version}.css");
             let dest_path = Utf8Path::new(dist_dir).join(&filename);
+            // Each version of the CSS never changes, so a copy from an earlier build will do
+            if dest_path.is_file() {
+                return Ok(filename);
+            }
             let oranda_css_response =
-                tokio::runtime::Handle::current().block_on(fetch_oranda(version))?;
+                tokio::runtime::Handle::current().block_on(fetch_oranda(version, cache))?;
             axoasset::LocalAsset::write_new(&oranda_css_response, dest_path)?;
             Ok(filename)
         }
     }
 }
 
-async fn fetch_oranda(version: &str) -> Result<String> {
+async fn fetch_oranda(version: &str, cache: &HttpCache) -> Result<String> {
     let tag = format!("css-v{version}");
     let oranda_css_request =
         octolotl::request::ReleaseAsset::new("axodotdev", "oranda", &tag, "oranda.css");
-    Ok(octolotl::Request::send(&oranda_css_request, true)
-        .await?
-        .text()
-        .await?)
+    cache.fetch_github(&oranda_css_request).await
 }
 
 pub fn build_additional(path_prefix: &Option<String>) -> Box<link<String>> {
@@ -76,9 +98,13 @@ pub fn build_additional(path_prefix: &Option<String>) -> Box<link<String>> {
     html!(<link rel="stylesheet" href=abs_path></link>)
 }
 
-pub fn write_additional(additional_css: &[String], dist_dir: &Utf8Path) -> Result<()> {
+pub fn write_additional(
+    additional_css: &[String],
+    dist_dir: &Utf8Path,
+    graph: &mut BuildGraph,
+) -> Result<()> {
     let minified_css = concat_minify(additional_css)?;
 
-    LocalAsset::write_new(&minified_css, dist_dir.join("custom.css"))?;
+    graph.write(&dist_dir.join("custom.css"), minified_css)?;
     Ok(())
 }
@@ -1,6 +1,6 @@
 use crate::config::Config;
 use crate::errors::*;
-use crate::site::link;
+use crate::site::{feed, link};
 
 use axoasset::Asset;
 use axohtml::elements::meta;
@@ -40,7 +40,7 @@ pub fn get_favicon(
     favicon: String,
     dist_dir: String,
     path_prefix: &Option<String>,
-) -> Result<Box<axohtml::elements::link<String>>> {
+) -> Result<(Box<axohtml::elements::link<String>>, String)> {
     let copy_result_future = Asset::copy(&favicon, &dist_dir[..]);
     let copy_result = tokio::runtime::Handle::current().block_on(copy_result_future)?;
 
@@ -48,7 +48,29 @@ pub fn get_favicon(
 
     let favicon_url = link::generate(path_prefix, &path_as_string);
 
-    Ok(html!(<link rel="icon" href=favicon_url />))
+    Ok((
+        html!(<link rel="icon" href=favicon_url />),
+        path_as_string.into_owned(),
+    ))
+}
+
+/// Links to the changelog's feeds, so browsers and feed readers can find them
+// False positive duplicate allocation warning
+// https://github.com/rust-lang/rust-clippy/issues?q=is%3Aissue+redundant_allocation+sort%3Aupdated-desc
+#[allow(clippy::vec_box)]
+pub fn create_feed_links(config: &Config) -> Vec<Box<axohtml::elements::link<String>>> {
+    match &config.changelog {
+        Some(changelog) if changelog.feeds() => {
+            let title = format!("{} releases", config.name);
+            let rss = link::generate(&config.path_prefix, feed::RSS_FEED_NAME);
+            let atom = link::generate(&config.path_prefix, feed::ATOM_FEED_NAME);
+            vec![
+                html!(<link rel="alternate" type="application/rss+xml" title=&title href=rss />),
+                html!(<link rel="alternate" type="application/atom+xml" title=&title href=atom />),
+            ]
+        }
+        _ => vec![],
+    }
 }
 
 // False positive duplicate allocation warning
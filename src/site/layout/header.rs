@@ -3,13 +3,13 @@ use std::collections::HashMap;
 use crate::config::{ArtifactsConfig, Config, FundingConfig, MdBookConfig};
 use crate::errors::*;
 use crate::message::{Message, MessageType};
-use crate::site::{link, page};
+use crate::site::{link, page, search};
 
 use axoasset::Asset;
 use axohtml::elements::{div, header, img, li, nav};
 use axohtml::{html, text};
 
-fn get_logo(logo: String, config: &Config) -> Result<Box<img<String>>> {
+fn get_logo(logo: String, config: &Config) -> Result<(Box<img<String>>, String)> {
     let fetched_logo = fetch_logo(&config.dist_dir, logo, &config.name);
 
     tokio::runtime::Handle::current().block_on(fetched_logo)
@@ -19,12 +19,18 @@ async fn fetch_logo(
     dist_dir: &str,
     origin_path: String,
     name: &String,
-) -> Result<Box<img<String>>> {
+) -> Result<(Box<img<String>>, String)> {
     let copy_result = Asset::copy(&origin_path, dist_dir).await?;
 
-    let path_as_string = copy_result.strip_prefix(dist_dir)?.to_string_lossy();
+    let path_as_string = copy_result
+        .strip_prefix(dist_dir)?
+        .to_string_lossy()
+        .into_owned();
 
-    Ok(html!(<img src=path_as_string alt=name class="logo" />))
+    Ok((
+        html!(<img src=&path_as_string alt=name class="logo" />),
+        path_as_string,
+    ))
 }
 
 fn nav(
@@ -32,7 +38,7 @@ fn nav(
     path_prefix: &Option<String>,
     artifacts: &ArtifactsConfig,
     md_book: &Option<MdBookConfig>,
-    changelog: &bool,
+    changelog: bool,
     funding: &Option<FundingConfig>,
 ) -> Result<Box<nav<String>>> {
     Message::new(MessageType::Info, "Building nav...").print();
@@ -91,7 +97,7 @@ fn nav(
         html.extend(html!(<li><a href=href>{text!("Funding")}</a></li>));
     }
 
-    if *changelog {
+    if changelog {
         Message::new(MessageType::Info, "Adding changelog...").print();
         let href = if let Some(prefix) = &path_prefix {
             format!("/{}/{}/", prefix, "changelog")
@@ -110,9 +116,23 @@ fn nav(
     ))
 }
 
-pub fn create(config: &Config) -> Result<Box<header<String>>> {
+/// The search box, which searches the index built by [`search`][]
+fn search_box(path_prefix: &Option<String>) -> Box<div<String>> {
+    let index_url = link::generate(path_prefix, search::SEARCH_INDEX_NAME);
+    html!(
+        <div class="search" data-index=index_url>
+            <input type="search" class="search-input" placeholder="Search (press /)" aria-label="Search"/>
+            <ul class="search-results"></ul>
+        </div>
+    )
+}
+
+/// Create the header, recording any files it copies to the dist dir in `assets`
+pub fn create(config: &Config, assets: &mut Vec<String>) -> Result<Box<header<String>>> {
     let logo = if let Some(logo) = config.logo.clone() {
-        Some(get_logo(logo, config)?)
+        let (logo, path) = get_logo(logo, config)?;
+        assets.push(path);
+        Some(logo)
     } else {
         None
     };
@@ -120,23 +140,25 @@ pub fn create(config: &Config) -> Result<Box<header<String>>> {
     let nav = if config.additional_pages.is_some()
         || config.artifacts.has_some()
         || config.mdbook.is_some()
-        || config.changelog
+        || config.changelog.is_some()
     {
         Some(nav(
             &config.additional_pages,
             &config.path_prefix,
             &config.artifacts,
             &config.mdbook,
-            &config.changelog,
+            config.changelog.is_some(),
             &config.funding,
         )?)
     } else {
         None
     };
+    let search = config.search.then(|| search_box(&config.path_prefix));
     Ok(html!(
         <header>
             {logo}
             <h1 class="title">{text!(&config.name)}</h1>
+            {search}
             {nav}
         </header>
     ))
@@ -0,0 +1,50 @@
+// Only included in pages built by `oranda dev`: waits for the next build, then reloads the
+// page (or shows what went wrong, if the build failed).
+(function () {
+  const ENDPOINT = "/__oranda/live-reload";
+  const OVERLAY_ID = "oranda-live-reload-error";
+  let lastBuild = null;
+
+  function showError(error) {
+    let overlay = document.getElementById(OVERLAY_ID);
+    if (!overlay) {
+      overlay = document.createElement("div");
+      overlay.id = OVERLAY_ID;
+      overlay.style.cssText =
+        "position:fixed;inset:0;z-index:2147483647;overflow:auto;padding:2rem;" +
+        "background:rgba(20,20,20,0.95);color:#f5f5f5;font-family:monospace;";
+      const heading = document.createElement("h2");
+      heading.textContent = "oranda couldn't build your site";
+      heading.style.cssText = "color:#ff6b6b;margin-top:0;";
+      const details = document.createElement("pre");
+      details.style.cssText = "white-space:pre-wrap;font-size:0.9rem;";
+      const hint = document.createElement("p");
+      hint.textContent = "This page will reload when the next build succeeds.";
+      overlay.append(heading, details, hint);
+      document.body.appendChild(overlay);
+    }
+    overlay.querySelector("pre").textContent = error;
+  }
+
+  async function poll() {
+    try {
+      const query = lastBuild === null ? "" : "?since=" + lastBuild;
+      const response = await fetch(ENDPOINT + query);
+      const status = await response.json();
+      const changed = lastBuild !== null && status.build !== lastBuild;
+      lastBuild = status.build;
+      if (status.error) {
+        showError(status.error);
+      } else if (changed) {
+        window.location.reload();
+        return;
+      }
+      poll();
+    } catch (e) {
+      // The dev server is probably restarting, try again in a bit
+      setTimeout(poll, 1000);
+    }
+  }
+
+  poll();
+})();
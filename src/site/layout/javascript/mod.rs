@@ -1,13 +1,15 @@
-use axoasset::LocalAsset;
-use axohtml::{elements::script, html};
+use axohtml::{elements::script, html, unsafe_text};
 use camino::Utf8Path;
 
 use crate::errors::*;
+use crate::site::incremental::BuildGraph;
 use crate::site::link;
 
 pub mod analytics;
 
 const ARTIFACTS_SCRIPT_SOURCE: &str = include_str!("./artifacts.js");
+const LIVE_RELOAD_SCRIPT_SOURCE: &str = include_str!("./live_reload.js");
+const SEARCH_SCRIPT_SOURCE: &str = include_str!("./search.js");
 
 pub fn build_os_script(path_prefix: &Option<String>) -> String {
     let script_url = link::generate(path_prefix, "artifacts.js");
@@ -15,7 +17,25 @@ pub fn build_os_script(path_prefix: &Option<String>) -> String {
     script.to_string()
 }
 
-pub fn write_os_script(dist_dir: &Utf8Path) -> Result<()> {
-    LocalAsset::write_new(ARTIFACTS_SCRIPT_SOURCE, dist_dir.join("artifacts.js"))?;
+pub fn write_os_script(dist_dir: &Utf8Path, graph: &mut BuildGraph) -> Result<()> {
+    graph.write(&dist_dir.join("artifacts.js"), ARTIFACTS_SCRIPT_SOURCE)?;
     Ok(())
 }
+
+/// The script behind the search box in the header
+pub fn build_search_script(path_prefix: &Option<String>) -> Box<script<String>> {
+    let script_url = link::generate(path_prefix, "search.js");
+    html!(<script src=script_url defer=true />)
+}
+
+pub fn write_search_script(dist_dir: &Utf8Path, graph: &mut BuildGraph) -> Result<()> {
+    graph.write(&dist_dir.join("search.js"), SEARCH_SCRIPT_SOURCE)?;
+    Ok(())
+}
+
+/// The script that makes pages reload themselves when `oranda dev` rebuilds the site
+pub fn build_live_reload_script() -> String {
+    let script: Box<script<String>> =
+        html!(<script>{unsafe_text!(LIVE_RELOAD_SCRIPT_SOURCE)}</script>);
+    script.to_string()
+}
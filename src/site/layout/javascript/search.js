@@ -0,0 +1,114 @@
+// Powers the search box in the header: fetches the site's search index the first time it's
+// needed, and lists the best matches for what's been typed.
+(function () {
+  const MAX_RESULTS = 10;
+  const SNIPPET_LENGTH = 120;
+
+  const box = document.querySelector(".search");
+  if (!box) {
+    return;
+  }
+  const input = box.querySelector(".search-input");
+  const results = box.querySelector(".search-results");
+  let index = null;
+
+  function loadIndex() {
+    if (!index) {
+      index = fetch(box.dataset.index)
+        .then((response) => response.json())
+        .then((data) =>
+          data.entries.map((entry) => ({
+            ...entry,
+            lowerTitle: entry.title.toLowerCase(),
+            lowerText: entry.text.toLowerCase(),
+          }))
+        )
+        .catch(() => []);
+    }
+    return index;
+  }
+
+  function score(entry, terms) {
+    let total = 0;
+    for (const term of terms) {
+      const inTitle = entry.lowerTitle.includes(term);
+      const inText = entry.lowerText.includes(term);
+      // Every term has to show up somewhere
+      if (!inTitle && !inText) {
+        return 0;
+      }
+      total += (inTitle ? 10 : 0) + (inText ? 1 : 0);
+    }
+    return total;
+  }
+
+  function snippet(entry, terms) {
+    const position = Math.max(
+      0,
+      Math.min(...terms.map((term) => entry.lowerText.indexOf(term)).filter((i) => i >= 0))
+    );
+    const start = Math.max(0, position - SNIPPET_LENGTH / 4);
+    const text = entry.text.slice(start, start + SNIPPET_LENGTH);
+    return (start > 0 ? "…" : "") + text + (start + SNIPPET_LENGTH < entry.text.length ? "…" : "");
+  }
+
+  async function search() {
+    const terms = input.value.toLowerCase().split(/\s+/).filter((term) => term.length > 0);
+    results.replaceChildren();
+    if (terms.length === 0) {
+      box.classList.remove("open");
+      return;
+    }
+    const entries = await loadIndex();
+    const matches = entries
+      .map((entry) => ({ entry, score: score(entry, terms) }))
+      .filter((match) => match.score > 0)
+      .sort((a, b) => b.score - a.score)
+      .slice(0, MAX_RESULTS);
+
+    if (matches.length === 0) {
+      const item = document.createElement("li");
+      item.className = "search-empty";
+      item.textContent = "No results";
+      results.appendChild(item);
+    }
+    for (const { entry } of matches) {
+      const item = document.createElement("li");
+      const link = document.createElement("a");
+      link.href = entry.url;
+      const title = document.createElement("span");
+      title.className = "search-result-title";
+      title.textContent = entry.title;
+      const text = document.createElement("span");
+      text.className = "search-result-text";
+      text.textContent = snippet(entry, terms);
+      link.append(title, text);
+      item.appendChild(link);
+      results.appendChild(item);
+    }
+    box.classList.add("open");
+  }
+
+  input.addEventListener("focus", loadIndex);
+  input.addEventListener("input", search);
+  input.addEventListener("keydown", (event) => {
+    if (event.key === "Escape") {
+      input.value = "";
+      search();
+      input.blur();
+    }
+  });
+  document.addEventListener("keydown", (event) => {
+    // "/" jumps to the search box, unless you're typing somewhere already
+    const typing = ["INPUT", "TEXTAREA"].includes(document.activeElement.tagName);
+    if (event.key === "/" && !typing) {
+      event.preventDefault();
+      input.focus();
+    }
+  });
+  document.addEventListener("click", (event) => {
+    if (!box.contains(event.target)) {
+      box.classList.remove("open");
+    }
+  });
+})();
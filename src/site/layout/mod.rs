@@ -1,7 +1,9 @@
 use axohtml::{html, text};
 
 use crate::config::Config;
+use crate::data::cache::HttpCache;
 use crate::errors::*;
+use crate::site::incremental::Inputs;
 use crate::site::oranda_theme::OrandaTheme;
 
 pub mod css;
@@ -14,25 +16,51 @@ use javascript::analytics::Analytics;
 #[derive(Debug)]
 pub struct Layout {
     template: String,
+    /// Files written to the dist dir while building the layout, relative to it
+    assets: Vec<String>,
+    /// Whether pages should reload themselves when the site is rebuilt
+    live_reload: bool,
 }
 
 const DOCTYPE: &str = r#"<!doctype html>"#;
 const BODY_PLACEHOLDER: &str = "{{{BODY}}}";
 const OS_SCRIPT_PLACEHOLDER: &str = "{{{OS_SCRIPT}}}";
+const LIVE_RELOAD_PLACEHOLDER: &str = "{{{LIVE_RELOAD}}}";
 
 impl Layout {
     pub fn render(&self, body: String, os_script: Option<String>) -> String {
+        let live_reload = if self.live_reload {
+            javascript::build_live_reload_script()
+        } else {
+            String::new()
+        };
         self.template
             .replace(BODY_PLACEHOLDER, &body)
             .replace(OS_SCRIPT_PLACEHOLDER, &os_script.unwrap_or(String::new()))
+            .replace(LIVE_RELOAD_PLACEHOLDER, &live_reload)
+    }
+
+    /// A hash of everything the layout puts around a page, for telling whether pages have to
+    /// be rendered again
+    pub fn fingerprint(&self) -> String {
+        Inputs::default()
+            .with(&self.template)
+            .with([self.live_reload as u8])
+            .finish()
+    }
+
+    /// The files that building this layout wrote to the dist dir (like the oranda CSS)
+    pub fn assets(&self) -> &[String] {
+        &self.assets
     }
 
     pub fn new(config: &Config) -> Result<Self> {
+        let mut assets = vec![];
         let theme = OrandaTheme::css_class(&config.styles.theme());
         let name = &config.name;
         let header = match config.no_header {
             true => None,
-            false => Some(header::create(config)?),
+            false => Some(header::create(config, &mut assets)?),
         };
         let homepage = config.homepage.as_ref().map(|homepage| {
             html!(
@@ -41,12 +69,12 @@ impl Layout {
         });
         let banner = header::repo_banner(config);
         let meta_tags = head::create_meta_tags(config);
+        let feed_links = head::create_feed_links(config);
         let favicon = if let Some(favicon) = config.favicon.clone() {
-            Some(head::get_favicon(
-                favicon,
-                config.dist_dir.clone(),
-                &config.path_prefix,
-            )?)
+            let (favicon, path) =
+                head::get_favicon(favicon, config.dist_dir.clone(), &config.path_prefix)?;
+            assets.push(path);
+            Some(favicon)
         } else {
             None
         };
@@ -61,7 +89,15 @@ impl Layout {
             &config.dist_dir,
             &config.path_prefix,
             &config.styles.oranda_css_version,
+            &HttpCache::new(config),
         )?;
+        let oranda_css = oranda_css.map(|(link, path)| {
+            assets.push(path);
+            link
+        });
+        // The search box lives in the header
+        let search_script = (config.search && !config.no_header)
+            .then(|| javascript::build_search_script(&config.path_prefix));
         let analytics = Analytics::new(&config.analytics);
         let template_html: String = html!(
         <html lang="en" id="oranda" class=theme>
@@ -70,8 +106,10 @@ impl Layout {
                 {homepage}
                 {favicon}
                 {meta_tags}
+                {feed_links}
                 {oranda_css}
                 {additional_css}
+                {search_script}
             </head>
             <body>
             <div class="container">
@@ -85,6 +123,7 @@ impl Layout {
                 {analytics.snippet}
                 {analytics.google_script}
                 <div>{text!(OS_SCRIPT_PLACEHOLDER)}</div>
+                {text!(LIVE_RELOAD_PLACEHOLDER)}
             </body>
         </html>
         )
@@ -92,6 +131,10 @@ impl Layout {
 
         let template = format!("{DOCTYPE}{template_html}");
 
-        Ok(Layout { template })
+        Ok(Layout {
+            template,
+            assets,
+            live_reload: config.live_reload,
+        })
     }
 }
@@ -1,3 +1,7 @@
+use url::Url;
+
+use crate::config::Config;
+
 pub fn generate(path_prefix: &Option<String>, file_name: &str) -> String {
     if let Some(prefix) = &path_prefix {
         format!("/{}/{}", prefix, file_name)
@@ -5,3 +9,27 @@ pub fn generate(path_prefix: &Option<String>, file_name: &str) -> String {
         format!("/{}", file_name)
     }
 }
+
+/// Get the path a page is served at, once it's written as a "pretty link"
+/// (e.g. `changelog.html` is served at `changelog/`)
+pub fn pretty(filename: &str) -> String {
+    match filename.strip_suffix("index.html") {
+        Some(dir) => dir.to_owned(),
+        None => format!("{}/", filename.trim_end_matches(".html")),
+    }
+}
+
+/// Get the full URL of something on the site
+///
+/// We build these from the `homepage`, if it's set. Otherwise, we have to settle for paths.
+pub fn absolute(config: &Config, file_name: &str) -> String {
+    let path = generate(&config.path_prefix, file_name);
+    let homepage = config
+        .homepage
+        .as_ref()
+        .and_then(|homepage| Url::parse(homepage).ok());
+    match homepage.and_then(|homepage| homepage.join(&path).ok()) {
+        Some(url) => url.to_string(),
+        None => path,
+    }
+}
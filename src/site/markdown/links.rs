@@ -0,0 +1,221 @@
+//! Rewriting relative links in markdown, so they still work on the built site
+//!
+//! READMEs link to other files in the repository, relative to where they are. On the site,
+//! those files are either pages oranda built, part of the mdbook, or not published at all:
+//!
+//! - links to the README or an additional page go to that page
+//! - links into the mdbook's source dir go to the built book
+//! - links to anything else in the repository go to the file on the forge (if we know how)
+//!
+//! Images and other media have to be on the site itself, so those get copied into the dist
+//! dir (see [`LocalMedia`][]).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::data::source::Repo;
+use crate::site::incremental;
+use crate::site::link;
+use crate::site::mdbook::{load_mdbook, mdbook_dir};
+
+/// Where local images and media get copied to in the dist dir
+pub const MEDIA_DIR: &str = "media";
+
+/// An image (or video, or other media) in the project that a page shows
+///
+/// Media gets copied into [`MEDIA_DIR`][] with a hash of its contents in the name, so it
+/// can't clash with other files with the same name, and browsers notice when it changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalMedia {
+    /// Where the file is in the project
+    pub source: Utf8PathBuf,
+    /// Where the file goes, relative to the dist dir
+    pub dest: String,
+}
+
+/// Rewrites the relative links in a markdown file
+///
+/// All the paths in here are absolute, since the files can be given to us either way.
+#[derive(Debug)]
+pub struct LinkRewriter {
+    /// The directory the markdown file is in
+    source_dir: Utf8PathBuf,
+    /// The root of the git checkout the project is in, which paths in the repository are
+    /// relative to (or our best guess, if it isn't in one)
+    repo_root: Utf8PathBuf,
+    path_prefix: Option<String>,
+    /// The markdown files that become pages, and the paths of those pages
+    pages: HashMap<Utf8PathBuf, String>,
+    /// The mdbook's source dir
+    book_src: Option<Utf8PathBuf>,
+    repo: Option<Repo>,
+    /// The media the file shows, so far
+    media: Mutex<Vec<LocalMedia>>,
+}
+
+impl LinkRewriter {
+    /// Get ready to rewrite the links in the markdown file at `source`
+    pub fn new(config: &Config, source: &str) -> Self {
+        let pwd = axoasset::LocalAsset::current_dir().unwrap_or_default();
+        let absolute = |path: &str| normalize(&pwd.join(path));
+
+        let mut pages = HashMap::new();
+        if !config.readme_path.is_empty() {
+            pages.insert(absolute(&config.readme_path), String::new());
+        }
+        if let Some(additional_pages) = &config.additional_pages {
+            for path in additional_pages.values() {
+                if let Some(stem) = Utf8Path::new(path).file_stem() {
+                    pages.insert(absolute(path), format!("{stem}/"));
+                }
+            }
+        }
+
+        // The build reports any problems with the book, so we don't have to
+        let book_src = config.mdbook.as_ref().and_then(|book_cfg| {
+            book_cfg.path.as_ref()?;
+            let book = load_mdbook(&mdbook_dir(book_cfg).ok()?).ok()?;
+            let src = Utf8PathBuf::from_path_buf(book.source_dir()).ok()?;
+            Some(normalize(&src))
+        });
+
+        let repo = config
+            .repository
+            .as_ref()
+            .and_then(|repo_url| Repo::from_url(repo_url, config.releases.source).ok());
+
+        let source_dir = absolute(source)
+            .parent()
+            .map(|dir| dir.to_owned())
+            .unwrap_or_default();
+        let repo_root = source_dir
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .or_else(|| source_dir.starts_with(&pwd).then_some(pwd.as_path()))
+            .unwrap_or(&source_dir)
+            .to_owned();
+
+        LinkRewriter {
+            source_dir,
+            repo_root,
+            path_prefix: config.path_prefix.clone(),
+            pages,
+            book_src,
+            repo,
+            media: Mutex::default(),
+        }
+    }
+
+    /// Get where a link should point on the built site, if it needs to change
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        let (file, fragment) = self.resolve(url)?;
+
+        if let Some(page) = self.pages.get(&file) {
+            return Some(format!(
+                "{}{fragment}",
+                link::generate(&self.path_prefix, page)
+            ));
+        }
+        if let Some(book_src) = &self.book_src {
+            if let Ok(in_book) = file.strip_prefix(book_src) {
+                // mdbook builds each chapter into an HTML file next to where its source was,
+                // except that a README becomes the index of its directory
+                let in_book = match (in_book.file_name(), in_book.extension()) {
+                    (Some("README.md"), _) => in_book.with_file_name("index.html"),
+                    (_, Some("md")) => in_book.with_extension("html"),
+                    _ => in_book.to_owned(),
+                };
+                return Some(format!(
+                    "{}{fragment}",
+                    link::generate(&self.path_prefix, &format!("book/{in_book}"))
+                ));
+            }
+        }
+        if file.exists() {
+            let repo = self.repo.as_ref()?;
+            let path = file.strip_prefix(&self.repo_root).ok()?;
+            return Some(format!("{}{fragment}", repo.file_url(path.as_str())?));
+        }
+        None
+    }
+
+    /// Get where an image (or other media) should be loaded from on the built site, if it's
+    /// a file in the project, and remember to copy it there
+    pub fn media(&self, url: &str) -> Option<String> {
+        let (file, fragment) = self.resolve(url)?;
+        let contents = std::fs::read(&file).ok()?;
+        let hash = incremental::hash(&contents);
+        let stem = file.file_stem()?;
+        let name = match file.extension() {
+            Some(ext) => format!("{stem}.{}.{ext}", &hash[..8]),
+            None => format!("{stem}.{}", &hash[..8]),
+        };
+        let dest = format!("{MEDIA_DIR}/{name}");
+
+        let mut media = self.media.lock().expect("media list was poisoned");
+        if !media.iter().any(|m| m.dest == dest) {
+            media.push(LocalMedia {
+                source: file,
+                dest: dest.clone(),
+            });
+        }
+        Some(format!(
+            "{}{fragment}",
+            link::generate(&self.path_prefix, &dest)
+        ))
+    }
+
+    /// Get all the media found while rendering
+    pub fn take_media(&self) -> Vec<LocalMedia> {
+        std::mem::take(&mut self.media.lock().expect("media list was poisoned"))
+    }
+
+    /// Work out which file in the project a relative link points at, splitting off any query
+    /// or fragment
+    fn resolve<'a>(&self, url: &'a str) -> Option<(Utf8PathBuf, &'a str)> {
+        // Links with a scheme (like `https:` or `mailto:`), absolute paths, and anchors on
+        // the same page are already fine
+        if url.is_empty() || url.starts_with(['/', '#']) || url::Url::parse(url).is_ok() {
+            return None;
+        }
+        let (path, fragment) = match url.find(['?', '#']) {
+            Some(idx) => url.split_at(idx),
+            None => (url, ""),
+        };
+        let path = percent_decode_str(path).decode_utf8().ok()?;
+        let file = normalize(&self.source_dir.join(path.as_ref()));
+        // Links that leave the repository aren't something we can do anything with
+        if !file.starts_with(&self.repo_root) {
+            return None;
+        }
+        Some((file, fragment))
+    }
+}
+
+/// Tidy up a path, resolving `.` and `..` without touching the filesystem
+fn normalize(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalized = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => {
+                let can_pop = matches!(
+                    normalized.components().next_back(),
+                    Some(Utf8Component::Normal(_))
+                );
+                if can_pop {
+                    normalized.pop();
+                } else {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
@@ -1,6 +1,10 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
+mod links;
 mod syntax_highlight;
+pub use links::{LinkRewriter, LocalMedia, MEDIA_DIR};
 pub use syntax_highlight::syntax_highlight;
 pub use syntax_highlight::syntax_themes::SyntaxTheme;
 
@@ -8,7 +12,8 @@ use crate::errors::*;
 
 use ammonia::Builder;
 use comrak::adapters::SyntaxHighlighterAdapter;
-use comrak::{self, ComrakOptions, ComrakPlugins};
+use comrak::nodes::NodeValue;
+use comrak::{self, Arena, ComrakOptions, ComrakPlugins};
 
 pub struct Adapters<'a> {
     syntax_theme: &'a SyntaxTheme,
@@ -48,16 +53,80 @@ fn initialize_comrak_options() -> ComrakOptions {
 }
 
 pub fn to_html(markdown: &str, syntax_theme: &SyntaxTheme) -> Result<String> {
+    render(markdown, syntax_theme, None)
+}
+
+/// Render markdown from a file in the project, rewriting its relative links to work on the site
+///
+/// Any images and media it shows from the project are collected in `links`.
+pub fn to_html_with_links(
+    markdown: &str,
+    syntax_theme: &SyntaxTheme,
+    links: &Arc<LinkRewriter>,
+) -> Result<String> {
+    render(markdown, syntax_theme, Some(links))
+}
+
+fn render(
+    markdown: &str,
+    syntax_theme: &SyntaxTheme,
+    links: Option<&Arc<LinkRewriter>>,
+) -> Result<String> {
     let options = initialize_comrak_options();
 
     let mut plugins = ComrakPlugins::default();
     let adapter = Adapters { syntax_theme };
     plugins.render.codefence_syntax_highlighter = Some(&adapter);
 
-    let unsafe_html = comrak::markdown_to_html_with_plugins(markdown, &options, &plugins);
-    let safe_html = Builder::new()
+    let arena = Arena::new();
+    let root = comrak::parse_document(&arena, markdown, &options);
+    if let Some(links) = links {
+        for node in root.descendants() {
+            if let NodeValue::Link(link) = &mut node.data.borrow_mut().value {
+                if let Some(url) = links.rewrite(&link.url) {
+                    link.url = url;
+                }
+            }
+        }
+    }
+    let mut unsafe_html = vec![];
+    comrak::format_html_with_plugins(root, &options, &mut unsafe_html, &plugins)?;
+    let unsafe_html = String::from_utf8_lossy(&unsafe_html);
+
+    let mut builder = Builder::new();
+    builder
         .add_generic_attributes(&["style", "class", "id"])
-        .clean(&unsafe_html)
-        .to_string();
+        .add_tags(&["video", "audio", "source"])
+        .add_tag_attributes(
+            "video",
+            &[
+                "src",
+                "poster",
+                "controls",
+                "autoplay",
+                "loop",
+                "muted",
+                "playsinline",
+                "width",
+                "height",
+            ],
+        )
+        .add_tag_attributes("audio", &["src", "controls", "autoplay", "loop", "muted"])
+        .add_tag_attributes("source", &["src", "type"]);
+    if let Some(links) = links {
+        // This catches images from markdown, as well as any media in inline HTML
+        let links = links.clone();
+        builder.attribute_filter(move |element, attribute, value| {
+            let is_media = matches!(
+                (element, attribute),
+                ("img" | "video" | "audio" | "source", "src") | ("video", "poster")
+            );
+            match is_media.then(|| links.media(value)).flatten() {
+                Some(url) => Some(Cow::Owned(url)),
+                None => Some(Cow::Borrowed(value)),
+            }
+        });
+    }
+    let safe_html = builder.clean(&unsafe_html).to_string();
     Ok(safe_html)
 }
@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
-use serde::Deserialize;
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, JsonSchema)]
+use serde::{Deserialize, Serialize};
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema,
+)]
 pub enum SyntaxTheme {
     AgilaClassicOceanicNext,
     AgilaCobalt,
@@ -5,7 +5,8 @@ use mdbook::MDBook;
 use crate::config::MdBookConfig;
 use crate::errors::*;
 use crate::message::{Message, MessageType};
-use crate::site::{oranda_theme::OrandaTheme, Site};
+use crate::site::incremental::{self, BuildGraph};
+use crate::site::oranda_theme::OrandaTheme;
 
 use super::markdown::SyntaxTheme;
 
@@ -23,6 +24,9 @@ const THEME_BOOK_JS: &str = include_str!("../../oranda-css/mdbook-theme/book.js"
 const THEME_INDEX_HBS_PATH: &str = "index.hbs";
 const THEME_INDEX_HBS: &str = include_str!("../../oranda-css/mdbook-theme/index.hbs");
 
+/// The name of the mdbook build in the build graph
+const MDBOOK_STEP: &str = "mdbook";
+
 /// variables.css needs us to substitute this with an entry from MDBOOK_THEMES
 const KEY_ORANDA_VARS: &str = "/*ORANDA-THEME-VARS*/";
 /// index.hbs needs us to substitute this with at least one copy of THEME_BUTTON_HTML_TEMPLATE
@@ -154,9 +158,8 @@ impl AxomdbookTheme {
 /// Get a proper absolute path to the mdbook's dir (the one containing book.toml)
 ///
 /// This needs to be absolute because some mdbook renderers flip out with relative paths.
-///
-/// FIXME: this is broken if pwd is not the same dir as oranda.json. Our config code
-/// should do this mapping for us, when it still knows where oranda.json is!
+/// (the config code already made the path relative to the current dir, rather than to
+/// wherever oranda.json is)
 pub fn mdbook_dir(book_cfg: &MdBookConfig) -> Result<Utf8PathBuf> {
     let pwd = axoasset::LocalAsset::current_dir()?;
     let book_path = book_cfg
@@ -187,14 +190,29 @@ pub fn build_mdbook(
     book_cfg: &MdBookConfig,
     oranda_theme: &OrandaTheme,
     syntax_theme: &SyntaxTheme,
+    graph: &mut BuildGraph,
 ) -> Result<()> {
-    Message::new(MessageType::Info, "Building mdbook...").print();
-    tracing::info!("Building mdbook...");
-
     // Read mdbook's config to inherit the user's setup
     let book_dir = mdbook_dir(book_cfg)?;
     let mut md = load_mdbook(&book_dir)?;
 
+    // If neither the book nor how we theme it changed, the last build's copy is still good
+    // FIXME: make this something they can set in the MdBookConfig
+    let book_dist = dist.join("book");
+    let book_build_dir = Utf8PathBuf::from_path_buf(md.root.join(&md.config.build.build_dir))
+        .expect("mdbook path wasn't utf8");
+    let inputs = format!(
+        "{}:{book_cfg:?}:{oranda_theme:?}:{syntax_theme:?}",
+        incremental::hash_dir(&book_dir, &[book_build_dir])?
+    );
+    if graph.step_is_fresh(MDBOOK_STEP, &inputs, &book_dist) {
+        tracing::info!("mdbook hasn't changed, skipping its build");
+        return Ok(());
+    }
+
+    Message::new(MessageType::Info, "Building mdbook...").print();
+    tracing::info!("Building mdbook...");
+
     // If custom theme is enabled, set that up
     let custom_theme = custom_theme(book_cfg, oranda_theme);
     let theme_dir = custom_theme_dir(book_cfg, dist)?;
@@ -236,9 +254,8 @@ pub fn build_mdbook(
     }
 
     // Copy the contents to "public/book/"
-    // FIXME: make this something they can set in the MdBookConfig
-    let book_dist = dist.join("book");
-    Site::copy_static(&book_dist, build_dir.as_str())?;
+    graph.copy_dir(&build_dir, &book_dist)?;
+    graph.record_step(MDBOOK_STEP, &inputs);
 
     Ok(())
 }
@@ -1,44 +1,94 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
 
-use axoasset::LocalAsset;
 use camino::{Utf8Path, Utf8PathBuf};
+use serde::Serialize;
 
-use crate::config::Config;
-use crate::data::{funding::Funding, Context};
+use crate::config::{ArtifactsConfig, Config, FundingConfig};
+use crate::data::{cache::HttpCache, funding::Funding, Context, Release};
 use crate::errors::*;
 use crate::message::{Message, MessageType};
 
+use incremental::{BuildGraph, Inputs};
 pub use layout::javascript;
 use layout::{css, Layout};
 use page::Page;
+use search::SearchEntry;
+use sitemap::SitemapEntry;
 
 pub mod artifacts;
 pub mod changelog;
+pub mod check;
+pub mod feed;
 pub mod funding;
+pub mod hosting;
 pub mod icons;
+pub mod incremental;
 pub mod layout;
 pub mod link;
 pub mod markdown;
 pub mod mdbook;
 pub mod oranda_theme;
 pub mod page;
+pub mod search;
+pub mod sitemap;
+pub mod workspace;
+
+/// What a site contributes to the indexes of everything on it
+#[derive(Debug, Default)]
+struct SiteIndex {
+    search: Vec<SearchEntry>,
+    sitemap: Vec<SitemapEntry>,
+}
 
 #[derive(Debug)]
 pub struct Site {
+    /// What the previous build wrote to the dist dir, and what this one has so far
+    graph: BuildGraph,
+    contents: SiteContents,
+}
+
+/// Everything that goes in the dist dir for a site
+#[derive(Debug)]
+struct SiteContents {
     pages: Vec<Page>,
+    /// Files that were written straight to the dist dir while building, relative to it
+    assets: Vec<String>,
+    /// Other files to write to the dist dir (like feeds), relative to it, with their contents
+    files: Vec<(String, String)>,
+    /// The sites for each package, if this is the site for a whole workspace
+    members: Vec<(Config, SiteContents)>,
 }
 
 impl Site {
     pub fn build(config: &Config) -> Result<Site> {
-        Self::clean_dist_dir(&config.dist_dir)?;
+        Self::create_dist_dir(&config.dist_dir)?;
+        let mut graph = BuildGraph::load(&config.dist_dir)?;
+        let contents = if config.members.is_empty() {
+            Self::build_single(config, &mut graph, true)?
+        } else {
+            Self::build_workspace(config, &mut graph)?
+        };
+        Ok(Site { graph, contents })
+    }
 
+    /// Build the site for a single project
+    ///
+    /// `is_root` is whether the site goes at the root of the dist dir, rather than being one
+    /// of the packages of a workspace.
+    fn build_single(
+        config: &Config,
+        graph: &mut BuildGraph,
+        is_root: bool,
+    ) -> Result<SiteContents> {
         let mut pages = vec![];
+        let mut files = vec![];
         let layout_template = Layout::new(config)?;
+        let mut assets = layout_template.assets().to_vec();
 
         if let Some(files) = &config.additional_pages {
             let mut additional_pages =
-                Self::build_additional_pages(files, &layout_template, config)?;
+                Self::build_additional_pages(files, &layout_template, config, graph)?;
             pages.append(&mut additional_pages);
         }
 
@@ -47,33 +97,45 @@ impl Site {
         if Self::needs_context(config) {
             match &config.repository {
                 Some(repo_url) => {
-                    let mut context = Context::new(repo_url, &config.artifacts)?;
+                    let mut context = Context::new(repo_url, config)?;
+                    let cache = HttpCache::new(config);
                     // FIXME: change the config so that you can set `artifacts: false` and disable this?
                     if context.latest().is_some() {
-                        context.latest_mut().unwrap().artifacts.make_scripts_viewable(config)?;
-                        index = Some(Page::index_with_artifacts(&context, &layout_template, config)?);
-                        let body = artifacts::page(&context, config)?;
-                        let artifacts_page = Page::new_from_contents(
-                            body,
-                            "artifacts.html",
-                            &layout_template,
-                            config,
-                        );
+                        context.latest_mut().unwrap().artifacts.make_scripts_viewable(config, &cache)?;
+                        assets.extend(Self::viewable_scripts(context.latest().unwrap()));
+                        let inputs = Self::context_inputs(Self::release_inputs(&layout_template, config)?, &context)?
+                            .with_json(&Self::markdown_config(config))?
+                            .with_file(&config.readme_path)
+                            .with(&config.name)
+                            .with(&config.description);
+                        index = Some(Self::render_page(graph, config, "index.html", inputs, || {
+                            Page::index_with_artifacts(&context, &layout_template, config)
+                        })?);
+                        let inputs = Self::context_inputs(Self::release_inputs(&layout_template, config)?, &context)?;
+                        let artifacts_page = Self::render_page(graph, config, "artifacts.html", inputs, || {
+                            let body = artifacts::page(&context, config)?;
+                            let mut artifacts_page = Page::new_from_contents(
+                                body,
+                                "artifacts.html",
+                                &layout_template,
+                                config,
+                            );
+                            artifacts_page.last_modified = context.latest().unwrap().published_at();
+                            Ok(artifacts_page)
+                        })?;
                         pages.push(artifacts_page);
+                        let mut versioned_pages = Self::build_versioned_artifacts_pages(&mut context, &layout_template, config, &cache, &mut assets, graph)?;
+                        pages.append(&mut versioned_pages);
                     }
-                    if config.changelog {
-                        let mut changelog_pages = Self::build_changelog_pages(&context, &layout_template, config)?;
+                    if let Some(changelog_cfg) = &config.changelog {
+                        let mut changelog_pages = Self::build_changelog_pages(&context, &layout_template, config, graph)?;
                         pages.append(&mut changelog_pages);
+                        if changelog_cfg.feeds() {
+                            files.extend(feed::build(&context, changelog_cfg, config)?);
+                        }
                     }
                     if let Some(funding_cfg) = &config.funding {
-                        let funding = Funding::new(funding_cfg, &config.styles)?;
-                        let body = funding::page(config, &funding)?;
-                        let page = Page::new_from_contents(
-                            body,
-                            "funding.html",
-                            &layout_template,
-                            config,
-                        );
+                        let page = Self::build_funding_page(funding_cfg, &layout_template, config, graph)?;
                         pages.push(page);
                     }
                 },
@@ -81,23 +143,167 @@ impl Site {
             }
         }
 
-        pages.push(index.unwrap_or(Page::index(&layout_template, config)?));
-        Ok(Site { pages })
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let inputs = Self::markdown_inputs(&layout_template, config)?
+                    .with_file(&config.readme_path)
+                    .with(&config.name)
+                    .with(&config.description);
+                Self::render_page(graph, config, "index.html", inputs, || {
+                    Page::index(&layout_template, config)
+                })?
+            }
+        };
+        pages.push(index);
+        // Hosts only look for a 404 page at the root, so packages in a workspace don't get one
+        if is_root {
+            pages.push(Page::not_found(&layout_template, config));
+        }
+        Ok(SiteContents {
+            pages,
+            assets,
+            files,
+            members: vec![],
+        })
+    }
+
+    /// Build the site for a whole workspace, which nests a site for each of its packages
+    fn build_workspace(config: &Config, graph: &mut BuildGraph) -> Result<SiteContents> {
+        let mut members = vec![];
+        for member in &config.members {
+            let msg = format!("Building pages for {}...", member.name);
+            Message::new(MessageType::Info, &msg).print();
+            tracing::info!("{}", &msg);
+            let member_config = config.for_member(member);
+            Self::create_dist_dir(&member_config.dist_dir)?;
+            let site = Self::build_single(&member_config, graph, false)?;
+            members.push((member_config, site));
+        }
+
+        // The releases all belong to the packages, so the top level doesn't link to them
+        let mut root_config = config.clone();
+        root_config.changelog = None;
+        root_config.artifacts = ArtifactsConfig::default();
+
+        let mut pages = vec![];
+        let layout_template = Layout::new(&root_config)?;
+        let assets = layout_template.assets().to_vec();
+        if let Some(files) = &root_config.additional_pages {
+            let mut additional_pages =
+                Self::build_additional_pages(files, &layout_template, &root_config, graph)?;
+            pages.append(&mut additional_pages);
+        }
+        if let Some(funding_cfg) = &root_config.funding {
+            let page =
+                Self::build_funding_page(funding_cfg, &layout_template, &root_config, graph)?;
+            pages.push(page);
+        }
+        let inputs = Self::markdown_inputs(&layout_template, &root_config)?
+            .with_file(&root_config.readme_path)
+            .with_json(&root_config.members)?;
+        let index = Self::render_page(graph, &root_config, "index.html", inputs, || {
+            Page::workspace_index(&layout_template, &root_config)
+        })?;
+        pages.push(index);
+        pages.push(Page::not_found(&layout_template, &root_config));
+
+        Ok(SiteContents {
+            pages,
+            assets,
+            files: vec![],
+            members,
+        })
     }
 
     fn needs_context(config: &Config) -> bool {
-        config.artifacts.has_some() || config.changelog || config.funding.is_some()
+        config.artifacts.has_some() || config.changelog.is_some() || config.funding.is_some()
+    }
+
+    /// Render a page, unless the previous build already rendered it from the same inputs
+    fn render_page(
+        graph: &mut BuildGraph,
+        config: &Config,
+        filename: &str,
+        inputs: Inputs,
+        render: impl FnOnce() -> Result<Page>,
+    ) -> Result<Page> {
+        let path = Self::output_path(Utf8Path::new(&config.dist_dir), filename);
+        let inputs = inputs.with(filename).finish();
+        if let Some(page) = graph.fresh_page(&path, filename, &inputs)? {
+            return Ok(page);
+        }
+        let page = render()?;
+        graph.record_page(&path, &inputs, &page)?;
+        Ok(page)
+    }
+
+    /// What every page depends on: the layout it's rendered into, and the config for how
+    /// it's linked to and indexed
+    ///
+    /// (the layout already covers most of the config, like the theme and the header)
+    fn page_inputs(layout_template: &Layout, config: &Config) -> Result<Inputs> {
+        Inputs::default()
+            .with(layout_template.fingerprint())
+            .with_json(&(&config.path_prefix, config.search))
+    }
+
+    /// What a page rendered from markdown depends on, besides the markdown itself
+    fn markdown_inputs(layout_template: &Layout, config: &Config) -> Result<Inputs> {
+        Self::page_inputs(layout_template, config)?.with_json(&Self::markdown_config(config))
+    }
+
+    /// The config that decides how markdown is rendered, mostly by where its links go
+    fn markdown_config(config: &Config) -> impl Serialize + '_ {
+        // (sorted, so the hash doesn't change from build to build)
+        let additional_pages = config
+            .additional_pages
+            .as_ref()
+            .map(|pages| pages.values().collect::<BTreeSet<_>>());
+        (
+            &config.readme_path,
+            additional_pages,
+            &config.mdbook,
+            &config.repository,
+            config.releases.source,
+            config.styles.syntax_theme(),
+        )
+    }
+
+    /// What a page made from releases depends on, besides the releases themselves
+    fn release_inputs(layout_template: &Layout, config: &Config) -> Result<Inputs> {
+        Self::page_inputs(layout_template, config)?.with_json(&(
+            &config.artifacts,
+            &config.changelog,
+            &config.releases,
+            &config.repository,
+            config.styles.syntax_theme(),
+        ))
+    }
+
+    /// Add all of the release data to some inputs
+    fn context_inputs(inputs: Inputs, context: &Context) -> Result<Inputs> {
+        inputs.with_json(&(
+            &context.releases,
+            context.latest_release,
+            context.has_prereleases,
+        ))
     }
 
     fn build_additional_pages(
         files: &HashMap<String, String>,
         layout_template: &Layout,
         config: &Config,
+        graph: &mut BuildGraph,
     ) -> Result<Vec<Page>> {
         let mut pages = vec![];
         for file_path in files.values() {
             if page::source::is_markdown(file_path) {
-                let additional_page = Page::new_from_file(file_path, layout_template, config)?;
+                let inputs = Self::markdown_inputs(layout_template, config)?.with_file(file_path);
+                let filename = Page::filename(file_path);
+                let additional_page = Self::render_page(graph, config, &filename, inputs, || {
+                    Page::new_from_file(file_path, layout_template, config)
+                })?;
                 pages.push(additional_page)
             } else {
                 let msg = format!(
@@ -110,54 +316,155 @@ impl Site {
         Ok(pages)
     }
 
+    fn build_funding_page(
+        funding_cfg: &FundingConfig,
+        layout_template: &Layout,
+        config: &Config,
+        graph: &mut BuildGraph,
+    ) -> Result<Page> {
+        let mut inputs = Self::page_inputs(layout_template, config)?
+            .with_json(&(funding_cfg, config.styles.syntax_theme()))?;
+        for path in [&funding_cfg.yml_path, &funding_cfg.md_path]
+            .into_iter()
+            .flatten()
+        {
+            inputs = inputs.with_file(path);
+        }
+        Self::render_page(graph, config, "funding.html", inputs, || {
+            let funding = Funding::new(funding_cfg, &config.styles)?;
+            let body = funding::page(config, &funding)?;
+            Ok(Page::new_from_contents(
+                body,
+                "funding.html",
+                layout_template,
+                config,
+            ))
+        })
+    }
+
+    /// Builds an install page for each release that gets one (at `artifacts/<tag>/`)
+    fn build_versioned_artifacts_pages(
+        context: &mut Context,
+        layout_template: &Layout,
+        config: &Config,
+        cache: &HttpCache,
+        assets: &mut Vec<String>,
+        graph: &mut BuildGraph,
+    ) -> Result<Vec<Page>> {
+        let mut pages = vec![];
+        for idx in artifacts::versioned_releases(context, config) {
+            let release = &mut context.releases[idx];
+            let path = artifacts::release_path(release);
+            // The latest release's scripts have already been copied to the root of the site
+            if context.latest_release != Some(idx) {
+                release
+                    .artifacts
+                    .make_scripts_viewable_in(config, cache, &path)?;
+                assets.extend(Self::viewable_scripts(release));
+            }
+            let context = &*context;
+            // Each page links to the latest release, if it isn't for that one
+            let latest = context.latest().map(|latest| &latest.source.tag_name);
+            let inputs = Self::release_inputs(layout_template, config)?
+                .with_json(&(&context.releases[idx], latest))?;
+            let filename = format!("{path}index.html");
+            let page = Self::render_page(graph, config, &filename, inputs, || {
+                let body = artifacts::release_page(context, idx, config)?;
+                let mut page = Page::new_from_contents(body, &filename, layout_template, config);
+                page.last_modified = context.releases[idx].published_at();
+                Ok(page)
+            })?;
+            pages.push(page);
+        }
+        Ok(pages)
+    }
+
     fn build_changelog_pages(
         context: &Context,
         layout_template: &Layout,
         config: &Config,
+        graph: &mut BuildGraph,
     ) -> Result<Vec<Page>> {
         let mut pages = vec![];
-        let changelog_html = changelog::build(context, config)?;
-        let changelog_page =
-            Page::new_from_contents(changelog_html, "changelog.html", layout_template, config);
-        let changelog_releases = changelog::build_all(context, config)?;
+        let inputs = Self::context_inputs(Self::release_inputs(layout_template, config)?, context)?;
+        let changelog_page = Self::render_page(graph, config, "changelog.html", inputs, || {
+            let changelog_html = changelog::build(context, config)?;
+            let mut changelog_page =
+                Page::new_from_contents(changelog_html, "changelog.html", layout_template, config);
+            changelog_page.last_modified = context
+                .releases
+                .iter()
+                .filter_map(|release| release.published_at())
+                .max();
+            Ok(changelog_page)
+        })?;
         pages.push(changelog_page);
-        for (name, content) in changelog_releases {
-            let page = Page::new_from_contents(
-                content,
-                &format!("changelog/{}.html", name),
-                layout_template,
-                config,
-            );
+        let install_pages = changelog::install_pages(context, config);
+        for (idx, release) in context.releases.iter().enumerate() {
+            let has_install_page = install_pages.contains(&idx);
+            let inputs = Self::release_inputs(layout_template, config)?
+                .with_json(&(release, has_install_page))?;
+            let filename = format!("changelog/{}.html", release.source.tag_name);
+            let page = Self::render_page(graph, config, &filename, inputs, || {
+                let content = changelog::build_single_release(config, release, has_install_page)?;
+                let mut page = Page::new_from_contents(content, &filename, layout_template, config);
+                page.last_modified = release.published_at();
+                Ok(page)
+            })?;
             pages.push(page);
         }
         Ok(pages)
     }
 
-    pub fn copy_static(dist_dir: &Utf8Path, static_path: &str) -> Result<()> {
-        let mut options = fs_extra::dir::CopyOptions::new();
-        options.overwrite = true;
-        // We want to be able to rename dirs in the copy, this enables it
-        options.copy_inside = true;
-        fs_extra::copy_items(&[static_path], dist_dir, &options)?;
-
-        Ok(())
+    /// The copies of a release's scripts that were written to the dist dir
+    fn viewable_scripts(release: &Release) -> Vec<String> {
+        release
+            .artifacts
+            .files()
+            .filter_map(|file| file.view_path.clone())
+            .collect()
     }
 
+    /// Write the site to the dist dir
+    ///
+    /// Only files that changed since the last build are rewritten, and files from the last
+    /// build that aren't part of this one are deleted.
     pub fn write(self, config: &Config) -> Result<()> {
+        let mut graph = self.graph;
+        let index = Self::write_into(self.contents, &mut graph, config)?;
+        // Search engines only look for these at the root of the site
+        sitemap::write(&index.sitemap, config, &mut graph)?;
+        graph.finish()
+    }
+
+    /// Write the site into the build graph, returning what it added to the site's indexes
+    fn write_into(
+        contents: SiteContents,
+        graph: &mut BuildGraph,
+        config: &Config,
+    ) -> Result<SiteIndex> {
         let dist = Utf8PathBuf::from(&config.dist_dir);
-        for page in self.pages {
-            let filename_path = Utf8PathBuf::from(&page.filename);
-            // Prepare to write a "pretty link" for pages that aren't index.html already. This essentially means that we rewrite
-            // the page from "page.html" to "page/index.html", so that it can be loaded as "mysite.com/page" in the browser.
-            let full_path: Utf8PathBuf = if !filename_path.ends_with("index.html") {
-                // FIXME: Can we do anything BUT unwrap here? What's the smart way to deal with a missing filename path portion?
-                let file_stem = filename_path.file_stem().unwrap();
-                let parent = filename_path.parent().unwrap_or("".into());
-                dist.join(parent).join(file_stem).join("index.html")
-            } else {
-                dist.join(filename_path)
-            };
-            LocalAsset::write_new_all(&page.contents, full_path)?;
+        for asset in &contents.assets {
+            graph.keep(&dist.join(asset))?;
+        }
+        let mut index = SiteIndex::default();
+        let mut media = BTreeMap::new();
+        for page in &contents.pages {
+            index.search.extend(search::page_entry(page, config));
+            index.sitemap.extend(sitemap::page_entry(page, config));
+            for item in &page.media {
+                media.insert(&item.dest, &item.source);
+            }
+        }
+        for (dest, source) in media {
+            graph.write(&dist.join(dest), std::fs::read(source)?)?;
+        }
+        for page in contents.pages {
+            let full_path = Self::output_path(&dist, &page.filename);
+            graph.write(&full_path, &page.contents)?;
+        }
+        for (path, file_contents) in &contents.files {
+            graph.write(&dist.join(path), file_contents)?;
         }
         if let Some(book_cfg) = &config.mdbook {
             mdbook::build_mdbook(
@@ -165,25 +472,68 @@ impl Site {
                 book_cfg,
                 &config.styles.theme(),
                 &config.styles.syntax_theme(),
+                graph,
             )?;
+            let book_dist = dist.join("book");
+            index
+                .search
+                .extend(search::mdbook_entries(&book_dist, config)?);
+            index
+                .sitemap
+                .extend(sitemap::mdbook_entries(&book_dist, config));
         }
-        if Path::new(&config.static_dir).exists() {
-            Self::copy_static(&dist, &config.static_dir)?;
+        let static_dir = Utf8PathBuf::from(&config.static_dir);
+        if static_dir.exists() {
+            // The static dir keeps its name in the dist dir
+            let dest = dist.join(static_dir.file_name().unwrap_or(config.static_dir.as_str()));
+            graph.copy_dir(&static_dir, &dest)?;
         }
-        javascript::write_os_script(&dist)?;
+        javascript::write_os_script(&dist, graph)?;
 
         let additional_css = &config.styles.additional_css;
         if !additional_css.is_empty() {
-            css::write_additional(additional_css, &dist)?;
+            css::write_additional(additional_css, &dist, graph)?;
         }
 
-        Ok(())
+        // The workspace's search box (and sitemap) covers its packages' sites too
+        for (member_config, site) in contents.members {
+            let member_index = Self::write_into(site, graph, &member_config)?;
+            index.search.extend(member_index.search);
+            index.sitemap.extend(member_index.sitemap);
+        }
+        if config.search {
+            search::write_index(&index.search, &dist, graph)?;
+            javascript::write_search_script(&dist, graph)?;
+        }
+
+        Ok(index)
     }
 
+    /// Where a page gets written in the dist dir
+    fn output_path(dist: &Utf8Path, filename: &str) -> Utf8PathBuf {
+        let filename_path = Utf8PathBuf::from(filename);
+        // Prepare to write a "pretty link" for pages that aren't index.html already. This essentially means that we rewrite
+        // the page from "page.html" to "page/index.html", so that it can be loaded as "mysite.com/page" in the browser.
+        // (the 404 page has to stay where hosts look for it)
+        if !filename_path.ends_with("index.html") && filename != hosting::NOT_FOUND_PAGE {
+            // FIXME: Can we do anything BUT unwrap here? What's the smart way to deal with a missing filename path portion?
+            let file_stem = filename_path.file_stem().unwrap();
+            let parent = filename_path.parent().unwrap_or("".into());
+            dist.join(parent).join(file_stem).join("index.html")
+        } else {
+            dist.join(filename_path)
+        }
+    }
+
+    /// Delete everything in the dist dir, so the next build starts from scratch
     pub fn clean_dist_dir(dist_path: &str) -> Result<()> {
         if Path::new(dist_path).exists() {
             std::fs::remove_dir_all(dist_path)?;
         }
+        Self::create_dist_dir(dist_path)
+    }
+
+    fn create_dist_dir(dist_path: &str) -> Result<()> {
         match std::fs::create_dir_all(dist_path) {
             Ok(_) => Ok(()),
             Err(e) => Err(OrandaError::DistDirCreationError {
@@ -1,22 +1,34 @@
 use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 
 use crate::config::Config;
 use crate::data::Context;
 use crate::errors::*;
 use crate::site::artifacts;
+use crate::site::hosting::NOT_FOUND_PAGE;
 use crate::site::layout::{javascript, Layout};
-use crate::site::markdown::{self, SyntaxTheme};
+use crate::site::link;
+use crate::site::markdown::{self, LinkRewriter, LocalMedia};
+use crate::site::workspace;
 
 use axoasset::SourceFile;
 use axohtml::elements::div;
-use axohtml::{html, unsafe_text};
+use axohtml::{html, text, unsafe_text};
 
 pub mod source;
 
 #[derive(Debug)]
 pub struct Page {
     pub contents: String,
+    /// The page's own content, before it was put in the layout
+    pub body: String,
     pub filename: String,
+    /// When the page's content last changed, if we know
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Images and media from the project that the page shows, to copy into the dist dir
+    pub media: Vec<LocalMedia>,
 }
 
 impl Page {
@@ -26,33 +38,79 @@ impl Page {
         config: &Config,
     ) -> Result<Self> {
         let mut body = artifacts::header(context, config)?;
-        let readme =
-            Self::load_and_render_contents(&config.readme_path, &config.styles.syntax_theme())?;
+        let (readme, media) = Self::load_and_render_readme(config)?;
         body.push_str(&readme);
         let os_script = javascript::build_os_script(&config.path_prefix);
-        let contents = layout.render(body, Some(os_script));
+        let contents = layout.render(body.clone(), Some(os_script));
         Ok(Page {
             contents,
+            body,
             filename: "index.html".to_string(),
+            last_modified: Self::modified(&config.readme_path),
+            media,
         })
     }
 
     pub fn index(layout: &Layout, config: &Config) -> Result<Self> {
-        let body =
-            Self::load_and_render_contents(&config.readme_path, &config.styles.syntax_theme())?;
-        let contents = layout.render(body, None);
+        let (body, media) = Self::load_and_render_readme(config)?;
+        let contents = layout.render(body.clone(), None);
         Ok(Page {
             contents,
+            body,
             filename: "index.html".to_string(),
+            last_modified: Self::modified(&config.readme_path),
+            media,
         })
     }
 
+    /// The landing page of a workspace, listing its packages
+    pub fn workspace_index(layout: &Layout, config: &Config) -> Result<Self> {
+        // The workspace doesn't have to have a README of its own
+        let (mut body, media) = if Path::new(&config.readme_path).exists() {
+            Self::load_and_render_contents(&config.readme_path, config)?
+        } else {
+            (String::new(), vec![])
+        };
+        body.push_str(&workspace::members(config));
+        let contents = layout.render(body.clone(), None);
+        Ok(Page {
+            contents,
+            body,
+            filename: "index.html".to_string(),
+            last_modified: Self::modified(&config.readme_path),
+            media,
+        })
+    }
+
+    /// The page hosts show for paths that don't exist
+    pub fn not_found(layout: &Layout, config: &Config) -> Self {
+        let home = link::generate(&config.path_prefix, "");
+        let body: Box<div<String>> = html!(
+            <div class="not-found">
+                <h1>{text!("404")}</h1>
+                <p>{text!("There's nothing here, sorry!")}</p>
+                <a class="button primary" href=home>{text!("Go home")}</a>
+            </div>
+        );
+        let body = body.to_string();
+        Page {
+            contents: layout.render(body.clone(), None),
+            body,
+            filename: NOT_FOUND_PAGE.to_string(),
+            last_modified: None,
+            media: vec![],
+        }
+    }
+
     pub fn new_from_file(source: &str, layout: &Layout, config: &Config) -> Result<Self> {
-        let body = Self::load_and_render_contents(source, &config.styles.syntax_theme())?;
-        let contents = layout.render(body, None);
+        let (body, media) = Self::load_and_render_contents(source, config)?;
+        let contents = layout.render(body.clone(), None);
         Ok(Page {
             contents,
+            body,
             filename: Self::filename(source),
+            last_modified: Self::modified(source),
+            media,
         })
     }
 
@@ -63,24 +121,56 @@ impl Page {
         config: &Config,
     ) -> Self {
         let os_script = javascript::build_os_script(&config.path_prefix);
-        let contents = layout.render(body, Some(os_script));
+        let contents = layout.render(body.clone(), Some(os_script));
         Page {
             contents,
+            body,
             filename: filename.to_string(),
+            last_modified: None,
+            media: vec![],
+        }
+    }
+
+    /// Render the project's README
+    ///
+    /// Packages in a workspace don't have to have one, in which case they get their
+    /// description instead.
+    fn load_and_render_readme(config: &Config) -> Result<(String, Vec<LocalMedia>)> {
+        if !config.readme_path.is_empty() {
+            return Self::load_and_render_contents(&config.readme_path, config);
         }
+        let description =
+            (!config.description.is_empty()).then(|| html!(<p>{text!(&config.description)}</p>));
+        let html: Box<div<String>> = html!(
+            <div class="rendered-markdown">
+                <h1>{text!(&config.name)}</h1>
+                {description}
+            </div>
+        );
+        Ok((html.to_string(), vec![]))
     }
 
-    fn load_and_render_contents(source: &str, syntax_theme: &SyntaxTheme) -> Result<String> {
+    /// Render a markdown file, along with the media from the project that it shows
+    fn load_and_render_contents(
+        source: &str,
+        config: &Config,
+    ) -> Result<(String, Vec<LocalMedia>)> {
+        let links = Arc::new(LinkRewriter::new(config, source));
         let source = SourceFile::load_local(source)?;
         let contents = source.contents();
-        markdown::to_html(contents, syntax_theme).map(|html| {
-            let html: Box<div<String>> = html!(
-                <div class="rendered-markdown">
-                    {unsafe_text!(html)}
-                </div>
-            );
-            html.to_string()
-        })
+        let html = markdown::to_html_with_links(contents, &config.styles.syntax_theme(), &links)?;
+        let html: Box<div<String>> = html!(
+            <div class="rendered-markdown">
+                {unsafe_text!(html)}
+            </div>
+        );
+        Ok((html.to_string(), links.take_media()))
+    }
+
+    /// When a source file was last modified
+    fn modified(source: &str) -> Option<DateTime<Utc>> {
+        let modified = std::fs::metadata(source).ok()?.modified().ok()?;
+        Some(modified.into())
     }
 
     pub fn filename(source: &str) -> String {
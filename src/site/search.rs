@@ -0,0 +1,164 @@
+//! Building an index of everything on the site, for the search box in the header
+//!
+//! The index is a plain list of pages with their text, which the search script fetches the
+//! first time someone uses the search box. Sections of the mdbook (if there is one) are added
+//! from mdbook's own search index, so one box searches the whole site.
+
+use camino::Utf8Path;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::errors::*;
+use crate::site::hosting::NOT_FOUND_PAGE;
+use crate::site::incremental::BuildGraph;
+use crate::site::link;
+use crate::site::page::Page;
+
+/// The name of the search index, at the root of each site
+pub const SEARCH_INDEX_NAME: &str = "search-index.json";
+/// The name of the search index mdbook writes
+const MDBOOK_SEARCH_INDEX_NAME: &str = "searchindex.json";
+
+/// Something the search box can find
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchEntry {
+    pub title: String,
+    /// Where the thing lives, including the `path_prefix`
+    pub url: String,
+    /// All the text in it, without any markup
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchIndex<'a> {
+    entries: &'a [SearchEntry],
+}
+
+/// Make an entry for a page, if it's worth searching
+pub fn page_entry(page: &Page, config: &Config) -> Option<SearchEntry> {
+    // The install pages of old releases would just drown out the latest one
+    if page.filename.starts_with("artifacts/") || page.filename == NOT_FOUND_PAGE {
+        return None;
+    }
+    let text = html_to_text(&page.body);
+    if text.is_empty() {
+        return None;
+    }
+    let title = first_heading(&page.body).unwrap_or_else(|| {
+        if page.filename == "index.html" {
+            config.name.clone()
+        } else {
+            let stem = page.filename.trim_end_matches(".html");
+            stem.rsplit('/').next().unwrap_or(stem).to_owned()
+        }
+    });
+    Some(SearchEntry {
+        title,
+        url: link::generate(&config.path_prefix, &link::pretty(&page.filename)),
+        text,
+    })
+}
+
+/// Read the sections of an mdbook out of the search index mdbook built for it
+///
+/// If the book was built without search, there's nothing to add.
+pub fn mdbook_entries(book_dist: &Utf8Path, config: &Config) -> Result<Vec<SearchEntry>> {
+    let index_path = book_dist.join(MDBOOK_SEARCH_INDEX_NAME);
+    if !index_path.exists() {
+        return Ok(vec![]);
+    }
+    let index: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(index_path)?)?;
+    let Some(doc_urls) = index["doc_urls"].as_array() else {
+        return Ok(vec![]);
+    };
+    let Some(docs) = index["index"]["documentStore"]["docs"].as_object() else {
+        return Ok(vec![]);
+    };
+
+    let mut entries = vec![];
+    for (id, doc) in docs {
+        let url = id
+            .parse::<usize>()
+            .ok()
+            .and_then(|id| doc_urls.get(id))
+            .and_then(|url| url.as_str());
+        let Some(url) = url else {
+            continue;
+        };
+        let title = doc["breadcrumbs"]
+            .as_str()
+            .or_else(|| doc["title"].as_str())
+            .unwrap_or_default();
+        let text = doc["body"].as_str().unwrap_or_default();
+        entries.push(SearchEntry {
+            title: title.to_owned(),
+            url: link::generate(&config.path_prefix, &format!("book/{url}")),
+            text: collapse_whitespace(text),
+        });
+    }
+    // mdbook's ids are strings, so put the sections back in book order
+    entries.sort_by_key(|entry| {
+        doc_urls
+            .iter()
+            .position(|url| url.as_str().map(|url| entry.url.ends_with(url)) == Some(true))
+    });
+    Ok(entries)
+}
+
+/// Write the search index to the dist dir
+pub fn write_index(
+    entries: &[SearchEntry],
+    dist_dir: &Utf8Path,
+    graph: &mut BuildGraph,
+) -> Result<()> {
+    let index = serde_json::to_string(&SearchIndex { entries })?;
+    graph.write(&dist_dir.join(SEARCH_INDEX_NAME), index)?;
+    Ok(())
+}
+
+/// Get the text of some HTML, without the tags
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        // Tags are replaced with a space, so `<td>a</td><td>b</td>` doesn't become `ab`
+        text.push(' ');
+        rest = &rest[start..];
+        // Scripts and styles aren't text anyone reads
+        for tag in ["script", "style"] {
+            if rest[1..].starts_with(tag) {
+                let end_tag = format!("</{tag}>");
+                rest = rest.find(&end_tag).map_or("", |end| &rest[end..]);
+            }
+        }
+        rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+    }
+    text.push_str(rest);
+    collapse_whitespace(&decode_entities(&text))
+}
+
+/// Get the text of the first `<h1>` (or `<h2>`, if there isn't one) in some HTML
+fn first_heading(html: &str) -> Option<String> {
+    ["h1", "h2"].iter().find_map(|tag| {
+        let start = html.find(&format!("<{tag}"))?;
+        let end = html[start..].find(&format!("</{tag}>"))? + start;
+        let heading = html_to_text(&html[start..end]);
+        (!heading.is_empty()).then_some(heading)
+    })
+}
+
+/// Decode the entities we (and markdown) use in HTML
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,163 @@
+//! Telling search engines what's on the site, with a `sitemap.xml` and a `robots.txt`
+//!
+//! Both need full URLs, so unless they're asked for, we only build them if we know the
+//! site's `homepage`. Search engines only look for a `robots.txt` at the root of a domain, so
+//! we never build one for a site under a `path_prefix`.
+
+use camino::Utf8Path;
+use chrono::{DateTime, SecondsFormat, Utc};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::errors::*;
+use crate::message::{Message, MessageType};
+use crate::site::feed::escape_xml;
+use crate::site::hosting::NOT_FOUND_PAGE;
+use crate::site::incremental::BuildGraph;
+use crate::site::link;
+use crate::site::page::Page;
+
+/// The name of the sitemap, at the root of the site
+pub const SITEMAP_NAME: &str = "sitemap.xml";
+/// The name of the robots.txt, at the root of the site
+pub const ROBOTS_NAME: &str = "robots.txt";
+/// Pages mdbook builds that aren't worth a search engine's time
+const MDBOOK_SKIPPED_PAGES: &[&str] = &["404.html", "print.html", "toc.html"];
+
+/// A page on the site, for the sitemap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapEntry {
+    pub url: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Make a sitemap entry for a page, unless it's the 404 page
+pub fn page_entry(page: &Page, config: &Config) -> Option<SitemapEntry> {
+    if page.filename == NOT_FOUND_PAGE {
+        return None;
+    }
+    Some(SitemapEntry {
+        url: link::absolute(config, &link::pretty(&page.filename)),
+        last_modified: page.last_modified,
+    })
+}
+
+/// Make sitemap entries for the pages of the mdbook built into `book_dist`
+pub fn mdbook_entries(book_dist: &Utf8Path, config: &Config) -> Vec<SitemapEntry> {
+    let mut entries = vec![];
+    for entry in WalkDir::new(book_dist).sort_by_file_name() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Ok(path) = entry.path().strip_prefix(book_dist) else {
+            continue;
+        };
+        let Some(path) = path.to_str() else {
+            continue;
+        };
+        let path = path.replace('\\', "/");
+        if !path.ends_with(".html") || MDBOOK_SKIPPED_PAGES.contains(&path.as_str()) {
+            continue;
+        }
+        // mdbook pages don't get pretty links, but `index.html` is still served as the directory
+        let path = path.strip_suffix("index.html").unwrap_or(&path);
+        let last_modified = entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .map(DateTime::<Utc>::from);
+        entries.push(SitemapEntry {
+            url: link::absolute(config, &format!("book/{path}")),
+            last_modified,
+        });
+    }
+    entries
+}
+
+/// Build the contents of a `sitemap.xml`
+pub fn build(entries: &[SitemapEntry]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for entry in entries {
+        xml.push_str("<url>");
+        xml.push_str(&format!("<loc>{}</loc>", escape_xml(&entry.url)));
+        if let Some(last_modified) = entry.last_modified {
+            let last_modified = last_modified.to_rfc3339_opts(SecondsFormat::Secs, true);
+            xml.push_str(&format!("<lastmod>{last_modified}</lastmod>"));
+        }
+        xml.push_str("</url>");
+    }
+    xml.push_str("</urlset>");
+    xml
+}
+
+/// Build the contents of a `robots.txt`
+pub fn build_robots(config: &Config) -> String {
+    let mut robots = String::from("User-agent: *\n");
+    let disallow = config
+        .robots
+        .as_ref()
+        .map(|robots| robots.disallow())
+        .unwrap_or_default();
+    if disallow.is_empty() {
+        robots.push_str("Disallow:\n");
+    }
+    for path in disallow {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        robots.push_str(&format!("Disallow: {path}\n"));
+    }
+    if config.sitemap {
+        robots.push_str(&format!(
+            "\nSitemap: {}\n",
+            link::absolute(config, SITEMAP_NAME)
+        ));
+    }
+    robots
+}
+
+/// Write the `sitemap.xml` and `robots.txt` (whichever are enabled) to the dist dir
+///
+/// If one was asked for but we can't build it (because there's no `homepage`, or the
+/// `robots.txt` would end up under the `path_prefix`), we warn and skip it instead.
+pub fn write(entries: &[SitemapEntry], config: &Config, graph: &mut BuildGraph) -> Result<()> {
+    let mut robots = config.robots.is_some();
+    if robots && config.path_prefix.is_some() {
+        let msg = format!("Skipping {ROBOTS_NAME}, because search engines only look for it at the root of a domain, and your site is under a `path_prefix`. Set `robots` to false in your oranda.json to silence this warning.");
+        Message::new(MessageType::Warning, &msg).print();
+        tracing::warn!("{}", &msg);
+        robots = false;
+    }
+
+    let mut files = vec![];
+    let mut config_keys = vec![];
+    if config.sitemap {
+        files.push(SITEMAP_NAME);
+        config_keys.push("sitemap");
+    }
+    if robots {
+        files.push(ROBOTS_NAME);
+        config_keys.push("robots");
+    }
+    if files.is_empty() {
+        return Ok(());
+    }
+    if config.homepage.is_none() {
+        let msg = format!(
+            "Skipping {files}, because we don't know where your site is hosted. Search engines need full URLs, so set `homepage` in your oranda.json (or your project manifest) to your site's address, or set `{config_keys}` to false in your oranda.json.",
+            files = files.join(" and "),
+            config_keys = config_keys.join("` and `"),
+        );
+        Message::new(MessageType::Warning, &msg).print();
+        tracing::warn!("{}", &msg);
+        return Ok(());
+    }
+
+    let dist = Utf8Path::new(&config.dist_dir);
+    if config.sitemap {
+        graph.write(&dist.join(SITEMAP_NAME), build(entries))?;
+    }
+    if robots {
+        graph.write(&dist.join(ROBOTS_NAME), build_robots(config))?;
+    }
+    Ok(())
+}
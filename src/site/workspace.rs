@@ -0,0 +1,40 @@
+use axohtml::elements::li;
+use axohtml::{html, text};
+
+use crate::config::Config;
+use crate::site::link;
+
+/// Generate the list of packages for the landing page of a workspace
+pub fn members(config: &Config) -> String {
+    let items: Vec<Box<li<String>>> = config
+        .members
+        .iter()
+        .map(|member| {
+            let href = link::generate(&config.path_prefix, &format!("{}/", member.name));
+            let version = member
+                .version
+                .as_ref()
+                .map(|version| html!(<span class="workspace-member-version">{text!(format!("v{version}"))}</span>));
+            let description = if member.description.is_empty() {
+                None
+            } else {
+                Some(html!(<p>{text!(&member.description)}</p>))
+            };
+            html!(
+                <li class="workspace-member">
+                    <h3><a href=href>{text!(&member.name)}</a> {version}</h3>
+                    {description}
+                </li>
+            )
+        })
+        .collect();
+    html!(
+        <div class="workspace-members">
+            <h2>{text!("Packages")}</h2>
+            <ul>
+                {items}
+            </ul>
+        </div>
+    )
+    .to_string()
+}
@@ -1,2 +1,3 @@
 pub mod oranda_config;
 pub mod page;
+pub mod snapshot;
@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use indexmap::IndexMap;
 
 use oranda::config::oranda_config::{
-    artifacts::PackageManagersConfig, AnalyticsConfig, ArtifactsConfig, StyleConfig,
+    artifacts::PackageManagersConfig, AnalyticsConfig, ArtifactsConfig, ChangelogConfig,
+    ReleasesConfig, StyleConfig,
 };
 use oranda::config::Config;
 use oranda::site::javascript::analytics::Plausible;
@@ -64,6 +65,7 @@ pub fn path_prefix(temp_dir: String) -> Config {
         artifacts: ArtifactsConfig {
             cargo_dist: Some(true),
             package_managers: None,
+            ..Default::default()
         },
         styles: StyleConfig {
             additional_css: vec![String::from(
@@ -100,6 +102,7 @@ pub fn path_prefix_with_package_managers(temp_dir: String) -> Config {
         artifacts: ArtifactsConfig {
             cargo_dist: Some(false),
             package_managers: Some(build_package_managers()),
+            ..Default::default()
         },
         styles: StyleConfig {
             additional_css: vec![String::from(
@@ -119,6 +122,7 @@ pub fn cargo_dist(temp_dir: String) -> Config {
         artifacts: ArtifactsConfig {
             cargo_dist: Some(true),
             package_managers: None,
+            ..Default::default()
         },
         repository: Some(String::from("https://github.com/axodotdev/oranda")),
         ..Default::default()
@@ -131,6 +135,7 @@ pub fn package_managers(temp_dir: String) -> Config {
         artifacts: ArtifactsConfig {
             cargo_dist: Some(false),
             package_managers: Some(build_package_managers()),
+            ..Default::default()
         },
         repository: Some(String::from("https://github.com/axodotdev/oranda")),
         ..Default::default()
@@ -141,7 +146,7 @@ pub fn changelog(temp_dir: String) -> Config {
     Config {
         dist_dir: temp_dir,
         repository: Some(String::from("https://github.com/axodotdev/oranda")),
-        changelog: true,
+        changelog: Some(ChangelogConfig::default()),
         ..Default::default()
     }
 }
@@ -157,3 +162,17 @@ pub fn analytics_plausible(temp_dir: String) -> Config {
         ..Default::default()
     }
 }
+
+pub fn snapshot(temp_dir: String, snapshot_dir: String) -> Config {
+    Config {
+        dist_dir: temp_dir,
+        repository: Some(String::from("https://github.com/axodotdev/axolotlsay")),
+        changelog: Some(ChangelogConfig::default()),
+        releases: ReleasesConfig {
+            snapshot: Some(snapshot_dir),
+            ..Default::default()
+        },
+        offline: true,
+        ..Default::default()
+    }
+}
@@ -1,5 +1,5 @@
 use oranda::config::Config;
-use oranda::data::Context;
+use oranda::data::{cache::HttpCache, Context};
 use oranda::site::{self, artifacts, changelog, layout::Layout, markdown, page::Page};
 
 fn readme() -> &'static str {
@@ -46,9 +46,12 @@ pub fn index(config: &Config, layout: &Layout) -> Page {
 pub fn index_with_artifacts(config: &Config, layout: &Layout) -> Page {
     reset(&config.dist_dir);
     let repo_url = config.repository.as_ref().unwrap();
-    let mut context = Context::new(repo_url, &config.artifacts).unwrap();
+    let mut context = Context::new(repo_url, config).unwrap();
     if let Some(latest) = context.latest_mut() {
-        latest.artifacts.make_scripts_viewable(config).unwrap();
+        latest
+            .artifacts
+            .make_scripts_viewable(config, &HttpCache::new(config))
+            .unwrap();
     }
     Page::index_with_artifacts(&context, layout, config).unwrap()
 }
@@ -63,7 +66,7 @@ pub fn index_with_warning(config: &Config, layout: &Layout) -> Page {
 pub fn artifacts(config: &Config, layout: &Layout) -> Page {
     reset(&config.dist_dir);
     let repo_url = config.repository.as_ref().unwrap();
-    let context = Context::new(repo_url, &config.artifacts).unwrap();
+    let context = Context::new(repo_url, config).unwrap();
     let artifacts_content = artifacts::page(&context, config).unwrap();
     Page::new_from_contents(artifacts_content, "artifacts.html", layout, config)
 }
@@ -71,7 +74,7 @@ pub fn artifacts(config: &Config, layout: &Layout) -> Page {
 pub fn changelog(config: &Config, layout: &Layout) -> Page {
     reset(&config.dist_dir);
     let repo_url = config.repository.as_ref().unwrap();
-    let context = Context::new(repo_url, &config.artifacts).unwrap();
+    let context = Context::new(repo_url, config).unwrap();
     let changelog_content = changelog::build(&context, config).unwrap();
     Page::new_from_contents(changelog_content, "changelog.html", layout, config)
 }
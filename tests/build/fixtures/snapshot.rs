@@ -0,0 +1,175 @@
+pub fn releases_json() -> &'static str {
+    r#"
+[
+  {
+    "url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/2",
+    "assets_url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/2/assets",
+    "html_url": "https://github.com/axodotdev/axolotlsay/releases/tag/v0.2.0",
+    "id": 2,
+    "tag_name": "v0.2.0",
+    "target_commitish": "main",
+    "name": "v0.2.0",
+    "draft": false,
+    "prerelease": false,
+    "created_at": "2023-06-01T00:00:00Z",
+    "published_at": "2023-06-01T00:00:00Z",
+    "assets": [
+      {
+        "url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/assets/20",
+        "id": 20,
+        "node_id": "asset20",
+        "name": "axolotlsay-installer.sh",
+        "label": null,
+        "content_type": "application/x-sh",
+        "state": "uploaded",
+        "size": 32,
+        "download_count": 0,
+        "created_at": "2023-06-01T00:00:00Z",
+        "updated_at": "2023-06-01T00:00:00Z",
+        "browser_download_url": "https://github.com/axodotdev/axolotlsay/releases/download/v0.2.0/axolotlsay-installer.sh"
+      }
+    ],
+    "tarball_url": "https://api.github.com/repos/axodotdev/axolotlsay/tarball/v0.2.0",
+    "zipball_url": "https://api.github.com/repos/axodotdev/axolotlsay/zipball/v0.2.0",
+    "body": "Now with more axolotls."
+  },
+  {
+    "url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/1",
+    "assets_url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/1/assets",
+    "html_url": "https://github.com/axodotdev/axolotlsay/releases/tag/v0.1.0",
+    "id": 1,
+    "tag_name": "v0.1.0",
+    "target_commitish": "main",
+    "name": "v0.1.0",
+    "draft": false,
+    "prerelease": false,
+    "created_at": "2023-05-01T00:00:00Z",
+    "published_at": "2023-05-01T00:00:00Z",
+    "assets": [
+      {
+        "url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/assets/10",
+        "id": 10,
+        "node_id": "asset10",
+        "name": "axolotlsay-installer.sh",
+        "label": null,
+        "content_type": "application/x-sh",
+        "state": "uploaded",
+        "size": 32,
+        "download_count": 0,
+        "created_at": "2023-05-01T00:00:00Z",
+        "updated_at": "2023-05-01T00:00:00Z",
+        "browser_download_url": "https://github.com/axodotdev/axolotlsay/releases/download/v0.1.0/axolotlsay-installer.sh"
+      }
+    ],
+    "tarball_url": "https://api.github.com/repos/axodotdev/axolotlsay/tarball/v0.1.0",
+    "zipball_url": "https://api.github.com/repos/axodotdev/axolotlsay/zipball/v0.1.0",
+    "body": "Initial release."
+  }
+]
+"#
+}
+
+pub fn installer_sh() -> &'static str {
+    "#!/bin/sh\necho 'installing axolotlsay'\n"
+}
+
+pub const LINUX_ARCHIVE: &str = "axolotlsay-x86_64-unknown-linux-gnu.tar.gz";
+pub const WINDOWS_ARCHIVE: &str = "axolotlsay-x86_64-pc-windows-msvc.zip";
+pub const MAC_ARCHIVE: &str = "axolotlsay-aarch64-apple-darwin.tar.gz";
+pub const LINUX_SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+pub const WINDOWS_SHA256: &str = "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752";
+
+/// A release with archives and checksums in a few different formats, and the
+/// contents of its assets
+pub fn checksummed_release() -> (String, Vec<(&'static str, String)>) {
+    let names = [
+        LINUX_ARCHIVE,
+        WINDOWS_ARCHIVE,
+        MAC_ARCHIVE,
+        "axolotlsay-x86_64-unknown-linux-gnu.tar.gz.sha256",
+        "axolotlsay-aarch64-apple-darwin.tar.gz.sha256",
+        "SHA256SUMS",
+    ];
+    let releases = releases_with_assets("v1.0.0", &names);
+    let files = vec![
+        // Just the hash
+        (
+            "axolotlsay-x86_64-unknown-linux-gnu.tar.gz.sha256",
+            format!("{LINUX_SHA256}\n"),
+        ),
+        // Not a checksum at all
+        (
+            "axolotlsay-aarch64-apple-darwin.tar.gz.sha256",
+            "<html>Not Found</html>\n".to_owned(),
+        ),
+        // Everything, in the format `sha256sum --binary` writes
+        (
+            "SHA256SUMS",
+            format!("{LINUX_SHA256} *{LINUX_ARCHIVE}\n{WINDOWS_SHA256} *{WINDOWS_ARCHIVE}\n"),
+        ),
+    ];
+    (releases, files)
+}
+
+/// A release with archives that have signatures, and debug symbols
+pub fn signed_release() -> String {
+    releases_with_assets(
+        "v1.0.0",
+        &[
+            LINUX_ARCHIVE,
+            WINDOWS_ARCHIVE,
+            MAC_ARCHIVE,
+            "axolotlsay-x86_64-unknown-linux-gnu.tar.gz.asc",
+            "axolotlsay-x86_64-pc-windows-msvc.zip.minisig",
+            "axolotlsay-aarch64-apple-darwin.tar.gz.sig",
+            "axolotlsay-aarch64-apple-darwin.tar.gz.pem",
+            "axolotlsay-x86_64-unknown-linux-gnu.tar.gz.sigstore.json",
+            "multiple.intoto.jsonl",
+            "axolotlsay-x86_64-pc-windows-msvc.pdb.zip",
+            "axolotlsay.deb",
+            "axolotlsay.deb.sig",
+        ],
+    )
+}
+
+/// A release in the format of the GitHub Releases API, with assets with these names
+fn releases_with_assets(tag: &str, names: &[&str]) -> String {
+    let assets = names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| {
+            serde_json::json!({
+                "url": format!("https://api.github.com/repos/axodotdev/axolotlsay/releases/assets/{id}"),
+                "id": id,
+                "node_id": format!("asset{id}"),
+                "name": name,
+                "label": null,
+                "content_type": "application/octet-stream",
+                "state": "uploaded",
+                "size": 32,
+                "download_count": 0,
+                "created_at": "2023-06-01T00:00:00Z",
+                "updated_at": "2023-06-01T00:00:00Z",
+                "browser_download_url": format!("https://github.com/axodotdev/axolotlsay/releases/download/{tag}/{name}")
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!([{
+        "url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/3",
+        "assets_url": "https://api.github.com/repos/axodotdev/axolotlsay/releases/3/assets",
+        "html_url": format!("https://github.com/axodotdev/axolotlsay/releases/tag/{tag}"),
+        "id": 3,
+        "tag_name": tag,
+        "target_commitish": "main",
+        "name": tag,
+        "draft": false,
+        "prerelease": false,
+        "created_at": "2023-06-01T00:00:00Z",
+        "published_at": "2023-06-01T00:00:00Z",
+        "assets": assets,
+        "tarball_url": format!("https://api.github.com/repos/axodotdev/axolotlsay/tarball/{tag}"),
+        "zipball_url": format!("https://api.github.com/repos/axodotdev/axolotlsay/zipball/{tag}"),
+        "body": "Stable at last."
+    }])
+    .to_string()
+}
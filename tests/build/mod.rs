@@ -1,10 +1,26 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use assert_fs::fixture::{FileWriteStr, PathChild};
 use assert_fs::TempDir;
+use camino::Utf8Path;
+use oranda::config::project::ProjectConfig;
+use oranda::config::{ChangelogConfig, Config, MdBookConfig, RobotsConfig};
+use oranda::data::artifacts::{checksums, ChecksumAlgorithm, SignatureKind};
+use oranda::data::{cache::HttpCache, Context};
+use oranda::errors::OrandaError;
+use oranda::site::check::{self, LinkProblemKind};
+use oranda::site::hosting::{self, Resolution};
+use oranda::site::incremental::{self, BuildGraph, Inputs};
 use oranda::site::layout::css::LATEST_ORANDA_CSS;
 use oranda::site::layout::Layout;
+use oranda::site::markdown::{self, LinkRewriter, LocalMedia};
+use oranda::site::page::Page;
+use oranda::site::{artifacts, changelog, feed, search, sitemap, workspace};
 
 mod fixtures;
 use super::utils::tokio_utils::TEST_RUNTIME;
-use fixtures::{oranda_config, page};
+use fixtures::{oranda_config, page, snapshot};
 
 fn temp_build_dir() -> (TempDir, String) {
     let dir = assert_fs::TempDir::new().unwrap();
@@ -12,6 +28,21 @@ fn temp_build_dir() -> (TempDir, String) {
     (dir, dir_str)
 }
 
+fn temp_snapshot_dir() -> (TempDir, String) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("releases.json")
+        .write_str(snapshot::releases_json())
+        .unwrap();
+    dir.child("v0.2.0/axolotlsay-installer.sh")
+        .write_str(snapshot::installer_sh())
+        .unwrap();
+    dir.child("v0.1.0/axolotlsay-installer.sh")
+        .write_str(snapshot::installer_sh())
+        .unwrap();
+    let dir_str = dir.to_str().unwrap().to_string();
+    (dir, dir_str)
+}
+
 #[test]
 fn it_adds_additional_css() {
     let _guard = TEST_RUNTIME.enter();
@@ -272,3 +303,746 @@ fn it_inserts_plausible_tag() {
     dbg!(&page.contents);
     assert!(page.contents.contains(r#"<script defer="true" src="https://plausible.io/js/script.js" data-domain="opensource.axo.dev"></script>"#))
 }
+
+#[test]
+fn it_builds_without_oranda_css_offline() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let mut config = oranda_config::no_artifacts(temp_dir);
+    config.offline = true;
+    config.cache = None;
+    let layout = Layout::new(&config).unwrap();
+    assert!(layout.assets().is_empty());
+    let page = page::index(&config, &layout);
+    assert!(!page.contents.contains("oranda-v"));
+}
+
+#[test]
+fn it_reuses_oranda_css_from_an_earlier_build() {
+    let _guard = TEST_RUNTIME.enter();
+    let (dir, temp_dir) = temp_build_dir();
+    let filename = format!("oranda-v{LATEST_ORANDA_CSS}.css");
+    dir.child(&filename).write_str("/* css */").unwrap();
+    let mut config = oranda_config::no_artifacts(temp_dir);
+    config.offline = true;
+    config.cache = None;
+    let layout = Layout::new(&config).unwrap();
+    assert_eq!(layout.assets(), [filename]);
+}
+
+#[test]
+fn it_renders_changelog_from_snapshot() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let config = oranda_config::snapshot(temp_dir, snapshot_dir.clone());
+    let context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    assert_eq!(context.releases.len(), 2);
+    assert_eq!(context.latest().unwrap().source.tag_name, "v0.2.0");
+    let changelog = changelog::build(&context, &config).unwrap();
+    assert!(changelog.contains("Now with more axolotls."));
+    assert!(changelog.contains("Initial release."));
+}
+
+#[test]
+fn it_makes_snapshot_scripts_viewable_offline() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let config = oranda_config::snapshot(temp_dir.clone(), snapshot_dir.clone());
+    let mut context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    let latest = context.latest_mut().unwrap();
+    latest
+        .artifacts
+        .make_scripts_viewable(&config, &HttpCache::new(&config))
+        .unwrap();
+    let installer = latest.artifacts.files().next().unwrap();
+    assert_eq!(
+        installer.view_path.as_deref(),
+        Some("axolotlsay-installer.sh.txt")
+    );
+    let copied = Utf8Path::new(&temp_dir).join("axolotlsay-installer.sh.txt");
+    assert!(copied.exists());
+}
+
+#[test]
+fn it_leaves_unchanged_viewable_scripts_alone() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let config = oranda_config::snapshot(temp_dir.clone(), snapshot_dir);
+    let cache = HttpCache::new(&config);
+    let mut context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    let latest = context.latest_mut().unwrap();
+    latest
+        .artifacts
+        .make_scripts_viewable(&config, &cache)
+        .unwrap();
+    let copied = Utf8Path::new(&temp_dir).join("axolotlsay-installer.sh.txt");
+    let modified = std::fs::metadata(&copied).unwrap().modified().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    latest
+        .artifacts
+        .make_scripts_viewable(&config, &cache)
+        .unwrap();
+    assert_eq!(
+        std::fs::metadata(&copied).unwrap().modified().unwrap(),
+        modified
+    );
+}
+
+#[test]
+fn it_filters_snapshot_releases_by_tag_prefix() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let mut config = oranda_config::snapshot(temp_dir, snapshot_dir);
+    config.releases.tag_prefix = Some("v0.1".to_owned());
+    let context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    assert_eq!(context.releases.len(), 1);
+    assert_eq!(context.latest().unwrap().source.tag_name, "v0.1.0");
+}
+
+#[test]
+fn it_builds_install_pages_for_older_releases() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let mut config = oranda_config::snapshot(temp_dir.clone(), snapshot_dir);
+    let mut context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    assert_eq!(artifacts::versioned_releases(&context, &config), vec![0, 1]);
+
+    let older = &mut context.releases[1];
+    let path = artifacts::release_path(older);
+    assert_eq!(path, "artifacts/v0.1.0/");
+    older
+        .artifacts
+        .make_scripts_viewable_in(&config, &HttpCache::new(&config), &path)
+        .unwrap();
+    let installer = older.artifacts.files().next().unwrap();
+    assert_eq!(
+        installer.view_path.as_deref(),
+        Some("artifacts/v0.1.0/axolotlsay-installer.sh.txt")
+    );
+    assert!(Utf8Path::new(&temp_dir)
+        .join("artifacts/v0.1.0/axolotlsay-installer.sh.txt")
+        .exists());
+
+    let page = artifacts::release_page(&context, 1, &config).unwrap();
+    assert!(page.contains("Install v0.1.0"));
+    assert!(page.contains("Install the latest release (v0.2.0) instead."));
+    assert!(page.contains("/artifacts/v0.1.0/axolotlsay-installer.sh.txt"));
+    let latest_page = artifacts::page(&context, &config).unwrap();
+    assert!(latest_page.contains(r#"href="/artifacts/v0.1.0/""#));
+
+    let changelog_pages = changelog::build_all(&context, &config).unwrap();
+    assert!(changelog_pages[1]
+        .1
+        .contains(r#"href="/artifacts/v0.1.0/""#));
+
+    config.artifacts.versioned_pages = Some(1);
+    assert_eq!(artifacts::versioned_releases(&context, &config), vec![0]);
+    let changelog_pages = changelog::build_all(&context, &config).unwrap();
+    assert!(!changelog_pages[1].1.contains("/artifacts/v0.1.0/"));
+}
+
+#[test]
+fn it_shows_checksums_from_snapshots() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let snapshot_dir = assert_fs::TempDir::new().unwrap();
+    let (releases, files) = snapshot::checksummed_release();
+    snapshot_dir
+        .child("releases.json")
+        .write_str(&releases)
+        .unwrap();
+    for (name, contents) in files {
+        snapshot_dir
+            .child(format!("v1.0.0/{name}"))
+            .write_str(&contents)
+            .unwrap();
+    }
+    let config = oranda_config::snapshot(temp_dir, snapshot_dir.to_str().unwrap().to_owned());
+    let context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    let release = context.latest().unwrap();
+    let checksum = |name: &str| {
+        let file = release.artifacts.files().find(|f| f.name == name).unwrap();
+        file.checksum.as_ref().map(|c| c.value.as_str())
+    };
+    assert_eq!(
+        checksum(snapshot::LINUX_ARCHIVE),
+        Some(snapshot::LINUX_SHA256)
+    );
+    assert_eq!(
+        checksum(snapshot::WINDOWS_ARCHIVE),
+        Some(snapshot::WINDOWS_SHA256)
+    );
+    assert_eq!(checksum(snapshot::MAC_ARCHIVE), None);
+    // Checksum files shouldn't be mistaken for the things they're checksums of
+    assert_eq!(
+        release
+            .artifacts
+            .installers()
+            .filter(|(_, i)| i.label == "tarball")
+            .count(),
+        2
+    );
+
+    let page = artifacts::page(&context, &config).unwrap();
+    assert!(page.contains(&format!(r#"data-copy="{}""#, snapshot::LINUX_SHA256)));
+    assert!(page.contains(&format!(
+        "echo &quot;{}  {}&quot; | sha256sum -c -",
+        snapshot::LINUX_SHA256,
+        snapshot::LINUX_ARCHIVE
+    )));
+    assert!(page.contains("Get-FileHash"));
+    // The macOS archive has no valid checksum, so there's nothing to verify it with
+    assert!(!page.contains("shasum"));
+}
+
+#[test]
+fn it_shows_signatures_and_debug_symbols() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let snapshot_dir = assert_fs::TempDir::new().unwrap();
+    snapshot_dir
+        .child("releases.json")
+        .write_str(&snapshot::signed_release())
+        .unwrap();
+    let config = oranda_config::snapshot(temp_dir, snapshot_dir.to_str().unwrap().to_owned());
+    let context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    let release = context.latest().unwrap();
+    let signatures = |name: &str| {
+        let idx = release.artifacts.file_idx(&name.to_owned()).unwrap();
+        release
+            .artifacts
+            .signatures(idx)
+            .into_iter()
+            .map(|sig| sig.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        signatures(snapshot::LINUX_ARCHIVE),
+        vec![
+            SignatureKind::Gpg,
+            SignatureKind::Cosign,
+            SignatureKind::Provenance
+        ]
+    );
+    assert_eq!(
+        signatures(snapshot::WINDOWS_ARCHIVE),
+        vec![SignatureKind::Minisign, SignatureKind::Provenance]
+    );
+    assert_eq!(
+        signatures(snapshot::MAC_ARCHIVE),
+        vec![SignatureKind::Cosign, SignatureKind::Provenance]
+    );
+    // The signature of the .deb shouldn't be mistaken for another .deb
+    assert_eq!(
+        release
+            .artifacts
+            .installers()
+            .filter(|(_, i)| i.label == "deb")
+            .count(),
+        1
+    );
+    assert_eq!(release.artifacts.symbols().len(), 1);
+
+    let page = artifacts::page(&context, &config).unwrap();
+    assert!(page.contains(&format!(
+        "gpg --verify {0}.asc {0}",
+        snapshot::LINUX_ARCHIVE
+    )));
+    assert!(page.contains(&format!(
+        "minisign -Vm {0} -x {0}.minisig",
+        snapshot::WINDOWS_ARCHIVE
+    )));
+    assert!(page.contains(&format!(
+        "cosign verify-blob {0} --signature {0}.sig --certificate {0}.pem",
+        snapshot::MAC_ARCHIVE
+    )));
+    assert!(page.contains(
+        "--certificate-identity-regexp &#39;^https://github.com/axodotdev/axolotlsay/&#39;"
+    ));
+    assert!(page.contains("--source-uri github.com/axodotdev/axolotlsay"));
+    assert!(page.contains("<h3>Debug symbols</h3>"));
+    assert!(page.contains("axolotlsay-x86_64-pc-windows-msvc.pdb.zip"));
+}
+
+#[test]
+fn it_parses_checksum_formats() {
+    let hash = snapshot::LINUX_SHA256;
+    let gnu = checksums::parse_checksums(&format!("{hash}  dir/app.tar.gz\n")).unwrap();
+    assert_eq!(gnu[0].name.as_deref(), Some("app.tar.gz"));
+    let bsd = checksums::parse_checksums(&format!("SHA256 (app.tar.gz) = {hash}\n")).unwrap();
+    assert_eq!(bsd[0].name.as_deref(), Some("app.tar.gz"));
+    assert_eq!(bsd[0].checksum.value, hash);
+    let sha512 = checksums::parse_checksums(&hash.repeat(2)).unwrap();
+    assert_eq!(sha512[0].checksum.algorithm, ChecksumAlgorithm::Sha512);
+    assert!(checksums::parse_checksums("").is_err());
+    assert!(checksums::parse_checksums("abc123  app.tar.gz").is_err());
+}
+
+#[test]
+fn it_filters_snapshot_releases_by_tag_patterns() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let mut config = oranda_config::snapshot(temp_dir, snapshot_dir);
+    config.releases.include_tags = Some(vec!["v*".to_owned()]);
+    config.releases.exclude_tags = Some(vec!["v0.2.*".to_owned()]);
+    let context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+    assert_eq!(context.releases.len(), 1);
+    assert_eq!(context.latest().unwrap().source.tag_name, "v0.1.0");
+}
+
+#[test]
+fn it_rejects_invalid_tag_patterns() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let mut config = oranda_config::snapshot(temp_dir, snapshot_dir);
+    config.releases.exclude_tags = Some(vec!["v[0.2".to_owned()]);
+    let result = Context::new(config.repository.as_ref().unwrap(), &config);
+    assert!(matches!(result, Err(OrandaError::InvalidTagPattern { .. })));
+}
+
+#[test]
+fn it_only_rewrites_changed_outputs() {
+    let (_t, temp_dir) = temp_build_dir();
+    let dist = Utf8Path::new(&temp_dir);
+
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    assert!(graph.write(&dist.join("index.html"), "home").unwrap());
+    assert!(graph.write(&dist.join("old/index.html"), "old").unwrap());
+    graph.write(&dist.join("book/index.html"), "book").unwrap();
+    graph.record_step("mdbook", "inputs");
+    graph.finish().unwrap();
+
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    assert!(!graph.write(&dist.join("index.html"), "home").unwrap());
+    assert!(graph.step_is_fresh("mdbook", "inputs", &dist.join("book")));
+    graph.finish().unwrap();
+    assert!(dist.join("index.html").exists());
+    assert!(dist.join("book/index.html").exists());
+    assert!(!dist.join("old").exists());
+
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    assert!(graph.write(&dist.join("index.html"), "new home").unwrap());
+    assert!(!graph.step_is_fresh("mdbook", "new inputs", &dist.join("book")));
+    graph.finish().unwrap();
+    assert!(!dist.join("book").exists());
+}
+
+#[test]
+fn it_only_renders_pages_whose_inputs_changed() {
+    let (_t, temp_dir) = temp_build_dir();
+    let (media_dir, _) = temp_build_dir();
+    media_dir.child("logo.png").write_str("logo").unwrap();
+    let dist = Utf8Path::new(&temp_dir);
+    let path = dist.join("page/index.html");
+    let page = Page {
+        contents: "<html>page</html>".to_owned(),
+        body: "page".to_owned(),
+        filename: "page.html".to_owned(),
+        last_modified: None,
+        media: vec![LocalMedia {
+            source: Utf8Path::from_path(&media_dir.child("logo.png"))
+                .unwrap()
+                .to_owned(),
+            dest: "media/logo.png".to_owned(),
+        }],
+    };
+    let inputs = Inputs::default().with("page.md").finish();
+
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    assert!(graph
+        .fresh_page(&path, "page.html", &inputs)
+        .unwrap()
+        .is_none());
+    graph.record_page(&path, &inputs, &page).unwrap();
+    graph.write(&path, &page.contents).unwrap();
+    graph.finish().unwrap();
+
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    let new_inputs = Inputs::default().with("new page.md").finish();
+    assert!(graph
+        .fresh_page(&path, "page.html", &new_inputs)
+        .unwrap()
+        .is_none());
+    let fresh = graph
+        .fresh_page(&path, "page.html", &inputs)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fresh.contents, page.contents);
+    assert_eq!(fresh.body, page.body);
+    assert_eq!(fresh.media, page.media);
+    assert!(!graph.write(&path, &fresh.contents).unwrap());
+    graph.finish().unwrap();
+
+    // Pages show media by a hash of its contents, so they change with it
+    media_dir.child("logo.png").write_str("new logo").unwrap();
+    let mut graph = BuildGraph::load(&temp_dir).unwrap();
+    assert!(graph
+        .fresh_page(&path, "page.html", &inputs)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn it_resolves_paths_like_github_pages() {
+    let (dir, temp_dir) = temp_build_dir();
+    dir.child("index.html").write_str("home").unwrap();
+    dir.child("changelog/index.html")
+        .write_str("changelog")
+        .unwrap();
+    dir.child("plain.html").write_str("plain").unwrap();
+    let dist = Utf8Path::new(&temp_dir);
+    let resolve =
+        |prefix: Option<&str>, path| hosting::resolve(dist, &prefix.map(|p| p.to_owned()), path);
+
+    assert_eq!(
+        resolve(None, "/"),
+        Resolution::File(dist.join("index.html"))
+    );
+    assert_eq!(
+        resolve(None, "/changelog"),
+        Resolution::Redirect("/changelog/".to_owned())
+    );
+    assert_eq!(
+        resolve(None, "/changelog/"),
+        Resolution::File(dist.join("changelog/index.html"))
+    );
+    assert_eq!(
+        resolve(None, "/plain"),
+        Resolution::File(dist.join("plain.html"))
+    );
+    assert_eq!(resolve(None, "/../secret"), Resolution::NotFound(None));
+    assert_eq!(resolve(None, "/missing"), Resolution::NotFound(None));
+
+    dir.child("404.html").write_str("not found").unwrap();
+    let not_found = Resolution::NotFound(Some(dist.join("404.html")));
+    assert_eq!(resolve(None, "/missing/"), not_found);
+    assert_eq!(
+        resolve(Some("oranda"), "/oranda"),
+        Resolution::Redirect("/oranda/".to_owned())
+    );
+    assert_eq!(
+        resolve(Some("oranda"), "/oranda/changelog/"),
+        Resolution::File(dist.join("changelog/index.html"))
+    );
+    assert_eq!(resolve(Some("oranda"), "/oranda/missing"), not_found);
+    assert_eq!(
+        resolve(Some("oranda"), "/index.html"),
+        Resolution::NotFound(None)
+    );
+}
+
+#[test]
+fn it_lists_workspace_members() {
+    let mut config = oranda_config::snapshot(String::new(), String::new());
+    config.path_prefix = Some("oranda".to_owned());
+    config.members = vec![ProjectConfig {
+        name: "axolotlsay".to_owned(),
+        description: "the axolotlsay package".to_owned(),
+        homepage: None,
+        repository: None,
+        version: Some("0.1.0".to_owned()),
+        license: None,
+        readme_path: None,
+        cargo_dist: None,
+        manifest_path: "axolotlsay/Cargo.toml".into(),
+        embedded_configs: vec![],
+    }];
+    let html = workspace::members(&config);
+    assert!(html.contains(r#"href="/oranda/axolotlsay/""#));
+    assert!(html.contains("the axolotlsay package"));
+    assert!(html.contains("v0.1.0"));
+}
+
+#[test]
+fn it_describes_packages_without_a_readme() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let config = Config {
+        dist_dir: temp_dir,
+        name: "axolotlsay".to_owned(),
+        description: "the axolotlsay package".to_owned(),
+        readme_path: String::new(),
+        offline: true,
+        cache: None,
+        ..Default::default()
+    };
+    let layout = Layout::new(&config).unwrap();
+    let page = Page::index(&layout, &config).unwrap();
+    assert!(page.body.contains("<h1>axolotlsay</h1>"));
+    assert!(page.body.contains("the axolotlsay package"));
+}
+
+#[test]
+fn it_indexes_pages_and_the_book_for_search() {
+    let mut config = oranda_config::snapshot(String::new(), String::new());
+    config.path_prefix = Some("oranda".to_owned());
+    let page = Page {
+        contents: String::new(),
+        body: "<h1>Changelog &amp; news</h1><p>Fixed <code>lotl</code>\n  crashing</p><script>let x = 1 < 2;</script>".to_owned(),
+        filename: "changelog.html".to_owned(),
+        last_modified: None,
+        media: vec![],
+    };
+    let entry = search::page_entry(&page, &config).unwrap();
+    assert_eq!(entry.title, "Changelog & news");
+    assert_eq!(entry.url, "/oranda/changelog/");
+    assert_eq!(entry.text, "Changelog & news Fixed lotl crashing");
+
+    let old_release = Page {
+        filename: "artifacts/v0.1.0/index.html".to_owned(),
+        ..page
+    };
+    assert!(search::page_entry(&old_release, &config).is_none());
+
+    let (dir, temp_dir) = temp_build_dir();
+    dir.child("searchindex.json")
+        .write_str(
+            r#"{
+                "doc_urls": ["intro.html#intro", "usage.html#usage"],
+                "index": {"documentStore": {"docs": {
+                    "1": {"title": "Usage", "breadcrumbs": "Guide » Usage", "body": "run  it"},
+                    "0": {"title": "Intro", "body": "hello"}
+                }}}
+            }"#,
+        )
+        .unwrap();
+    let entries = search::mdbook_entries(Utf8Path::new(&temp_dir), &config).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].url, "/oranda/book/intro.html#intro");
+    assert_eq!(entries[0].title, "Intro");
+    assert_eq!(entries[1].title, "Guide » Usage");
+    assert_eq!(entries[1].text, "run it");
+}
+
+#[test]
+fn it_builds_release_feeds() {
+    let _guard = TEST_RUNTIME.enter();
+    let (_t, temp_dir) = temp_build_dir();
+    let (_s, snapshot_dir) = temp_snapshot_dir();
+    let mut config = oranda_config::snapshot(temp_dir, snapshot_dir);
+    config.homepage = Some("https://axodotdev.github.io/axolotlsay".to_owned());
+    config.path_prefix = Some("axolotlsay".to_owned());
+    let mut context = Context::new(config.repository.as_ref().unwrap(), &config).unwrap();
+
+    let feeds = feed::build(&context, &ChangelogConfig::default(), &config).unwrap();
+    let (name, rss) = &feeds[0];
+    assert_eq!(name, "changelog.rss");
+    assert!(rss.contains("<link>https://axodotdev.github.io/axolotlsay/changelog/v0.2.0/</link>"));
+    assert!(rss.contains("<pubDate>Thu, 01 Jun 2023 00:00:00 +0000</pubDate>"));
+    assert!(rss.contains("/changelog/v0.1.0/"));
+    let (name, atom) = &feeds[1];
+    assert_eq!(name, "changelog.atom");
+    assert!(atom.contains("<updated>2023-06-01T00:00:00+00:00</updated>"));
+    assert!(atom.contains(r#"<content type="html">"#));
+
+    context.releases[1].source.prerelease = true;
+    let changelog_config = ChangelogConfig {
+        feed_prereleases: Some(false),
+        ..Default::default()
+    };
+    let feeds = feed::build(&context, &changelog_config, &config).unwrap();
+    assert!(feeds[0].1.contains("/changelog/v0.2.0/"));
+    assert!(!feeds[0].1.contains("/changelog/v0.1.0/"));
+}
+
+#[test]
+fn it_builds_a_sitemap_and_robots_txt() {
+    let mut config = oranda_config::snapshot(String::new(), String::new());
+    config.homepage = Some("https://axodotdev.github.io/axolotlsay/".to_owned());
+    config.path_prefix = Some("axolotlsay".to_owned());
+    config.robots = Some(RobotsConfig {
+        disallow: Some(vec!["/changelog/".to_owned()]),
+    });
+    let page = |filename: &str| Page {
+        contents: String::new(),
+        body: String::new(),
+        filename: filename.to_owned(),
+        last_modified: Some("2023-06-01T12:00:00Z".parse().unwrap()),
+        media: vec![],
+    };
+
+    assert!(sitemap::page_entry(&page("404.html"), &config).is_none());
+    let entries = vec![
+        sitemap::page_entry(&page("index.html"), &config).unwrap(),
+        sitemap::page_entry(&page("changelog/v0.1.0.html"), &config).unwrap(),
+    ];
+    let xml = sitemap::build(&entries);
+    assert!(xml.contains(
+        "<url><loc>https://axodotdev.github.io/axolotlsay/</loc><lastmod>2023-06-01T12:00:00Z</lastmod></url>"
+    ));
+    assert!(xml.contains("<loc>https://axodotdev.github.io/axolotlsay/changelog/v0.1.0/</loc>"));
+
+    // Search engines only look for a robots.txt at the root of a domain
+    config.homepage = Some("https://axolotlsay.dev".to_owned());
+    config.path_prefix = None;
+    let robots = sitemap::build_robots(&config);
+    assert_eq!(
+        robots,
+        "User-agent: *\nDisallow: /changelog/\n\nSitemap: https://axolotlsay.dev/sitemap.xml\n"
+    );
+}
+
+#[test]
+fn it_finds_broken_links() {
+    let (dir, temp_dir) = temp_build_dir();
+    dir.child("index.html")
+        .write_str(concat!(
+            r#"<a href="/oranda/changelog/">changelog</a><a href="changelog/#v1">v1</a>"#,
+            r#"<a href="docs/foo.md">docs</a><a href="/oranda/changelog/#v2">v2</a>"#,
+            r#"<a href="/other/">other</a><img src="logo.png"/>"#,
+            r#"<a href="https://axodotdev.github.io/oranda/changelog/">full</a>"#,
+            r#"<a href="https://github.com/axodotdev/oranda">repo</a>"#,
+            r#"<a href="https://example.com">elsewhere</a><a href="mailto:hi@axo.dev">mail</a>"#,
+            r#"<script>if (a<b) { x = "<a href='nope'>"; }</script><!-- <a href="nope"> -->"#,
+        ))
+        .unwrap();
+    dir.child("changelog/index.html")
+        .write_str(r#"<h2 id="v1">v1</h2><a href="../">home</a><a href="../docs/foo.md">docs</a>"#)
+        .unwrap();
+    let mut config = oranda_config::snapshot(temp_dir, String::new());
+    config.path_prefix = Some("oranda".to_owned());
+    config.homepage = Some("https://axodotdev.github.io/oranda".to_owned());
+
+    let (pages, problems) = check::find_problems(&config).unwrap();
+    assert_eq!(pages, 2);
+    let problems: Vec<_> = problems
+        .iter()
+        .map(|problem| (problem.link.as_str(), &problem.kind, problem.pages.clone()))
+        .collect();
+    assert_eq!(
+        problems,
+        vec![
+            (
+                "../docs/foo.md",
+                &LinkProblemKind::NotFound,
+                vec!["changelog/index.html".to_owned()]
+            ),
+            (
+                "/oranda/changelog/#v2",
+                &LinkProblemKind::AnchorNotFound {
+                    page: "changelog/index.html".to_owned(),
+                    anchor: "v2".to_owned()
+                },
+                vec!["index.html".to_owned()]
+            ),
+            (
+                "/other/",
+                &LinkProblemKind::OutsidePrefix,
+                vec!["index.html".to_owned()]
+            ),
+            (
+                "docs/foo.md",
+                &LinkProblemKind::NotFound,
+                vec!["index.html".to_owned()]
+            ),
+            (
+                "logo.png",
+                &LinkProblemKind::NotFound,
+                vec!["index.html".to_owned()]
+            ),
+        ]
+    );
+
+    config.check.external_links = Some(vec!["https://github.com/*".to_owned()]);
+    let (_, problems) = check::find_problems(&config).unwrap();
+    let external: Vec<_> = problems
+        .iter()
+        .filter(|problem| problem.kind == LinkProblemKind::ExternalNotAllowed)
+        .map(|problem| problem.link.as_str())
+        .collect();
+    assert_eq!(external, vec!["https://example.com"]);
+}
+
+#[test]
+fn it_rewrites_relative_markdown_links() {
+    let _guard = TEST_RUNTIME.enter();
+    // Paths are relative to the project, which is this repository when running tests
+    let mut additional_pages = HashMap::new();
+    additional_pages.insert("Security".to_owned(), "./SECURITY.md".to_owned());
+    let config = Config {
+        readme_path: "README.md".to_owned(),
+        additional_pages: Some(additional_pages),
+        mdbook: Some(MdBookConfig {
+            path: Some("./docs".to_owned()),
+            theme: None,
+        }),
+        repository: Some("https://github.com/axodotdev/oranda".to_owned()),
+        path_prefix: Some("oranda".to_owned()),
+        ..Default::default()
+    };
+    let links = Arc::new(LinkRewriter::new(&config, "docs/src/introduction.md"));
+
+    let rewrite = |url| links.rewrite(url);
+    assert_eq!(rewrite("../../README.md"), Some("/oranda/".to_owned()));
+    assert_eq!(
+        rewrite("../../SECURITY.md#reporting"),
+        Some("/oranda/SECURITY/#reporting".to_owned())
+    );
+    assert_eq!(
+        rewrite("./cli/build.md#incremental-builds"),
+        Some("/oranda/book/cli/build.html#incremental-builds".to_owned())
+    );
+    assert_eq!(
+        rewrite("../../src/main.rs"),
+        Some("https://github.com/axodotdev/oranda/blob/HEAD/src/main.rs".to_owned())
+    );
+    assert_eq!(rewrite("../../nothing-here.md"), None);
+    assert_eq!(rewrite("../../../outside.md"), None);
+    assert_eq!(rewrite("https://example.com/README.md"), None);
+    assert_eq!(rewrite("#heading"), None);
+    assert_eq!(rewrite("/book/"), None);
+
+    let html = markdown::to_html_with_links(
+        "[security](../../SECURITY.md) ![logo](images/logo.png)",
+        &config.styles.syntax_theme(),
+        &links,
+    )
+    .unwrap();
+    assert!(html.contains(r#"<a href="/oranda/SECURITY/" rel="noopener noreferrer">security</a>"#));
+    // Images are left alone
+    assert!(html.contains(r#"src="images/logo.png""#));
+}
+
+#[test]
+fn it_collects_local_media() {
+    let (dir, temp_dir) = temp_build_dir();
+    dir.child("assets/demo.gif").write_str("GIF89a").unwrap();
+    let readme = format!("{temp_dir}/README.md");
+    let config = Config {
+        readme_path: readme.clone(),
+        path_prefix: Some("oranda".to_owned()),
+        ..Default::default()
+    };
+    let links = Arc::new(LinkRewriter::new(&config, &readme));
+
+    let html = markdown::to_html_with_links(
+        concat!(
+            "![demo](./assets/demo.gif) ![remote](https://example.com/demo.gif) ",
+            "![missing](missing.png)\n\n",
+            r#"<p align="center"><img src="assets/demo.gif" width="200"></p>"#
+        ),
+        &config.styles.syntax_theme(),
+        &links,
+    )
+    .unwrap();
+    let name = format!("demo.{}.gif", &incremental::hash(b"GIF89a")[..8]);
+    assert!(html.contains(&format!(r#"<img src="/oranda/media/{name}" alt="demo">"#)));
+    assert!(html.contains(&format!(r#"<img src="/oranda/media/{name}" width="200">"#)));
+    assert!(html.contains(r#"src="https://example.com/demo.gif""#));
+    assert!(html.contains(r#"src="missing.png""#));
+    assert_eq!(
+        links.take_media(),
+        vec![LocalMedia {
+            source: Utf8Path::new(&temp_dir).join("assets/demo.gif"),
+            dest: format!("media/{name}"),
+        }]
+    );
+}
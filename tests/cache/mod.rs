@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::{routing::get, Router};
+
+use super::utils::tokio_utils::TEST_RUNTIME;
+use oranda::config::{CacheConfig, Config};
+use oranda::data::cache::HttpCache;
+use oranda::errors::OrandaError;
+
+const ETAG: &str = "\"axolotl-v1\"";
+
+/// Spin up a server that serves one file with an ETag, returning its url and
+/// counters for (full responses, not-modified responses)
+fn serve_etagged_file() -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+    let full = Arc::new(AtomicUsize::new(0));
+    let not_modified = Arc::new(AtomicUsize::new(0));
+    let (full_count, not_modified_count) = (full.clone(), not_modified.clone());
+    let app = Router::new().route(
+        "/oranda.css",
+        get(move |headers: HeaderMap| async move {
+            if headers.get(header::IF_NONE_MATCH).map(|v| v.as_bytes()) == Some(ETAG.as_bytes()) {
+                not_modified_count.fetch_add(1, Ordering::SeqCst);
+                (
+                    StatusCode::NOT_MODIFIED,
+                    [(header::ETAG, ETAG)],
+                    String::new(),
+                )
+            } else {
+                full_count.fetch_add(1, Ordering::SeqCst);
+                (
+                    StatusCode::OK,
+                    [(header::ETAG, ETAG)],
+                    "body { }".to_owned(),
+                )
+            }
+        }),
+    );
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    TEST_RUNTIME.spawn(server);
+    (format!("http://{addr}/oranda.css"), full, not_modified)
+}
+
+fn cache_config(cache_dir: &assert_fs::TempDir, ttl: u64, offline: bool) -> Config {
+    Config {
+        cache: Some(CacheConfig {
+            dir: Some(cache_dir.to_str().unwrap().to_owned()),
+            ttl: Some(ttl),
+        }),
+        offline,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn it_revalidates_stale_entries() {
+    let _guard = TEST_RUNTIME.enter();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let (url, full, not_modified) = serve_etagged_file();
+    let cache = HttpCache::new(&cache_config(&cache_dir, 0, false));
+
+    let first = TEST_RUNTIME.block_on(cache.fetch(&url)).unwrap();
+    let second = TEST_RUNTIME.block_on(cache.fetch(&url)).unwrap();
+    assert_eq!(first, "body { }");
+    assert_eq!(second, "body { }");
+    assert_eq!(full.load(Ordering::SeqCst), 1);
+    assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn it_uses_fresh_entries_without_asking() {
+    let _guard = TEST_RUNTIME.enter();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let (url, full, not_modified) = serve_etagged_file();
+    let cache = HttpCache::new(&cache_config(&cache_dir, 300, false));
+
+    TEST_RUNTIME.block_on(cache.fetch(&url)).unwrap();
+    TEST_RUNTIME.block_on(cache.fetch(&url)).unwrap();
+    assert_eq!(full.load(Ordering::SeqCst), 1);
+    assert_eq!(not_modified.load(Ordering::SeqCst), 0);
+
+    // Even a stale entry should be served when we're offline
+    let offline_cache = HttpCache::new(&cache_config(&cache_dir, 0, true));
+    let offline = TEST_RUNTIME.block_on(offline_cache.fetch(&url)).unwrap();
+    assert_eq!(offline, "body { }");
+    assert_eq!(full.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn it_reports_offline_cache_misses() {
+    let _guard = TEST_RUNTIME.enter();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let cache = HttpCache::new(&cache_config(&cache_dir, 300, true));
+    let result = TEST_RUNTIME.block_on(cache.fetch("http://127.0.0.1:1/nothing-here"));
+    assert!(matches!(result, Err(OrandaError::OfflineCacheMiss { .. })));
+}
@@ -8,6 +8,32 @@ pub fn cargo_toml() -> &'static str {
     "#
 }
 
+pub fn workspace_cargo_toml() -> &'static str {
+    r#"
+[workspace]
+members = ["axolotlsay", "axolotlsay-internals"]
+    "#
+}
+
+pub fn member_cargo_toml(name: &str, publish: bool) -> String {
+    format!(
+        r#"
+[package]
+name = "{name}"
+version = "0.1.0"
+description = "the {name} package"
+repository = "https://github.com/axodotdev/axolotlsay"
+publish = {publish}
+    "#
+    )
+}
+
+pub fn lib_rs() -> &'static str {
+    r#"
+pub fn hello() {}
+    "#
+}
+
 pub fn main_rs() -> &'static str {
     r#"
 fn main() {
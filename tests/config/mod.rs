@@ -3,7 +3,11 @@ use super::utils::tokio_utils::TEST_RUNTIME;
 use camino::Utf8Path;
 use fixtures::project_config;
 
+use miette::Diagnostic;
 use oranda::config::project::ProjectConfig;
+use oranda::config::{Config, WorkspaceConfig, WorkspacePackageConfig};
+use oranda::errors::OrandaError;
+use oranda::site::oranda_theme::OrandaTheme;
 
 use assert_fs::fixture::{FileWriteStr, PathChild};
 
@@ -95,3 +99,416 @@ fn it_can_successfully_not_detect_a_project() {
         .close()
         .expect("could not successfully delete temporary directory");
 }
+
+#[test]
+fn it_loads_a_rust_workspace() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    tempdir
+        .child("Cargo.toml")
+        .write_str(project_config::workspace_cargo_toml())
+        .expect("failed to write cargo toml");
+    for (name, publish) in [("axolotlsay", true), ("axolotlsay-internals", false)] {
+        tempdir
+            .child(format!("{name}/Cargo.toml"))
+            .write_str(&project_config::member_cargo_toml(name, publish))
+            .expect("failed to write member cargo toml");
+        tempdir
+            .child(format!("{name}/src/lib.rs"))
+            .write_str(project_config::lib_rs())
+            .expect("failed to write lib.rs");
+    }
+
+    // The root isn't a package, so it's not a project on its own...
+    let project =
+        ProjectConfig::load(Some(tempdir.path().to_path_buf())).expect("failed to load Cargo.toml");
+    assert!(project.is_none());
+
+    // ...but its published packages are
+    let members = ProjectConfig::load_workspace(Some(tempdir.path().to_path_buf()))
+        .expect("failed to load workspace");
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].name, "axolotlsay");
+    assert_eq!(members[0].description, "the axolotlsay package");
+    tempdir
+        .close()
+        .expect("could not successfully delete temporary directory");
+}
+
+#[test]
+fn it_configures_workspace_members() {
+    let member = ProjectConfig {
+        name: "axolotlsay".to_owned(),
+        description: "the axolotlsay package".to_owned(),
+        homepage: None,
+        repository: Some("https://github.com/axodotdev/axolotlsay".to_owned()),
+        version: Some("0.1.0".to_owned()),
+        license: None,
+        readme_path: Some("axolotlsay/README.md".into()),
+        cargo_dist: Some(true),
+        manifest_path: "axolotlsay/Cargo.toml".into(),
+        embedded_configs: vec![],
+    };
+    let config = Config {
+        dist_dir: "public".to_owned(),
+        path_prefix: Some("oranda".to_owned()),
+        members: vec![member.clone()],
+        ..Default::default()
+    };
+    let member_config = config.for_member(&member);
+    assert_eq!(member_config.name, "axolotlsay");
+    assert_eq!(member_config.dist_dir, "public/axolotlsay");
+    assert_eq!(
+        member_config.path_prefix.as_deref(),
+        Some("oranda/axolotlsay")
+    );
+    assert_eq!(member_config.readme_path, "axolotlsay/README.md");
+    assert_eq!(
+        member_config.releases.tag_prefix.as_deref(),
+        Some("axolotlsay-v")
+    );
+    assert_eq!(
+        member_config.artifacts.app_name.as_deref(),
+        Some("axolotlsay")
+    );
+    assert!(member_config.artifacts.cargo_dist());
+    assert!(member_config.members.is_empty());
+    assert!(member_config.mdbook.is_none());
+
+    // Packages can override the defaults
+    let config = Config {
+        workspace: Some(WorkspaceConfig {
+            packages: Some(
+                [(
+                    "axolotlsay".to_owned(),
+                    WorkspacePackageConfig {
+                        tag_prefix: Some("v".to_owned()),
+                        readme_path: Some("docs/axolotlsay.md".to_owned()),
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..config
+    };
+    let member_config = config.for_member(&member);
+    assert_eq!(member_config.releases.tag_prefix.as_deref(), Some("v"));
+    assert_eq!(member_config.readme_path, "docs/axolotlsay.md");
+
+    // Packages without a README don't borrow the workspace's
+    let member = ProjectConfig {
+        readme_path: None,
+        ..member
+    };
+    let member_config = config.for_member(&member);
+    assert_eq!(member_config.readme_path, "docs/axolotlsay.md");
+    let config = Config {
+        workspace: None,
+        ..config
+    };
+    let member_config = config.for_member(&member);
+    assert_eq!(member_config.readme_path, "");
+}
+
+#[test]
+fn it_resolves_paths_relative_to_the_project_and_config() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    tempdir
+        .child("docs/book.toml")
+        .write_str("[book]\ntitle = \"axo\"\n")
+        .unwrap();
+    tempdir.child("funding.md").write_str("give").unwrap();
+    tempdir
+        .child("config/oranda.json")
+        .write_str(
+            r#"{
+                "readme_path": "../README.md",
+                "additional_pages": {"Extra": "pages/extra.md"},
+                "logo": "https://example.com/logo.png",
+                "styles": {"additional_css": ["custom.css"]},
+                "releases": {"snapshot": "/releases.json"}
+            }"#,
+        )
+        .unwrap();
+
+    let config_path = root.join("config/oranda.json");
+    let config = Config::build(root, Some(&config_path)).unwrap();
+    assert_eq!(config.dist_dir, root.join("public"));
+    assert_eq!(config.static_dir, root.join("static"));
+    assert_eq!(config.readme_path, root.join("config/../README.md"));
+    assert_eq!(
+        config.additional_pages.unwrap()["Extra"],
+        root.join("config/pages/extra.md")
+    );
+    assert_eq!(
+        config.styles.additional_css,
+        vec![root.join("config/custom.css")]
+    );
+    // URLs and absolute paths stay as they are
+    assert_eq!(config.logo.unwrap(), "https://example.com/logo.png");
+    assert_eq!(config.releases.snapshot.unwrap(), "/releases.json");
+    // Things we find ourselves are found in the project root
+    assert_eq!(config.mdbook.unwrap().path.unwrap(), root.join("./docs/"));
+    assert_eq!(
+        config.funding.unwrap().md_path.unwrap(),
+        root.join("./funding.md")
+    );
+
+    assert!(Config::build(&root.join("nope"), None).is_err());
+}
+
+#[test]
+fn it_loads_config_from_toml_and_manifests() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    tempdir
+        .child("oranda.toml")
+        .write_str("name = \"from toml\"\n[styles]\ntheme = \"hacker\"\n")
+        .unwrap();
+    let config = Config::build(root, None).unwrap();
+    assert_eq!(config.name, "from toml");
+    assert_eq!(config.styles.theme, Some(OrandaTheme::Hacker));
+    // Search is opt-in
+    assert!(!config.search);
+    std::fs::remove_file(root.join("oranda.toml")).unwrap();
+
+    let mut package_json: serde_json::Value =
+        serde_json::from_str(project_config::package_json()).unwrap();
+    package_json["oranda"] = serde_json::json!({ "name": "from package.json", "dist_dir": "site" });
+    tempdir
+        .child("package.json")
+        .write_str(&package_json.to_string())
+        .unwrap();
+    let config = Config::build(root, None).unwrap();
+    assert_eq!(config.name, "from package.json");
+    // Paths are relative to the manifest
+    assert_eq!(config.dist_dir, root.join("site"));
+
+    // Config in both places gets layered, with the config file winning
+    tempdir
+        .child("oranda.json")
+        .write_str(r#"{ "name": "from oranda.json" }"#)
+        .unwrap();
+    let config = Config::build(root, None).unwrap();
+    assert_eq!(config.name, "from oranda.json");
+    assert_eq!(config.dist_dir, root.join("site"));
+}
+
+#[test]
+fn it_loads_config_from_cargo_metadata() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    tempdir
+        .child("Cargo.toml")
+        .write_str(&format!(
+            "{}\n[package.metadata.oranda]\nname = \"from cargo\"\nadditional_pages = {{ Extra = \"extra.md\" }}\n[workspace]\n[workspace.metadata.oranda]\nname = \"from workspace\"\nno_header = true\n",
+            project_config::cargo_toml()
+        ))
+        .unwrap();
+    tempdir
+        .child("src/main.rs")
+        .write_str(project_config::main_rs())
+        .unwrap();
+
+    // The package's config goes on top of the workspace's
+    let config = Config::build(root, None).unwrap();
+    assert_eq!(config.name, "from cargo");
+    assert!(config.no_header);
+    assert_eq!(
+        config.additional_pages.unwrap()["Extra"],
+        root.join("extra.md")
+    );
+}
+
+#[test]
+fn it_reports_unknown_config_keys() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    let config = r#"{
+  "changlog": true,
+  "dist-dir": "site",
+  "md_book": false,
+  "additional_pages": { "Anything Goes": "extra.md" },
+  "styles": { "them": "dark" }
+}"#;
+    tempdir.child("oranda.json").write_str(config).unwrap();
+    let err = Config::build(root, None).unwrap_err();
+    let OrandaError::UnknownConfigKeys { problems, .. } = err else {
+        panic!("expected unknown keys, got {err:?}");
+    };
+    let found = problems
+        .iter()
+        .map(|problem| {
+            let span = problem.span.expect("JSON keys should have spans");
+            (
+                problem.key.as_str(),
+                &config[span.offset()..span.offset() + span.len()],
+                problem.help().map(|help| help.to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "changlog",
+                "\"changlog\"",
+                Some(String::from("Did you mean `changelog`?"))
+            ),
+            (
+                "dist-dir",
+                "\"dist-dir\"",
+                Some(String::from("Did you mean `dist_dir`?"))
+            ),
+            (
+                "styles.them",
+                "\"them\"",
+                Some(String::from("Did you mean `theme`?"))
+            ),
+        ]
+    );
+
+    // Fields that used to be silently dropped are applied now
+    tempdir
+        .child("oranda.json")
+        .write_str(r#"{ "no_header": true, "license": "MIT" }"#)
+        .unwrap();
+    let config = Config::build(root, None).unwrap();
+    assert!(config.no_header);
+    assert_eq!(config.license.as_deref(), Some("MIT"));
+}
+
+#[test]
+fn it_only_builds_a_sitemap_and_robots_txt_with_a_homepage() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    let config = |json: &str| {
+        tempdir.child("oranda.json").write_str(json).unwrap();
+        Config::build(root, None).unwrap()
+    };
+
+    let config_without_homepage = config("{}");
+    assert!(!config_without_homepage.sitemap);
+    assert!(config_without_homepage.robots.is_none());
+
+    let config_with_homepage = config(r#"{ "homepage": "https://axolotlsay.dev" }"#);
+    assert!(config_with_homepage.sitemap);
+    assert!(config_with_homepage.robots.is_some());
+
+    // Search engines won't look for a robots.txt under the path prefix
+    let config_with_prefix =
+        config(r#"{ "homepage": "https://axodotdev.github.io", "path_prefix": "axolotlsay" }"#);
+    assert!(config_with_prefix.sitemap);
+    assert!(config_with_prefix.robots.is_none());
+
+    // Asking for them anyway gets a warning when building instead
+    let config_asking = config(r#"{ "sitemap": true, "robots": true }"#);
+    assert!(config_asking.sitemap);
+    assert!(config_asking.robots.is_some());
+}
+
+#[test]
+fn it_tracks_where_config_values_came_from() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    tempdir
+        .child("package.json")
+        .write_str(project_config::package_json())
+        .unwrap();
+    tempdir
+        .child("oranda.json")
+        .write_str(
+            r#"{ "name": "from oranda.json", "no_header": false, "styles": { "theme": "hacker" } }"#,
+        )
+        .unwrap();
+    let (config, provenance) = Config::build_with_provenance(root, None).unwrap();
+    let oranda_json = root.join("oranda.json");
+    let package_json = root.join("package.json");
+
+    // The last layer to set a value gets the credit for it
+    assert_eq!(provenance.layer(&["name"]), Some(oranda_json.as_str()));
+    // Even if it set it to the default
+    assert_eq!(provenance.layer(&["no_header"]), Some(oranda_json.as_str()));
+    assert_eq!(
+        provenance.layer(&["styles", "theme"]),
+        Some(oranda_json.as_str())
+    );
+    assert_eq!(
+        provenance.layer(&["description"]),
+        Some(package_json.as_str())
+    );
+    assert_eq!(provenance.layer(&["dist_dir"]), Some("default"));
+
+    let toml = provenance.annotated_toml(&config);
+    assert!(toml.contains(&format!("name = \"from oranda.json\" # {oranda_json}")));
+    assert!(toml.contains(&format!("description = \">o_o<\" # {package_json}")));
+    let json = provenance.annotated_json(&config);
+    assert_eq!(
+        json["version"],
+        serde_json::json!({ "value": "0.1.0", "source": package_json })
+    );
+}
+
+#[test]
+fn it_extends_other_configs() {
+    let _guard = TEST_RUNTIME.enter();
+    let tempdir = assert_fs::TempDir::new().expect("failed creating tempdir");
+    let root = Utf8Path::from_path(tempdir.path()).expect("non-utf8 temp path");
+    tempdir
+        .child("shared/base.json")
+        .write_str(
+            r#"{
+  "name": "from base",
+  "styles": { "theme": "hacker" },
+  "additional_pages": { "Shared": "shared.md" }
+}"#,
+        )
+        .unwrap();
+    tempdir
+        .child("shared/more/social.toml")
+        .write_str("[social]\ntwitter_account = \"@axodotdev\"\n")
+        .unwrap();
+    tempdir
+        .child("oranda.json")
+        .write_str(
+            r#"{
+  "extends": ["shared/base.json", "shared/more"],
+  "name": "from project",
+  "additional_pages": { "Mine": "mine.md" }
+}"#,
+        )
+        .unwrap();
+    let config = Config::build(root, None).unwrap();
+    // The config's own values win over the ones it extends
+    assert_eq!(config.name, "from project");
+    assert_eq!(config.styles.theme, Some(OrandaTheme::Hacker));
+    let social = config.social.unwrap();
+    assert_eq!(social.twitter_account.as_deref(), Some("@axodotdev"));
+    // Maps are merged, and paths are relative to the config they're in
+    let pages = config.additional_pages.unwrap();
+    assert_eq!(pages["Shared"], root.join("shared/shared.md"));
+    assert_eq!(pages["Mine"], root.join("mine.md"));
+    assert_eq!(
+        config.extended_configs,
+        vec![
+            root.join("shared/base.json"),
+            root.join("shared/more/social.toml")
+        ]
+    );
+
+    tempdir
+        .child("shared/base.json")
+        .write_str(r#"{ "extends": "../oranda.json" }"#)
+        .unwrap();
+    let err = Config::build(root, None).unwrap_err();
+    assert!(matches!(err, OrandaError::ConfigExtendsCycle { .. }));
+}
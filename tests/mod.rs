@@ -1,4 +1,6 @@
 pub mod utils;
 
 mod build;
+mod cache;
 mod config;
+mod source;
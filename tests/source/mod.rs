@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{routing::get, Router};
+
+use super::utils::tokio_utils::TEST_RUNTIME;
+use oranda::config::{Config, LatestReleaseKind, ReleaseSourceKind, ReleasesConfig};
+use oranda::data::cache::HttpCache;
+use oranda::data::github::GithubRepo;
+use oranda::data::source::{GiteaRepo, GitlabRepo, Repo};
+use oranda::data::Context;
+use oranda::errors::OrandaError;
+use oranda::site::changelog;
+
+/// Spin up a server for a router, returning its base url
+///
+/// The router is built from the base url, so responses can point back at the server.
+fn serve(app: impl FnOnce(&str) -> Router) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app(&base).into_make_service());
+    TEST_RUNTIME.spawn(server);
+    base
+}
+
+fn uncached() -> HttpCache {
+    HttpCache::new(&Config {
+        cache: None,
+        ..Default::default()
+    })
+}
+
+/// A cache that authenticates with GitHub using the given token
+fn authenticated(var: &str, token: &str) -> HttpCache {
+    std::env::set_var(var, token);
+    HttpCache::new(&Config {
+        cache: None,
+        releases: ReleasesConfig {
+            token_env: Some(var.to_owned()),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+fn github_repo(base: &str) -> GithubRepo {
+    GithubRepo {
+        owner: "axodotdev".to_owned(),
+        name: "axolotlsay".to_owned(),
+        api_url: base.to_owned(),
+    }
+}
+
+fn github_release(base: &str, tag: &str) -> serde_json::Value {
+    serde_json::json!({
+        "url": format!("{base}/repos/axodotdev/axolotlsay/releases/1"),
+        "assets_url": format!("{base}/repos/axodotdev/axolotlsay/releases/1/assets"),
+        "html_url": format!("https://github.com/axodotdev/axolotlsay/releases/tag/{tag}"),
+        "id": 1,
+        "tag_name": tag,
+        "target_commitish": "main",
+        "name": tag,
+        "draft": false,
+        "prerelease": false,
+        "created_at": "2023-06-01T00:00:00Z",
+        "published_at": "2023-06-01T00:00:00Z",
+        "assets": [],
+        "tarball_url": format!("{base}/tarball/{tag}"),
+        "zipball_url": format!("{base}/zipball/{tag}"),
+        "body": null
+    })
+}
+
+fn gitlab_releases(base: &str) -> Vec<serde_json::Value> {
+    vec![
+        serde_json::json!({
+            "tag_name": "v0.2.0-beta.1",
+            "name": "v0.2.0-beta.1",
+            "description": "Trying out more axolotls.",
+            "created_at": "2023-06-02T00:00:00Z",
+            "released_at": "2023-06-02T00:00:00Z",
+            "upcoming_release": false,
+            "commit": { "id": "b0b" },
+            "assets": { "sources": [], "links": [] }
+        }),
+        serde_json::json!({
+            "tag_name": "v0.1.0",
+            "name": "v0.1.0",
+            "description": "Initial release.",
+            "created_at": "2023-06-01T00:00:00Z",
+            "released_at": "2023-06-01T00:00:00Z",
+            "upcoming_release": false,
+            "commit": { "id": "a1a" },
+            "assets": {
+                "sources": [
+                    {
+                        "format": "tar.gz",
+                        "url": format!("{base}/group/sub/axolotlsay/-/archive/v0.1.0/axolotlsay-v0.1.0.tar.gz")
+                    }
+                ],
+                "links": [
+                    {
+                        "id": 1,
+                        "name": "axolotlsay-installer.sh",
+                        "url": format!("{base}/links/1"),
+                        "direct_asset_url": format!("{base}/group/sub/axolotlsay/-/releases/v0.1.0/downloads/axolotlsay-installer.sh"),
+                        "link_type": "other"
+                    }
+                ]
+            }
+        }),
+    ]
+}
+
+fn gitea_releases(base: &str) -> String {
+    serde_json::json!([
+        {
+            "id": 7,
+            "tag_name": "v0.1.0",
+            "target_commitish": "main",
+            "name": "v0.1.0",
+            "body": "Initial release.",
+            "url": format!("{base}/api/v1/repos/axodotdev/axolotlsay/releases/7"),
+            "html_url": format!("{base}/axodotdev/axolotlsay/releases/tag/v0.1.0"),
+            "tarball_url": format!("{base}/axodotdev/axolotlsay/archive/v0.1.0.tar.gz"),
+            "zipball_url": format!("{base}/axodotdev/axolotlsay/archive/v0.1.0.zip"),
+            "draft": false,
+            "prerelease": false,
+            "created_at": "2023-06-01T00:00:00Z",
+            "published_at": "2023-06-01T00:00:00Z",
+            "assets": [
+                {
+                    "id": 3,
+                    "name": "axolotlsay-installer.sh",
+                    "size": 42,
+                    "download_count": 9,
+                    "created_at": "2023-06-01T00:00:00Z",
+                    "uuid": "abc",
+                    "browser_download_url": format!("{base}/attachments/abc")
+                }
+            ]
+        }
+    ])
+    .to_string()
+}
+
+#[test]
+fn it_parses_nested_gitlab_urls() {
+    let repo = GitlabRepo::from_url("https://gitlab.example.com/group/sub/axolotlsay/-/tree/main")
+        .unwrap();
+    assert_eq!(repo.path, "group/sub/axolotlsay");
+    assert_eq!(repo.api_url, "https://gitlab.example.com/api/v4");
+    assert_eq!(
+        repo.web_url,
+        "https://gitlab.example.com/group/sub/axolotlsay"
+    );
+}
+
+#[test]
+fn it_parses_gitea_urls_with_subpaths() {
+    let repo = GiteaRepo::from_url("https://example.com/git/axodotdev/axolotlsay.git").unwrap();
+    assert_eq!(repo.owner, "axodotdev");
+    assert_eq!(repo.name, "axolotlsay");
+    assert_eq!(repo.api_url, "https://example.com/git/api/v1");
+}
+
+#[test]
+fn it_guesses_the_source_from_the_host() {
+    let gitlab = Repo::from_url("https://gitlab.com/group/sub/axolotlsay", None).unwrap();
+    assert!(matches!(gitlab, Repo::Gitlab(_)));
+    let forgejo = Repo::from_url("https://codeberg.org/axodotdev/axolotlsay", None).unwrap();
+    assert!(matches!(forgejo, Repo::Gitea(_)));
+    let github = Repo::from_url("https://github.com/axodotdev/axolotlsay", None).unwrap();
+    assert!(matches!(github, Repo::Github(_)));
+    let gitea = Repo::from_url(
+        "https://git.example.com/axodotdev/axolotlsay",
+        Some(ReleaseSourceKind::Gitea),
+    )
+    .unwrap();
+    assert!(matches!(gitea, Repo::Gitea(_)));
+}
+
+#[test]
+fn it_fetches_gitlab_releases() {
+    let _guard = TEST_RUNTIME.enter();
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+    let base = serve(|base| {
+        // One release per page, with only GitLab's `X-Next-Page` header to go on
+        let releases = gitlab_releases(base);
+        Router::new().route(
+            "/api/v4/projects/:project/releases",
+            get(
+                move |Path(project): Path<String>,
+                      Query(query): Query<HashMap<String, String>>| async move {
+                    tx.send(project).unwrap();
+                    let page = query.get("page").map(|p| p.as_str()).unwrap_or("1");
+                    let (body, next) = match page {
+                        "1" => (&releases[0], "2"),
+                        _ => (&releases[1], ""),
+                    };
+                    let body = serde_json::json!([body]).to_string();
+                    ([("x-next-page", next.to_owned())], body)
+                },
+            ),
+        )
+    });
+
+    let repo = GitlabRepo::from_url(&format!("{base}/group/sub/axolotlsay")).unwrap();
+    let context = Context::fetch_all_releases(repo, &Config::default(), &uncached()).unwrap();
+    assert_eq!(rx.recv().unwrap(), "group/sub/axolotlsay");
+
+    assert_eq!(context.releases.len(), 2);
+    assert!(context.has_prereleases);
+    assert!(context.releases[0].source.prerelease);
+    let latest = context.latest().unwrap();
+    assert_eq!(latest.source.id, None);
+    assert_eq!(latest.source.tag_name, "v0.1.0");
+    assert_eq!(latest.source.body.as_deref(), Some("Initial release."));
+    assert_eq!(
+        latest.source.html_url,
+        format!("{base}/group/sub/axolotlsay/-/releases/v0.1.0")
+    );
+    let installer_url =
+        format!("{base}/group/sub/axolotlsay/-/releases/v0.1.0/downloads/axolotlsay-installer.sh");
+    assert_eq!(
+        latest.source.asset_url("axolotlsay-installer.sh"),
+        Some(installer_url.as_str())
+    );
+    assert!(latest
+        .source
+        .tarball_url
+        .ends_with("axolotlsay-v0.1.0.tar.gz"));
+}
+
+#[test]
+fn it_fetches_gitea_releases() {
+    let _guard = TEST_RUNTIME.enter();
+    let base = serve(|base| {
+        let base = base.to_owned();
+        let body = gitea_releases(&base);
+        Router::new().route(
+            "/api/v1/repos/axodotdev/axolotlsay/releases",
+            get(
+                move |Query(query): Query<HashMap<String, String>>| async move {
+                    if query.get("page").map(|p| p.as_str()) == Some("2") {
+                        (HeaderMap::new(), "[]".to_owned())
+                    } else {
+                        let next = format!(
+                            "<{base}/api/v1/repos/axodotdev/axolotlsay/releases?limit=50&page=2>; rel=\"next\""
+                        );
+                        let mut headers = HeaderMap::new();
+                        headers.insert(header::LINK, next.parse().unwrap());
+                        (headers, body)
+                    }
+                },
+            ),
+        )
+    });
+
+    let repo = GiteaRepo::from_url(&format!("{base}/axodotdev/axolotlsay")).unwrap();
+    let context = Context::fetch_all_releases(repo, &Config::default(), &uncached()).unwrap();
+
+    assert_eq!(context.releases.len(), 1);
+    assert!(!context.has_prereleases);
+    assert!(matches!(context.repo, Repo::Gitea(_)));
+    let latest = context.latest().unwrap();
+    assert_eq!(latest.source.id, Some(7));
+    let installer_url = format!("{base}/attachments/abc");
+    assert_eq!(
+        latest.source.asset_url("axolotlsay-installer.sh"),
+        Some(installer_url.as_str())
+    );
+}
+
+#[test]
+fn it_reports_failed_fetches() {
+    let _guard = TEST_RUNTIME.enter();
+    let base = serve(|_| Router::new());
+    let repo = GiteaRepo::from_url(&format!("{base}/axodotdev/axolotlsay")).unwrap();
+    let result = Context::fetch_all_releases(repo, &Config::default(), &uncached());
+    assert!(matches!(
+        result,
+        Err(OrandaError::ReleasesFetchError { .. })
+    ));
+}
+
+#[test]
+fn it_pages_through_authenticated_github_releases() {
+    let _guard = TEST_RUNTIME.enter();
+    let (tx, rx) = std::sync::mpsc::channel::<Option<String>>();
+    let base = serve(|base| {
+        let base = base.to_owned();
+        Router::new().route(
+            "/repos/axodotdev/axolotlsay/releases",
+            get(
+                move |Query(query): Query<HashMap<String, String>>, headers: HeaderMap| async move {
+                    let auth = headers
+                        .get(header::AUTHORIZATION)
+                        .map(|v| v.to_str().unwrap().to_owned());
+                    tx.send(auth).unwrap();
+                    if query.get("page").map(|p| p.as_str()) == Some("2") {
+                        let body = serde_json::json!([github_release(&base, "v0.1.0")]);
+                        (HeaderMap::new(), body.to_string())
+                    } else {
+                        let next = format!(
+                            "<{base}/repos/axodotdev/axolotlsay/releases?per_page=100&page=2>; rel=\"next\", <{base}/repos/axodotdev/axolotlsay/releases?per_page=100&page=2>; rel=\"last\""
+                        );
+                        let mut headers = HeaderMap::new();
+                        headers.insert(header::LINK, next.parse().unwrap());
+                        let body = serde_json::json!([github_release(&base, "v0.2.0")]);
+                        (headers, body.to_string())
+                    }
+                },
+            ),
+        )
+    });
+
+    let cache = authenticated("ORANDA_TEST_PAGING_TOKEN", "axolotl-token");
+    let context =
+        Context::fetch_all_releases(github_repo(&base), &Config::default(), &cache).unwrap();
+    let tags = context
+        .releases
+        .iter()
+        .map(|r| r.source.tag_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["v0.2.0", "v0.1.0"]);
+    for _ in 0..2 {
+        assert_eq!(rx.recv().unwrap().as_deref(), Some("Bearer axolotl-token"));
+    }
+}
+
+#[test]
+fn it_reports_github_rate_limits() {
+    let _guard = TEST_RUNTIME.enter();
+    let base = serve(|_| {
+        Router::new().route(
+            "/repos/axodotdev/axolotlsay/releases",
+            get(|| async {
+                (
+                    StatusCode::FORBIDDEN,
+                    [
+                        ("x-ratelimit-remaining", "0"),
+                        ("x-ratelimit-reset", "1700000000"),
+                    ],
+                    "API rate limit exceeded",
+                )
+                    .into_response()
+            }),
+        )
+    });
+
+    let cache = authenticated("ORANDA_TEST_RATE_LIMIT_TOKEN", "axolotl-token");
+    let result = Context::fetch_all_releases(github_repo(&base), &Config::default(), &cache);
+    match result {
+        Err(OrandaError::GithubRateLimit {
+            reset,
+            authenticated,
+        }) => {
+            assert_eq!(reset, "2023-11-14 22:13:20 UTC");
+            assert!(authenticated);
+        }
+        Err(e) => panic!("expected a rate limit error, got {e:?}"),
+        Ok(_) => panic!("expected a rate limit error"),
+    }
+}
+
+#[test]
+fn it_sorts_filtered_releases_by_version() {
+    let _guard = TEST_RUNTIME.enter();
+    let base = serve(|base| {
+        // Newest first, like the API: a backport of 0.9 came out after 0.10
+        let body = serde_json::json!([
+            github_release(base, "css-v0.0.7"),
+            github_release(base, "v0.9.1"),
+            github_release(base, "v0.10.0"),
+            github_release(base, "v0.9.0"),
+        ])
+        .to_string();
+        Router::new().route(
+            "/repos/axodotdev/axolotlsay/releases",
+            get(move || async move { body }),
+        )
+    });
+
+    let config = Config {
+        releases: ReleasesConfig {
+            exclude_tags: Some(vec!["css-v*".to_owned()]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let context = Context::fetch_all_releases(github_repo(&base), &config, &uncached()).unwrap();
+    let tags = context
+        .releases
+        .iter()
+        .map(|r| r.source.tag_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["v0.10.0", "v0.9.1", "v0.9.0"]);
+    assert_eq!(context.latest().unwrap().source.tag_name, "v0.10.0");
+}
+
+#[test]
+fn it_picks_the_latest_release_by_priority() {
+    let _guard = TEST_RUNTIME.enter();
+    let base = serve(|base| {
+        let mut beta = github_release(base, "v2.0.0-beta.1");
+        beta["prerelease"] = true.into();
+        let body = serde_json::json!([
+            beta,
+            github_release(base, "v1.4.1"),
+            github_release(base, "v0.9.0"),
+            github_release(base, "v1.5.0"),
+        ])
+        .to_string();
+        Router::new().route(
+            "/repos/axodotdev/axolotlsay/releases",
+            get(move || async move { body }),
+        )
+    });
+
+    let context =
+        Context::fetch_all_releases(github_repo(&base), &Config::default(), &uncached()).unwrap();
+    assert_eq!(context.latest().unwrap().source.tag_name, "v1.5.0");
+    let lines = context
+        .release_lines
+        .iter()
+        .map(|line| {
+            (
+                line.name.as_str(),
+                context.releases[line.latest_release]
+                    .source
+                    .tag_name
+                    .as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            ("2.x", "v2.0.0-beta.1"),
+            ("1.x", "v1.5.0"),
+            ("0.9.x", "v0.9.0")
+        ]
+    );
+    let changelog = changelog::build(&context, &Config::default()).unwrap();
+    assert!(changelog.contains("Release lines"));
+
+    let config = Config {
+        releases: ReleasesConfig {
+            latest_priority: Some(vec![
+                LatestReleaseKind::Prerelease,
+                LatestReleaseKind::Stable,
+            ]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let context = Context::fetch_all_releases(github_repo(&base), &config, &uncached()).unwrap();
+    assert_eq!(context.latest().unwrap().source.tag_name, "v2.0.0-beta.1");
+}
+
+#[test]
+fn it_only_fetches_checksums_for_releases_with_install_pages() {
+    let _guard = TEST_RUNTIME.enter();
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+    let base = serve(|base| {
+        let releases = ["v0.3.0", "v0.2.0", "v0.1.0"]
+            .into_iter()
+            .map(|tag| {
+                let mut release = github_release(base, tag);
+                let asset = |name: &str| {
+                    let url = format!("{base}/download/{tag}/{name}");
+                    serde_json::json!({
+                        "url": url,
+                        "id": 1,
+                        "node_id": "",
+                        "name": name,
+                        "label": null,
+                        "content_type": "application/octet-stream",
+                        "state": "uploaded",
+                        "size": 0,
+                        "download_count": 0,
+                        "created_at": "2023-06-01T00:00:00Z",
+                        "updated_at": "2023-06-01T00:00:00Z",
+                        "browser_download_url": url
+                    })
+                };
+                release["assets"] = serde_json::json!([
+                    asset("axolotlsay-x86_64-unknown-linux-gnu.tar.gz"),
+                    asset("SHA256SUMS"),
+                ]);
+                release
+            })
+            .collect::<Vec<_>>();
+        let body = serde_json::json!(releases).to_string();
+        Router::new()
+            .route(
+                "/repos/axodotdev/axolotlsay/releases",
+                get(move || async move { body }),
+            )
+            .route(
+                "/download/:tag/SHA256SUMS",
+                get(move |Path(tag): Path<String>| async move {
+                    tx.send(tag).unwrap();
+                    "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08  axolotlsay-x86_64-unknown-linux-gnu.tar.gz\n"
+                }),
+            )
+    });
+
+    let mut config = Config::default();
+    config.artifacts.versioned_pages = Some(2);
+    let context = Context::fetch_all_releases(github_repo(&base), &config, &uncached()).unwrap();
+    let mut fetched = rx.try_iter().collect::<Vec<_>>();
+    fetched.sort();
+    assert_eq!(fetched, vec!["v0.2.0", "v0.3.0"]);
+    let has_checksum = |idx: usize| {
+        context.releases[idx]
+            .artifacts
+            .files()
+            .any(|file| file.checksum.is_some())
+    };
+    assert!(has_checksum(0));
+    assert!(!has_checksum(2));
+}